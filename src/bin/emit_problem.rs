@@ -1,66 +1,172 @@
-use std::io::{stdout, BufWriter};
+use std::fs::File;
+use std::io::{stdout, BufReader, BufWriter, Write};
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use bitint::prelude::*;
-use eternity_ii::sat::{Clauses, Literal, Variable};
-use eternity_ii::{hints, Color, RotatedTile, Rotation, Side, Tile};
-use strum::IntoEnumIterator;
+use eternity_ii::sat::{
+    emit_seam_color_counts, Clauses, FullVariable as Variable, InstanceManifest, Literal,
+};
+use eternity_ii::tile_set::{TileColors, TileSet};
+use eternity_ii::{
+    forced_edge_colors, hints, symmetry_break, Clue, Color, RotatedTile, Rotation, Side, Tile,
+    TileClass,
+};
+
+/// Returns the argument following `--name` on the command line, if present.
+fn arg_string(name: &str) -> Result<Option<String>> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return Ok(Some(
+                args.next().ok_or_else(|| anyhow!("{name} needs a value"))?,
+            ));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the number following `--name` on the command line, if present.
+fn arg_value(name: &str) -> Result<Option<usize>> {
+    Ok(match arg_string(name)? {
+        Some(value) => Some(value.parse()?),
+        None => None,
+    })
+}
+
+/// Returns the tiles used for a `width` by `height` subgrid: the lowest-numbered
+/// corner, edge, and center tiles in the numbers the subgrid's perimeter and
+/// interior call for, with the tiles from `clues` taking the first center
+/// slots. For the full board, that's every tile.
+fn subgrid_tiles(tile_set: &TileSet, width: usize, height: usize, clues: &[Clue]) -> Vec<Tile> {
+    let class = |tile| {
+        tile_set.class(RotatedTile {
+            tile,
+            rotation: Rotation::Identity,
+        })
+    };
+    let clue_tiles = Vec::from_iter(clues.iter().map(|&(_, _, rotated_tile)| rotated_tile.tile));
+    let mut tiles = clue_tiles.clone();
+    for (tile_class, count) in [
+        (TileClass::Corner, 4),
+        (TileClass::Edge, 2 * (width - 2) + 2 * (height - 2)),
+        (TileClass::Center, (width - 2) * (height - 2)),
+    ] {
+        let already_chosen = tiles
+            .iter()
+            .filter(|&&tile| class(tile) == tile_class)
+            .count();
+        tiles.extend(
+            tile_set
+                .tiles()
+                .into_iter()
+                .filter(|tile| class(*tile) == tile_class && !clue_tiles.contains(tile))
+                .take(count.saturating_sub(already_chosen)),
+        );
+    }
+    tiles.sort();
+    tiles
+}
 
 #[bitint_literals]
 fn main() -> Result<()> {
+    let annotate = std::env::args().skip(1).any(|arg| arg == "--annotate");
+    let hint_free = std::env::args()
+        .skip(1)
+        .any(|arg| arg == "--symmetry-break");
+    let smt2 = match arg_string("--format")?.as_deref() {
+        None | Some("dimacs") => false,
+        Some("smt2") => true,
+        Some(format) => bail!("unknown --format {format:?}, expected dimacs or smt2"),
+    };
+    if smt2 && annotate {
+        bail!("--annotate only applies to --format dimacs");
+    }
+    let groups_path = arg_string("--groups")?;
+    let adjacency = !std::env::args().skip(1).any(|arg| arg == "--no-adjacency");
+    let seam_counts = std::env::args().skip(1).any(|arg| arg == "--seam-counts");
+    let width = arg_value("--width")?.unwrap_or(16);
+    let height = arg_value("--height")?.unwrap_or(16);
+    if !(2..=16).contains(&width) || !(2..=16).contains(&height) {
+        bail!("--width and --height must be between 2 and 16");
+    }
+    if seam_counts && (width, height) != (16, 16) {
+        bail!("--seam-counts only applies to the full board");
+    }
+    if seam_counts && !adjacency {
+        bail!("--seam-counts needs the edge implications left out by --no-adjacency");
+    }
+    let tiles_path = arg_string("--tiles")?;
+    if tiles_path.is_some() && hint_free {
+        bail!("--symmetry-break pins a built-in tile, so it doesn't apply with --tiles");
+    }
+    if tiles_path.is_some() && seam_counts {
+        bail!("--seam-counts counts the built-in tiles' colors, so it doesn't apply with --tiles");
+    }
+    let tile_set = match &tiles_path {
+        Some(path) => TileSet::from_reader(BufReader::new(File::open(path)?))?,
+        None => TileSet::eternity_ii(),
+    };
     let mut clauses = Clauses::default();
 
-    // Assign the tile placements known from published clues.
-    for (x, y, rotated_tile) in hints() {
+    // Assign the tile placements known from published clues, or for the
+    // hint-free puzzle, just enough to break the board's rotational symmetry.
+    // In a subgrid, only clues in its interior are kept. A loaded tile set has
+    // no clues.
+    clauses.begin_section("clue tile placements");
+    let clues = if tiles_path.is_some() {
+        Vec::new()
+    } else if hint_free {
+        Vec::from_iter(symmetry_break())
+    } else {
+        Vec::from_iter(hints().filter(|&(x, y, _)| {
+            let (x, y) = (x.to_primitive() as usize, y.to_primitive() as usize);
+            (1..width - 1).contains(&x) && (1..height - 1).contains(&y)
+        }))
+    };
+    let tiles = subgrid_tiles(&tile_set, width, height, &clues);
+    if tiles.len() != width * height {
+        bail!(
+            "the tile set has {} tiles of the classes a {width}x{height} board needs",
+            tiles.len(),
+        );
+    }
+    let rotated_tiles = Vec::from_iter(
+        RotatedTile::values().filter(|rotated_tile| tiles.contains(&rotated_tile.tile)),
+    );
+    for &(x, y, rotated_tile) in &clues {
         clauses.push_unit(Literal::positive(Variable::for_tile_placement(
             x,
             y,
             rotated_tile,
         )));
-        if x < 15_U4 {
-            clauses.push_unit(Literal::positive(Variable::for_right_edge_color(
-                x,
-                y,
-                rotated_tile.color(Side::Right),
-            )));
-        }
-        if y < 15_U4 {
-            clauses.push_unit(Literal::positive(Variable::for_bottom_edge_color(
-                x,
-                y,
-                rotated_tile.color(Side::Bottom),
-            )));
-        }
     }
 
     // One rotated tile per cell.
-    for y in 0..16 {
-        for x in 0..16 {
-            let mut variables = Vec::new();
-            for tile in Tile::values() {
-                for rotation in Rotation::iter() {
-                    variables.push(Variable::for_tile_placement(
-                        U4::new_masked(x),
-                        U4::new_masked(y),
-                        RotatedTile { tile, rotation },
-                    ));
-                }
-            }
-            clauses.emit_at_most_one_of(&variables);
-            clauses.emit_at_least_one_of(&variables);
+    clauses.begin_section("one tile per cell");
+    clauses.emit_regions(0..height, |clauses, y| {
+        for x in 0..width {
+            let variables = Vec::from_iter(rotated_tiles.iter().map(|&rotated_tile| {
+                Variable::for_tile_placement(
+                    U4::new_masked(x as u8),
+                    U4::new_masked(y as u8),
+                    rotated_tile,
+                )
+            }));
+            clauses.emit_exactly_one_of(&variables);
         }
-    }
+    });
 
     // One use for each tile.
-    for tile in Tile::values() {
+    clauses.begin_section("one use per tile");
+    clauses.emit_regions(&tiles, |clauses, &tile| {
         let mut variables = Vec::new();
-        for y in 0..16 {
-            for x in 0..16 {
-                for rotation in Rotation::iter() {
+        for y in 0..height {
+            for x in 0..width {
+                for rotated_tile in tile.rotations() {
                     variables.push(Variable::for_tile_placement(
-                        U4::new_masked(x),
-                        U4::new_masked(y),
-                        RotatedTile { tile, rotation },
+                        U4::new_masked(x as u8),
+                        U4::new_masked(y as u8),
+                        rotated_tile,
                     ));
                 }
             }
@@ -68,19 +174,110 @@ fn main() -> Result<()> {
         clauses.emit_at_most_one_of(&variables);
         // No need to emit_at_least_one_of() here. The above constraint for one rotated tile per
         // cell already ensures by the pigeonhole principle that all tiles are placed.
+    });
+
+    // Rule out exterior edges inside the subgrid.
+    clauses.begin_section("no interior exterior edges");
+    for y in 0..height {
+        for x in 0..width {
+            for &rotated_tile in &rotated_tiles {
+                let is_exterior =
+                    |side| tile_set.rotated_color(rotated_tile, side) == Color::EXTERIOR;
+                if (x < width - 1 && is_exterior(Side::Right))
+                    || (y > 0 && is_exterior(Side::Top))
+                    || (x > 0 && is_exterior(Side::Left))
+                    || (y < height - 1 && is_exterior(Side::Bottom))
+                {
+                    clauses.push_unit(Literal::negative(Variable::for_tile_placement(
+                        U4::new_masked(x as u8),
+                        U4::new_masked(y as u8),
+                        rotated_tile,
+                    )));
+                }
+            }
+        }
+    }
+
+    // Rule out top and bottom edges on the perimeter that aren't gray.
+    clauses.begin_section("exterior top and bottom perimeter");
+    let (right, bottom) = (
+        U4::new_masked(width as u8 - 1),
+        U4::new_masked(height as u8 - 1),
+    );
+    for x in 0..width {
+        let x = U4::new_masked(x as u8);
+        for &rotated_tile in &rotated_tiles {
+            if tile_set.rotated_color(rotated_tile, Side::Top) != Color::EXTERIOR {
+                clauses.push_unit(Literal::negative(Variable::for_tile_placement(
+                    x,
+                    0_U4,
+                    rotated_tile,
+                )));
+            }
+            if tile_set.rotated_color(rotated_tile, Side::Bottom) != Color::EXTERIOR {
+                clauses.push_unit(Literal::negative(Variable::for_tile_placement(
+                    x,
+                    bottom,
+                    rotated_tile,
+                )));
+            }
+        }
     }
 
-    // Imply right edge colors for tile placements.
-    for y in 0..16 {
-        let y = U4::new_masked(y);
-        for x in 0..15 {
-            let x = U4::new_masked(x);
-            for tile in Tile::values() {
-                for rotation in Rotation::iter() {
-                    let rotated_tile = RotatedTile { tile, rotation };
+    // Rule out left and right edges on the perimeter that aren't gray.
+    clauses.begin_section("exterior left and right perimeter");
+    for y in 0..height {
+        let y = U4::new_masked(y as u8);
+        for &rotated_tile in &rotated_tiles {
+            if tile_set.rotated_color(rotated_tile, Side::Left) != Color::EXTERIOR {
+                clauses.push_unit(Literal::negative(Variable::for_tile_placement(
+                    0_U4,
+                    y,
+                    rotated_tile,
+                )));
+            }
+            if tile_set.rotated_color(rotated_tile, Side::Right) != Color::EXTERIOR {
+                clauses.push_unit(Literal::negative(Variable::for_tile_placement(
+                    right,
+                    y,
+                    rotated_tile,
+                )));
+            }
+        }
+    }
 
+    // Every clause above involves only tile placement variables. Every clause
+    // below involves edge color variables, so solvers that benefit from
+    // grouped variables see each kind together.
+
+    // Assign the edge colors forced by the clues. Left and top sides are the
+    // right and bottom sides of a neighbor, which are also reported.
+    clauses.begin_section("edge colors forced by clues");
+    for ((x, y), side, color) in forced_edge_colors(&clues) {
+        match side {
+            Side::Right => clauses.push_unit(Literal::positive(Variable::for_right_edge_color(
+                x, y, color,
+            ))),
+            Side::Bottom => clauses.push_unit(Literal::positive(Variable::for_bottom_edge_color(
+                x, y, color,
+            ))),
+            Side::Top | Side::Left => (),
+        }
+    }
+
+    // Tie tile placements to edge colors, which is what makes neighbors match.
+    // Without these, any placement that respects the perimeter and clues
+    // satisfies the problem.
+    if adjacency {
+        // Imply right edge colors for tile placements.
+        clauses.begin_section("right edge implications");
+        clauses.emit_regions(0..height, |clauses, y| {
+            let y = U4::new_masked(y as u8);
+            for x in 0..width - 1 {
+                let x = U4::new_masked(x as u8);
+                for &rotated_tile in &rotated_tiles {
                     // Right edge of the tile at (x, y).
-                    let color = rotated_tile.color(Side::Right);
+                    let color = tile_set.rotated_color(rotated_tile, Side::Right);
                     if color.is_valid_non_border_color() {
                         // placed(x, y, rotated_tile) -> right_edge_color(x, y, color)
                         clauses.push_binary(
@@ -104,18 +301,10 @@ fn main() -> Result<()> {
                                 );
                             }
                         }
-                    } else {
-                        // Can't place a gray edge in the middle of the board.
-                        // -placed(x, y, rotated_tile)
-                        clauses.push_unit(Literal::negative(Variable::for_tile_placement(
-                            x,
-                            y,
-                            rotated_tile,
-                        )));
                     }
 
                     // Left edge of the tile at (x+1, y).
-                    let color = rotated_tile.color(Side::Left);
+                    let color = tile_set.rotated_color(rotated_tile, Side::Left);
                     if color.is_valid_non_border_color() {
                         // placed(x+1, y, rotated_tile) -> right_edge_color(x, y, color)
                         clauses.push_binary(
@@ -143,31 +332,20 @@ fn main() -> Result<()> {
                                 );
                             }
                         }
-                    } else {
-                        // Can't place a gray edge in the middle of the board.
-                        // -placed(x+1, y, rotated_tile)
-                        clauses.push_unit(Literal::negative(Variable::for_tile_placement(
-                            x + 1_U4,
-                            y,
-                            rotated_tile,
-                        )));
                     }
                 }
             }
-        }
-    }
-
-    // Imply bottom edge colors for tile placements.
-    for y in 0..15 {
-        let y = U4::new_masked(y);
-        for x in 0..16 {
-            let x = U4::new_masked(x);
-            for tile in Tile::values() {
-                for rotation in Rotation::iter() {
-                    let rotated_tile = RotatedTile { tile, rotation };
+        });
 
+        // Imply bottom edge colors for tile placements.
+        clauses.begin_section("bottom edge implications");
+        clauses.emit_regions(0..height - 1, |clauses, y| {
+            let y = U4::new_masked(y as u8);
+            for x in 0..width {
+                let x = U4::new_masked(x as u8);
+                for &rotated_tile in &rotated_tiles {
                     // Bottom edge of the tile at (x, y).
-                    let color = rotated_tile.color(Side::Bottom);
+                    let color = tile_set.rotated_color(rotated_tile, Side::Bottom);
                     if color != Color::EXTERIOR {
                         // placed(x, y, rotated_tile) -> bottom_edge_color(x, y, color)
                         clauses.push_binary(
@@ -191,18 +369,10 @@ fn main() -> Result<()> {
                                 );
                             }
                         }
-                    } else {
-                        // Can't place a gray edge in the middle of the board.
-                        // -placed(x, y, rotated_tile)
-                        clauses.push_unit(Literal::negative(Variable::for_tile_placement(
-                            x,
-                            y,
-                            rotated_tile,
-                        )));
                     }
 
                     // Top edge of the tile at (x, y+1).
-                    let color = rotated_tile.color(Side::Top);
+                    let color = tile_set.rotated_color(rotated_tile, Side::Top);
                     if color != Color::EXTERIOR {
                         // placed(x, y+1, rotated_tile) -> bottom_edge_color(x, y, color)
                         clauses.push_binary(
@@ -230,70 +400,38 @@ fn main() -> Result<()> {
                                 );
                             }
                         }
-                    } else {
-                        // Can't place a gray edge in the middle of the board.
-                        // -placed(x, y+1, rotated_tile)
-                        clauses.push_unit(Literal::negative(Variable::for_tile_placement(
-                            x,
-                            y + 1_U4,
-                            rotated_tile,
-                        )));
                     }
                 }
             }
-        }
+        });
     }
 
-    // Rule out top and bottom edges on the perimeter that aren't gray.
-    for x in 0..16 {
-        let x = U4::new_masked(x);
-        for tile in Tile::values() {
-            for rotation in Rotation::iter() {
-                let rotated_tile = RotatedTile { tile, rotation };
-                if rotated_tile.color(Side::Top) != Color::EXTERIOR {
-                    clauses.push_unit(Literal::negative(Variable::for_tile_placement(
-                        x,
-                        0_U4,
-                        rotated_tile,
-                    )));
-                }
-                if rotated_tile.color(Side::Bottom) != Color::EXTERIOR {
-                    clauses.push_unit(Literal::negative(Variable::for_tile_placement(
-                        x,
-                        15_U4,
-                        rotated_tile,
-                    )));
-                }
-            }
-        }
+    // Limit how many seams each color can fill. Redundant, but it helps CDCL
+    // solvers notice early when a color's budget runs out.
+    if seam_counts {
+        clauses.begin_section("seam color counts");
+        emit_seam_color_counts(&mut clauses);
     }
 
-    // Rule out left and right edges on the perimeter that aren't gray.
-    for y in 0..16 {
-        let y = U4::new_masked(y);
-        for tile in Tile::values() {
-            for rotation in Rotation::iter() {
-                let rotated_tile = RotatedTile { tile, rotation };
-                if rotated_tile.color(Side::Left) != Color::EXTERIOR {
-                    clauses.push_unit(Literal::negative(Variable::for_tile_placement(
-                        0_U4,
-                        y,
-                        rotated_tile,
-                    )));
-                }
-                if rotated_tile.color(Side::Right) != Color::EXTERIOR {
-                    clauses.push_unit(Literal::negative(Variable::for_tile_placement(
-                        15_U4,
-                        y,
-                        rotated_tile,
-                    )));
-                }
-            }
-        }
+    if let Some(path) = groups_path {
+        let mut w = BufWriter::new(File::create(path)?);
+        Variable::write_kind_ranges(&mut w)?;
+        w.flush()?;
     }
 
-    println!("p cnf {} {}", Variable::COUNT, clauses.len());
-    clauses.print_dimacs_fragment(BufWriter::new(stdout().lock()))?;
+    let mut w = BufWriter::new(stdout().lock());
+    let manifest = InstanceManifest::new(clauses.variable_count(), &clauses);
+    if smt2 {
+        manifest.print_smt2_comments(&mut w)?;
+        clauses.write_smt2(&mut w)?;
+    } else {
+        manifest.print_dimacs_comments(&mut w)?;
+        if annotate {
+            clauses.write_annotated_dimacs(&mut w)?;
+        } else {
+            clauses.write_dimacs(&mut w)?;
+        }
+    }
 
     Ok(())
 }
@@ -1,20 +1,51 @@
 use eternity_ii::rectangular::RectangularRotation;
-use eternity_ii::report::{format_ratio, Table};
+use eternity_ii::report::{format_ratio, print_fan_out, ExpectedCount, Table};
 use eternity_ii::set::builder::counting_sampling::CountingSamplingSetBuilder;
 use eternity_ii::set::builder::in_memory_rectangular_mosaic::InMemoryRectangularMosaicSetBuilder;
 use eternity_ii::set::builder::in_memory_square_mosaic::InMemorySquareMosaicSetBuilder;
 use eternity_ii::set::{
     build_1x1_sets, build_1x1_sets_with_clues, build_rectangles, build_rectangular_centers,
-    build_rectangular_corners, build_rectangular_edges, build_square_centers, build_square_corners,
-    build_square_edges, build_squares,
+    build_rectangular_centers_instrumented, build_rectangular_corners, build_rectangular_edges,
+    build_square_centers, build_square_corners, build_square_edges, build_squares,
+    expected_rectangular_corner_count, expected_rectangular_edge_count,
+    rectangular_center_count_upper_bound,
 };
 use eternity_ii::Rotation;
 
+const USAGE: &str = "usage: enumerate_mosaics [--with-hints | --without-hints]
+
+Enumerates mosaics with the clue tiles split into their own sets, or with
+--without-hints, from every tile alike. --with-hints is the default.";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Command {
+    Enumerate { with_hints: bool },
+    Help,
+}
+
+/// Parses the arguments following the program name.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut with_hints = true;
+    for arg in args {
+        match arg.as_str() {
+            "--with-hints" => with_hints = true,
+            "--without-hints" => with_hints = false,
+            "-h" | "--help" => return Ok(Command::Help),
+            _ => return Err(format!("unexpected argument {arg:?}")),
+        }
+    }
+    Ok(Command::Enumerate { with_hints })
+}
+
 fn main() {
-    if false {
-        enumerate_mosaics();
-    } else {
-        enumerate_mosaics_with_hints();
+    match parse_args(std::env::args().skip(1)) {
+        Ok(Command::Enumerate { with_hints: false }) => enumerate_mosaics(),
+        Ok(Command::Enumerate { with_hints: true }) => enumerate_mosaics_with_hints(),
+        Ok(Command::Help) => println!("{USAGE}"),
+        Err(e) => {
+            eprintln!("ERROR: {e}\n\n{USAGE}");
+            std::process::exit(2);
+        }
     }
 }
 
@@ -27,26 +58,44 @@ fn enumerate_mosaics() {
     table.check_square("1x1 center mosaics", &square_1x1_centers);
     table.print();
 
-    let rectangular_2x1_corners = table.track_build_rectangle("2x1 corner mosaics", || {
-        build_rectangular_corners(
-            InMemoryRectangularMosaicSetBuilder::<2, 1, _>::new(),
+    let rectangular_2x1_corners = table.track_build_rectangle_expecting(
+        "2x1 corner mosaics",
+        ExpectedCount::Exact(expected_rectangular_corner_count(
             &square_1x1_corners,
             &square_1x1_edges,
-        )
-    });
-    let rectangular_2x1_edges = table.track_build_rectangle("2x1 edge mosaics", || {
-        build_rectangular_edges(
-            InMemoryRectangularMosaicSetBuilder::<2, 1, _>::new(),
+        )),
+        || {
+            build_rectangular_corners(
+                InMemoryRectangularMosaicSetBuilder::<2, 1, _>::new(),
+                &square_1x1_corners,
+                &square_1x1_edges,
+            )
+        },
+    );
+    let rectangular_2x1_edges = table.track_build_rectangle_expecting(
+        "2x1 edge mosaics",
+        ExpectedCount::Exact(expected_rectangular_edge_count(
             &square_1x1_edges,
             &square_1x1_centers,
-        )
-    });
-    let rectangular_2x1_centers = table.track_build_rectangle("2x1 center mosaics", || {
-        build_rectangular_centers(
-            InMemoryRectangularMosaicSetBuilder::<2, 1, _>::new(),
-            &square_1x1_centers,
-        )
-    });
+        )),
+        || {
+            build_rectangular_edges(
+                InMemoryRectangularMosaicSetBuilder::<2, 1, _>::new(),
+                &square_1x1_edges,
+                &square_1x1_centers,
+            )
+        },
+    );
+    let rectangular_2x1_centers = table.track_build_rectangle_expecting(
+        "2x1 center mosaics",
+        ExpectedCount::AtMost(rectangular_center_count_upper_bound(&square_1x1_centers)),
+        || {
+            build_rectangular_centers(
+                InMemoryRectangularMosaicSetBuilder::<2, 1, _>::new(),
+                &square_1x1_centers,
+            )
+        },
+    );
     table.print();
 
     let square_2x2_corners = table.track_build_square("2x2 corner mosaics", || {
@@ -86,12 +135,16 @@ fn enumerate_mosaics() {
             &square_2x2_centers,
         )
     });
+    let mut fan_out = None;
     table.track_count_and_sample("4x2 center mosaics", || {
-        build_rectangular_centers::<2, 4, _, _>(
-            CountingSamplingSetBuilder::new(),
+        let (result, stats) = build_rectangular_centers_instrumented::<2, 4, _, _>(
+            CountingSamplingSetBuilder::with_capacity(4),
             &square_2x2_centers,
-        )
+        );
+        fan_out = Some(stats);
+        result
     });
+    print_fan_out(&fan_out.unwrap());
     table.print();
 }
 
@@ -129,28 +182,46 @@ fn enumerate_mosaics_with_hints() {
     );
     table.print();
 
-    let rectangular_2x1_corners_no_clues =
-        table.track_build_rectangle("2x1 corners, no clues", || {
+    let rectangular_2x1_corners_no_clues = table.track_build_rectangle_expecting(
+        "2x1 corners, no clues",
+        ExpectedCount::Exact(expected_rectangular_corner_count(
+            &square_1x1_corners_no_clues,
+            &square_1x1_edges_no_clues,
+        )),
+        || {
             build_rectangular_corners(
                 InMemoryRectangularMosaicSetBuilder::<2, 1, _>::new(),
                 &square_1x1_corners_no_clues,
                 &square_1x1_edges_no_clues,
             )
-        });
-    let rectangular_2x1_edges_no_clues = table.track_build_rectangle("2x1 edges, no clues", || {
-        build_rectangular_edges(
-            InMemoryRectangularMosaicSetBuilder::<2, 1, _>::new(),
+        },
+    );
+    let rectangular_2x1_edges_no_clues = table.track_build_rectangle_expecting(
+        "2x1 edges, no clues",
+        ExpectedCount::Exact(expected_rectangular_edge_count(
             &square_1x1_edges_no_clues,
             &square_1x1_centers_no_clues,
-        )
-    });
-    let rectangular_2x1_centers_no_clues =
-        table.track_build_rectangle("2x1 centers, no clues", || {
+        )),
+        || {
+            build_rectangular_edges(
+                InMemoryRectangularMosaicSetBuilder::<2, 1, _>::new(),
+                &square_1x1_edges_no_clues,
+                &square_1x1_centers_no_clues,
+            )
+        },
+    );
+    let rectangular_2x1_centers_no_clues = table.track_build_rectangle_expecting(
+        "2x1 centers, no clues",
+        ExpectedCount::AtMost(rectangular_center_count_upper_bound(
+            &square_1x1_centers_no_clues,
+        )),
+        || {
             build_rectangular_centers(
                 InMemoryRectangularMosaicSetBuilder::<2, 1, _>::new(),
                 &square_1x1_centers_no_clues,
             )
-        });
+        },
+    );
     let rectangular_2x1_centers_c3_clue =
         table.track_build_rectangle("2x1 centers, C3 clue", || {
             build_rectangles(
@@ -389,3 +460,30 @@ fn enumerate_mosaics_with_hints() {
     );
     table.print();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_args, Command};
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parse_args_modes() {
+        assert_eq!(parse(&[]), Ok(Command::Enumerate { with_hints: true }));
+        assert_eq!(
+            parse(&["--with-hints"]),
+            Ok(Command::Enumerate { with_hints: true }),
+        );
+        assert_eq!(
+            parse(&["--without-hints"]),
+            Ok(Command::Enumerate { with_hints: false }),
+        );
+        assert_eq!(parse(&["--with-hints", "--help"]), Ok(Command::Help));
+        assert_eq!(
+            parse(&["--hints"]),
+            Err("unexpected argument \"--hints\"".to_string()),
+        );
+    }
+}
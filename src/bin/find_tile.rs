@@ -1,24 +1,28 @@
-use eternity_ii::{Color, RotatedTile, Rotation, Side, Tile};
-use strum::IntoEnumIterator;
+use eternity_ii::{Color, RotatedTile};
 
 fn main() {
-    let goal = std::env::args().skip(1).next().unwrap();
-    println!("Searching for a tile with edge assignment {goal:?} (right, up, left, down)");
+    let goal = std::env::args().nth(1).unwrap();
+    println!(
+        "Searching for tiles with edge assignment {goal:?} (right, up, left, down, ? for any)"
+    );
 
-    for tile in Tile::values() {
-        for rotation in Rotation::iter() {
-            let oriented = RotatedTile { tile, rotation };
-            if goal
-                .chars()
-                .map(Color::from_char)
-                .map(Option::unwrap)
-                .zip(Side::iter())
-                .all(|(color, side)| oriented.color(side) == color)
-            {
-                println!("Matched tile {} {:?}", tile.to_primitive(), rotation);
-                return;
-            }
-        }
+    let colors = Vec::from_iter(goal.chars().map(|c| match c {
+        '?' => None,
+        _ => Some(Color::from_char(c).unwrap()),
+    }));
+    let [right, top, left, bottom] = colors[..] else {
+        panic!("expected four colors");
+    };
+    let matches = RotatedTile::tiles_matching(right, top, left, bottom);
+    if matches.is_empty() {
+        println!("No tile matched");
+    }
+    for RotatedTile { tile, rotation } in matches {
+        let colors = String::from_iter(tile.colors().map(Color::to_char));
+        println!(
+            "Matched tile {} {:?}, which is {colors:?} unrotated",
+            tile.to_primitive(),
+            rotation,
+        )
     }
-    println!("No tile matched");
 }
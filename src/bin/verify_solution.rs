@@ -0,0 +1,108 @@
+use std::io::{stdin, BufRead, BufReader};
+
+use anyhow::{bail, Result};
+use eternity_ii::mosaic::RectangularMosaic;
+use eternity_ii::sat::{model_to_mosaic, FullVariable};
+use eternity_ii::{hints, Color, Side};
+
+fn main() -> Result<()> {
+    let mut literals = Vec::new();
+    'outer: for line in BufReader::new(stdin()).lines() {
+        if let Some(line) = line?.strip_prefix('v') {
+            for literal in line.split_ascii_whitespace() {
+                let literal: isize = literal.parse()?;
+                if literal == 0 {
+                    break 'outer;
+                }
+                literals.push(literal);
+            }
+        }
+    }
+
+    let mosaic = model_to_mosaic(literals.iter().copied())?;
+    let mut violations = Vec::new();
+
+    // Every tile is used exactly once.
+    let mut uses = [0usize; 256];
+    for y in 0..16 {
+        for x in 0..16 {
+            uses[mosaic.get(x, y).tile.to_primitive() as usize] += 1;
+        }
+    }
+    for (tile, &count) in uses.iter().enumerate() {
+        if count != 1 {
+            violations.push(format!("tile {tile} is used {count} times"));
+        }
+    }
+
+    // Every edge on the perimeter is gray.
+    for i in 0..16 {
+        for (x, y, side) in [
+            (i, 0, Side::Top),
+            (i, 15, Side::Bottom),
+            (0, i, Side::Left),
+            (15, i, Side::Right),
+        ] {
+            let color = mosaic.get(x, y).color(side);
+            if color != Color::EXTERIOR {
+                violations.push(format!(
+                    "({x}, {y}) has {} on its {side:?} side, which is on the perimeter",
+                    color.name(),
+                ));
+            }
+        }
+    }
+
+    // Every pair of neighbors agrees on the color between them.
+    for y in 0..16 {
+        for x in 0..16 {
+            let rotated_tile = mosaic.get(x, y);
+            if x < 15 {
+                let (a, b) = (
+                    rotated_tile.color(Side::Right),
+                    mosaic.get(x + 1, y).color(Side::Left),
+                );
+                if a != b {
+                    violations.push(format!(
+                        "({x}, {y}) and ({}, {y}) meet with {} and {}",
+                        x + 1,
+                        a.name(),
+                        b.name(),
+                    ));
+                }
+            }
+            if y < 15 {
+                let (a, b) = (
+                    rotated_tile.color(Side::Bottom),
+                    mosaic.get(x, y + 1).color(Side::Top),
+                );
+                if a != b {
+                    violations.push(format!(
+                        "({x}, {y}) and ({x}, {}) meet with {} and {}",
+                        y + 1,
+                        a.name(),
+                        b.name(),
+                    ));
+                }
+            }
+        }
+    }
+
+    // Every published clue is in place. Each cell holds exactly one tile, so
+    // this is the same as the clue's placement variable being true.
+    for (x, y, rotated_tile) in hints() {
+        let variable = usize::from(FullVariable::for_tile_placement(x, y, rotated_tile)) as isize;
+        if !literals.contains(&variable) {
+            violations.push(format!("({x}, {y}) doesn't hold the clue {rotated_tile}"));
+        }
+    }
+
+    for violation in &violations {
+        eprintln!("ERROR: {violation}");
+    }
+    if !violations.is_empty() {
+        bail!("{} violations", violations.len());
+    }
+    println!("The model is a valid solution.");
+    Ok(())
+}
@@ -3,6 +3,12 @@ use std::ops::{Index, IndexMut};
 
 use crate::Color;
 
+/// The colors along one edge of a mosaic, read clockwise.
+///
+/// Edges order lexicographically by color, and colors order by primitive value,
+/// which is the same as their characters: `a < b < ... < w`. So edges order
+/// like their [byte strings](Self::from_byte_string), and iteration over maps
+/// keyed by edges is reproducible across runs.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ArrayEdge<const N: usize>([Color; N]);
 
@@ -15,9 +21,45 @@ impl<const N: usize> ArrayEdge<N> {
     ///
     /// Panics if any byte in `colors` is not a valid [`Color`].
     pub fn from_byte_string(colors: &[u8; N]) -> Self {
+        Self::try_from_byte_string(colors).unwrap()
+    }
+
+    pub fn try_from_byte_string(colors: &[u8; N]) -> Result<Self, EdgeParseError> {
         let mut result = Self::default();
-        for (index, b) in colors.iter().copied().enumerate() {
-            result[index] = Color::from_byte_char(b).unwrap();
+        for (index, byte) in colors.iter().copied().enumerate() {
+            result[index] =
+                Color::from_byte_char(byte).ok_or(EdgeParseError::InvalidColor { index, byte })?;
+        }
+        Ok(result)
+    }
+
+    /// Packs the edge into an integer, five bits per color, with the first
+    /// color in the most significant position. Packed edges of the same length
+    /// compare in the same order as the edges themselves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is greater than 12.
+    pub fn to_packed(self) -> u64 {
+        assert!(N <= 12);
+        self.0.iter().fold(0, |packed, color| {
+            (packed << 5) | color.to_primitive() as u64
+        })
+    }
+
+    /// The inverse of [`Self::to_packed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is greater than 12 or a packed color is out of range.
+    pub fn from_packed(mut packed: u64) -> Self {
+        assert!(N <= 12);
+        let mut result = Self::default();
+        for index in (0..N).rev() {
+            let value = (packed & 0x1f) as u8;
+            result[index] = Color::try_from_primitive(value)
+                .unwrap_or_else(|| panic!("invalid packed color {value}"));
+            packed >>= 5;
         }
         result
     }
@@ -30,8 +72,18 @@ impl<const N: usize> ArrayEdge<N> {
         result
     }
 
+    /// Whether this edge can abut `other`.
+    ///
+    /// Edges are read clockwise around their mosaic, so where two mosaics meet
+    /// their shared edge is read in opposite directions. Two edges abut when
+    /// one is the other reversed, which is not the same as being equal.
+    pub fn matches(&self, other: &ArrayEdge<N>) -> bool {
+        self == &other.reversed()
+    }
+
+    #[deprecated(note = "use `ArrayEdge::matches`, which is the same predicate")]
     pub fn flip_eq(&self, rhs: &Self) -> bool {
-        self.0.iter().zip(rhs.0.iter().rev()).all(|(a, b)| a == b)
+        self.matches(rhs)
     }
 
     pub fn iter(&self) -> impl Iterator<Item = Color> + '_ {
@@ -61,7 +113,7 @@ impl<const N: usize> IndexMut<usize> for ArrayEdge<N> {
 
 impl<const N: usize> Debug for ArrayEdge<N> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        <Self as Display>::fmt(&self, f)
+        <Self as Display>::fmt(self, f)
     }
 }
 
@@ -73,3 +125,120 @@ impl<const N: usize> Display for ArrayEdge<N> {
         Ok(())
     }
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeParseError {
+    InvalidColor { index: usize, byte: u8 },
+}
+
+impl Display for EdgeParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Self::InvalidColor { index, byte } => write!(
+                f,
+                "edge position {index} has invalid color {:?}",
+                byte.escape_ascii().to_string(),
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EdgeParseError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::Color;
+
+    use super::{ArrayEdge, EdgeParseError};
+
+    fn check_packing_round_trips<const N: usize>() {
+        let mut prev_packed = None;
+        for mut i in 0..23usize.pow(N as u32) {
+            let mut edge = ArrayEdge::<N>::default();
+            for index in (0..N).rev() {
+                edge[index] = Color::new_masked((i % 23) as u8);
+                i /= 23;
+            }
+
+            let packed = edge.to_packed();
+            assert_eq!(ArrayEdge::from_packed(packed), edge);
+            // Edges are generated in ascending order, so packing must preserve it.
+            assert!(prev_packed < Some(packed));
+            prev_packed = Some(packed);
+        }
+    }
+
+    #[test]
+    fn packing_round_trips() {
+        check_packing_round_trips::<0>();
+        check_packing_round_trips::<1>();
+        check_packing_round_trips::<2>();
+        check_packing_round_trips::<3>();
+        check_packing_round_trips::<4>();
+        let edge = ArrayEdge::from_byte_string(b"wwwwwwwwwwww");
+        assert_eq!(ArrayEdge::from_packed(edge.to_packed()), edge);
+    }
+
+    #[test]
+    fn zero_length() {
+        let edge = ArrayEdge::<0>::default();
+        assert_eq!(edge.to_string(), "");
+        assert_eq!(edge.reversed(), edge);
+        assert!(edge.matches(&edge));
+        assert_eq!(edge.iter().count(), 0);
+        assert_eq!(ArrayEdge::from_byte_string(&[]), edge);
+        assert_eq!(ArrayEdge::<0>::from_packed(edge.to_packed()), edge);
+    }
+
+    #[test]
+    fn matches() {
+        let edge = ArrayEdge::from_byte_string(b"bvw");
+        let reversed = ArrayEdge::from_byte_string(b"wvb");
+        assert!(edge.matches(&reversed));
+        assert!(reversed.matches(&edge));
+        assert!(!edge.matches(&edge));
+
+        // A palindrome both equals and matches itself.
+        let palindrome = ArrayEdge::from_byte_string(b"bvb");
+        assert!(palindrome.matches(&palindrome));
+    }
+
+    #[test]
+    fn order_matches_byte_strings() {
+        assert!(ArrayEdge::from_byte_string(b"ab") < ArrayEdge::from_byte_string(b"ba"));
+        assert!(ArrayEdge::from_byte_string(b"wa") > ArrayEdge::from_byte_string(b"aw"));
+
+        let byte_strings =
+            Vec::from_iter((b'a'..=b'w').flat_map(|a| (b'a'..=b'w').map(move |b| [a, b])));
+        for x in &byte_strings {
+            for y in &byte_strings {
+                assert_eq!(
+                    ArrayEdge::from_byte_string(x).cmp(&ArrayEdge::from_byte_string(y)),
+                    x.cmp(y),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn try_from_byte_string() {
+        assert_eq!(
+            ArrayEdge::try_from_byte_string(b"bv"),
+            Ok(ArrayEdge::new([Color::B, Color::V])),
+        );
+        assert_eq!(
+            ArrayEdge::try_from_byte_string(b"zz"),
+            Err(EdgeParseError::InvalidColor {
+                index: 0,
+                byte: b'z',
+            }),
+        );
+        assert_eq!(
+            ArrayEdge::try_from_byte_string(b"a?"),
+            Err(EdgeParseError::InvalidColor {
+                index: 1,
+                byte: b'?',
+            }),
+        );
+    }
+}
@@ -1,8 +1,13 @@
+use std::collections::HashMap;
 use std::fmt::{self, Display, Formatter};
 use std::ops::{Add, AddAssign};
+use std::str::FromStr;
+use std::sync::OnceLock;
 
 use mvbitfield::prelude::*;
-use strum::EnumIter;
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::tile_set::{EternityIiTiles, TileColors};
 
 #[macro_use]
 mod macros;
@@ -12,7 +17,11 @@ pub mod mosaic;
 pub mod rectangular;
 pub mod report;
 pub mod sat;
+pub mod scan;
 pub mod set;
+pub mod tile_set;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 bitfield! {
     #[derive(PartialOrd, Ord, EnumIter)]
@@ -25,6 +34,12 @@ bitfield! {
 }
 
 impl Side {
+    /// Returns the side with primitive value `value`, or `None` if it's 4 or
+    /// more. Unlike `new_masked`, this doesn't hide out-of-range values.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        (value < 4).then(|| Self::new_masked(value))
+    }
+
     pub fn rotation_from_right(self) -> Rotation {
         self.to_bitint().into()
     }
@@ -36,6 +51,10 @@ impl Side {
     pub fn reverse_transform(self, rotation: Rotation) -> Self {
         self.to_bitint().wrapping_sub(rotation.to_bitint()).into()
     }
+
+    pub fn opposite(self) -> Self {
+        self.transform(Rotation::HalfTurn)
+    }
 }
 
 bitfield! {
@@ -48,6 +67,29 @@ bitfield! {
     }
 }
 
+impl Rotation {
+    /// Every rotation, indexed by its number of quarter turns left.
+    pub const QUARTER_TURNS: [Rotation; 4] = [
+        Rotation::Identity,
+        Rotation::QuarterTurnLeft,
+        Rotation::HalfTurn,
+        Rotation::QuarterTurnRight,
+    ];
+
+    /// Returns the rotation with primitive value `value`, or `None` if it's 4
+    /// or more. Unlike `new_masked`, this doesn't hide out-of-range values.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        (value < 4).then(|| Self::new_masked(value))
+    }
+
+    /// Returns this rotation followed by `n` quarter turns left, which is the
+    /// same as `n` quarter turns left when starting from
+    /// [`Rotation::Identity`].
+    pub fn times(self, n: u8) -> Self {
+        self + Self::QUARTER_TURNS[n as usize % 4]
+    }
+}
+
 impl Add for Rotation {
     type Output = Self;
 
@@ -101,23 +143,126 @@ impl Color {
     pub const V: Self = Self::from_bitint(21_U5);
     pub const W: Self = Self::from_bitint(22_U5);
 
+    /// Bit `i` is set if color `i` appears beside an exterior side on some
+    /// tile. Despite their names, these aren't the colors between
+    /// [`Self::BORDER_COLOR_MIN`] and [`Self::BORDER_COLOR_MAX`].
+    const FRAME_COLORS: u32 = {
+        let edges = Tile::EDGES.as_bytes();
+        let mut mask = 0;
+        let mut tile = 0;
+        while tile < edges.len() {
+            let mut side = 0;
+            while side < 4 {
+                let color = edges[tile + side];
+                let next = edges[tile + (side + 1) % 4];
+                let prev = edges[tile + (side + 3) % 4];
+                if color != b'a' && (next == b'a' || prev == b'a') {
+                    mask |= 1 << (color - b'a');
+                }
+                side += 1;
+            }
+            tile += 4;
+        }
+        mask
+    };
+
     pub fn iter() -> impl Iterator<Item = Self> {
-        (0u8..=22u8)
-            .into_iter()
-            .map(|x| unsafe { Self::new_unchecked(x) })
+        (0u8..=22u8).map(|x| unsafe { Self::new_unchecked(x) })
     }
 
-    pub fn is_border(self) -> bool {
+    pub fn is_exterior(self) -> bool {
         self == Self::EXTERIOR
     }
 
+    #[deprecated(note = "this means exterior, not a border color; use `is_exterior`")]
+    pub fn is_border(self) -> bool {
+        self.is_exterior()
+    }
+
+    /// Returns whether this is one of the colors found only along the frame of
+    /// the board, between adjacent border pieces: `b`, `f`, `j`, `n`, and `r`.
+    pub fn is_border_color(self) -> bool {
+        Self::FRAME_COLORS & (1 << self.to_primitive()) != 0
+    }
+
+    /// Returns whether this is one of the colors found between interior pieces.
+    pub fn is_interior_color(self) -> bool {
+        !self.is_exterior() && self <= Self::W && !self.is_border_color()
+    }
+
     pub fn is_valid_non_border_color(self) -> bool {
         self >= Self::BORDER_COLOR_MIN && self <= Self::INTERIOR_COLOR_MAX
     }
 
+    /// # Panics
+    ///
+    /// Panics if this is not a valid color.
+    pub fn category(self) -> ColorCategory {
+        if self.is_exterior() {
+            ColorCategory::Exterior
+        } else if self.is_border_color() {
+            ColorCategory::Border
+        } else if self.is_interior_color() {
+            ColorCategory::Interior
+        } else {
+            panic!("invalid color {}", self.to_primitive())
+        }
+    }
+
+    /// Returns a human-friendly name, such as `exterior`, `border-1`, or
+    /// `interior-A`. Border colors are numbered and interior colors lettered in
+    /// color order, so `b` is `border-1` and `c` is `interior-A`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is not a valid color.
+    pub fn name(self) -> &'static str {
+        const BORDER_NAMES: [&str; 5] =
+            ["border-1", "border-2", "border-3", "border-4", "border-5"];
+        const INTERIOR_NAMES: [&str; 17] = [
+            "interior-A",
+            "interior-B",
+            "interior-C",
+            "interior-D",
+            "interior-E",
+            "interior-F",
+            "interior-G",
+            "interior-H",
+            "interior-I",
+            "interior-J",
+            "interior-K",
+            "interior-L",
+            "interior-M",
+            "interior-N",
+            "interior-O",
+            "interior-P",
+            "interior-Q",
+        ];
+        let below = (1u32 << self.to_primitive()) - 1;
+        let border_below = (Self::FRAME_COLORS & below).count_ones() as usize;
+        match self.category() {
+            ColorCategory::Exterior => "exterior",
+            ColorCategory::Border => BORDER_NAMES[border_below],
+            ColorCategory::Interior => {
+                INTERIOR_NAMES[self.to_primitive() as usize - 1 - border_below]
+            }
+        }
+    }
+
+    /// Returns the color with primitive value `value`, or `None` if it's
+    /// outside the 23 Eternity II colors, even though it would fit in five
+    /// bits.
+    pub const fn try_from_primitive(value: u8) -> Option<Self> {
+        if value <= 22 {
+            Some(unsafe { Self::new_unchecked(value) })
+        } else {
+            None
+        }
+    }
+
     pub const fn from_byte_char(b: u8) -> Option<Self> {
-        if b >= b'a' && b <= b'w' {
-            Some(unsafe { Self::new_unchecked(b - b'a') })
+        if b >= b'a' {
+            Self::try_from_primitive(b - b'a')
         } else {
             None
         }
@@ -134,6 +279,24 @@ impl Color {
     pub fn to_char(self) -> char {
         self.to_byte_char() as char
     }
+
+    /// Returns the color to draw this as, from [`mosaic::PALETTE`]: gray for
+    /// [`Self::EXTERIOR`] and a distinct hue for each motif.
+    pub fn rgb(self) -> (u8, u8, u8) {
+        let [r, g, b] = mosaic::PALETTE[self.to_primitive() as usize];
+        (r, g, b)
+    }
+}
+
+/// Where on the board a [`Color`] can appear.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorCategory {
+    /// The outside of the board.
+    Exterior,
+    /// Between adjacent border pieces, along the frame of the board.
+    Border,
+    /// Between interior pieces.
+    Interior,
 }
 
 impl Display for Color {
@@ -153,6 +316,86 @@ mod color_tests {
             "abcdefghijklmnopqrstuvw",
         );
     }
+
+    #[test]
+    fn color_classes() {
+        for (color, exterior, border, interior) in [
+            (Color::EXTERIOR, true, false, false),
+            (Color::B, false, true, false),
+            (Color::C, false, false, true),
+            (Color::J, false, true, false),
+            (Color::P, false, false, true),
+            (Color::R, false, true, false),
+            (Color::W, false, false, true),
+        ] {
+            assert_eq!(color.is_exterior(), exterior, "{color}");
+            assert_eq!(color.is_border_color(), border, "{color}");
+            assert_eq!(color.is_interior_color(), interior, "{color}");
+            assert_eq!(color.is_valid_non_border_color(), !exterior, "{color}");
+        }
+        assert!(!Color::new_masked(23).is_interior_color());
+    }
+
+    #[test]
+    fn border_colors_match_the_tiles() {
+        let mut border_colors = Vec::new();
+        for rotated_tile in RotatedTile::values() {
+            if rotated_tile.exterior_mask().bottom() {
+                border_colors.push(rotated_tile.color(Side::Left));
+                border_colors.push(rotated_tile.color(Side::Right));
+            }
+        }
+        border_colors.sort();
+        border_colors.dedup();
+        border_colors.retain(|color| !color.is_exterior());
+        assert_eq!(
+            String::from_iter(border_colors.iter().map(|color| color.to_char())),
+            "bfjnr",
+        );
+        for color in Color::iter() {
+            assert_eq!(color.is_border_color(), border_colors.contains(&color));
+        }
+    }
+
+    #[test]
+    fn rgb() {
+        let (r, g, b) = Color::EXTERIOR.rgb();
+        assert!(r == g && g == b);
+
+        let mut colors = Vec::from_iter(Color::iter().map(Color::rgb));
+        colors.sort();
+        colors.dedup();
+        assert_eq!(colors.len(), 23);
+    }
+
+    #[test]
+    fn names_and_categories() {
+        for (color, name, category) in [
+            (Color::EXTERIOR, "exterior", ColorCategory::Exterior),
+            (Color::B, "border-1", ColorCategory::Border),
+            (Color::C, "interior-A", ColorCategory::Interior),
+            (Color::F, "border-2", ColorCategory::Border),
+            (Color::G, "interior-D", ColorCategory::Interior),
+            (Color::R, "border-5", ColorCategory::Border),
+            (Color::W, "interior-Q", ColorCategory::Interior),
+        ] {
+            assert_eq!(color.name(), name, "{color}");
+            assert_eq!(color.category(), category, "{color}");
+        }
+        let mut names = Vec::from_iter(Color::iter().map(Color::name));
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), 23);
+    }
+
+    #[test]
+    fn try_from_primitive() {
+        assert_eq!(Color::try_from_primitive(0), Some(Color::EXTERIOR));
+        assert_eq!(Color::try_from_primitive(22), Some(Color::W));
+        assert_eq!(Color::try_from_primitive(23), None);
+        assert_eq!(Color::try_from_primitive(31), None);
+        assert_eq!(Color::from_byte_char(b'x'), None);
+    }
 }
 
 bitfield! {
@@ -182,7 +425,7 @@ impl Tile {
     };
 
     pub fn values() -> impl Iterator<Item = Tile> {
-        (0..=255).into_iter().map(|i| Tile::from_primitive(i))
+        (0..=255).map(Tile::from_primitive)
     }
 
     pub fn color(self, side: Side) -> Color {
@@ -192,6 +435,59 @@ impl Tile {
         // .unwrap()
         Self::PARSED_EDGES[4 * self.to_primitive() as usize + side.to_primitive() as usize]
     }
+
+    /// Returns the colors on all four sides, in [`Side`] order.
+    pub fn colors(self) -> [Color; 4] {
+        let start = 4 * self.to_primitive() as usize;
+        Self::PARSED_EDGES[start..start + 4].try_into().unwrap()
+    }
+
+    /// Returns this tile in each of its four orientations, in
+    /// `Rotation::iter()` order.
+    pub fn rotations(self) -> [RotatedTile; 4] {
+        Rotation::QUARTER_TURNS.map(|rotation| RotatedTile {
+            tile: self,
+            rotation,
+        })
+    }
+
+    /// Returns how many times each color appears across every side of every
+    /// tile, indexed by the color's primitive value.
+    ///
+    /// Joins on an edge with rare colors have few candidates, so trying those
+    /// edges first prunes sooner.
+    pub fn color_frequencies() -> [usize; 23] {
+        static FREQUENCIES: OnceLock<[usize; 23]> = OnceLock::new();
+        *FREQUENCIES.get_or_init(|| {
+            let mut frequencies = [0; 23];
+            for color in Self::PARSED_EDGES {
+                frequencies[color.to_primitive() as usize] += 1;
+            }
+            frequencies
+        })
+    }
+
+    /// Returns how many of the four rotations of this tile have distinct color
+    /// patterns: 1, 2, or 4.
+    pub fn distinct_rotations(self) -> u8 {
+        let colors = self.colors();
+        (1..4)
+            .find(|&period| (0..4).all(|i| colors[i] == colors[(i + period) % 4]))
+            .unwrap_or(4) as u8
+    }
+
+    /// Returns whether some rotation other than the identity leaves this tile's
+    /// colors unchanged.
+    pub fn is_rotationally_symmetric(self) -> bool {
+        self.distinct_rotations() < 4
+    }
+}
+
+/// Formats the tile as its number, like `135`.
+impl Display for Tile {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_primitive())
+    }
 }
 
 bitfield! {
@@ -204,6 +500,43 @@ bitfield! {
     }
 }
 
+impl ExteriorMask {
+    /// Returns whether a real tile can have this mask: no exterior sides, one,
+    /// or two adjacent ones. Opposite exterior sides or more than two never
+    /// occur.
+    pub fn is_valid_tile_mask(self) -> bool {
+        let mask = self.to_primitive();
+        match mask.count_ones() {
+            0 | 1 => true,
+            2 => mask != 0b0101 && mask != 0b1010,
+            _ => false,
+        }
+    }
+}
+
+/// The kind of board position a tile can occupy.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TileClass {
+    /// Two adjacent exterior sides.
+    Corner,
+    /// One exterior side.
+    Edge,
+    /// No exterior sides.
+    Center,
+}
+
+/// A line to mirror a tile across.
+///
+/// Eternity II pieces can't be flipped, but other edge-matching puzzles allow
+/// mirrored placements.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, EnumIter)]
+pub enum FlipAxis {
+    /// A vertical line, swapping the left and right sides.
+    Vertical,
+    /// A horizontal line, swapping the top and bottom sides.
+    Horizontal,
+}
+
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct RotatedTile {
     pub tile: Tile,
@@ -221,19 +554,171 @@ impl RotatedTile {
         rotation: Rotation::QuarterTurnRight,
     };
 
+    /// Returns all 1024 rotated tiles, ordered by tile and then by rotation.
+    pub fn values() -> impl Iterator<Item = RotatedTile> {
+        Tile::values().flat_map(Tile::rotations)
+    }
+
     pub fn color(self, side: Side) -> Color {
         self.tile.color(side.reverse_transform(self.rotation))
     }
 
+    /// Returns the colors on all four sides after rotation, in [`Side`] order.
+    pub fn colors(self) -> [Color; 4] {
+        let colors = self.tile.colors();
+        [Side::Right, Side::Top, Side::Left, Side::Bottom]
+            .map(|side| colors[side.reverse_transform(self.rotation).to_primitive() as usize])
+    }
+
+    /// Returns the rotated tile with the given colors on its right, top, left,
+    /// and bottom sides, if any.
+    ///
+    /// If several rotations of a tile match, the lowest rotation is returned.
+    pub fn from_colors(right: Color, top: Color, left: Color, bottom: Color) -> Option<Self> {
+        static INDEX: OnceLock<HashMap<[Color; 4], RotatedTile>> = OnceLock::new();
+        INDEX
+            .get_or_init(|| {
+                let mut index = HashMap::new();
+                for rotated_tile in RotatedTile::values() {
+                    index.entry(rotated_tile.colors()).or_insert(rotated_tile);
+                }
+                index
+            })
+            .get(&[right, top, left, bottom])
+            .copied()
+    }
+
+    /// Returns every rotated tile with the given colors on its right, top,
+    /// left, and bottom sides, where `None` matches any color.
+    ///
+    /// Unlike [`Self::from_colors`], each matching rotation is returned, so a
+    /// symmetric tile can appear more than once.
+    pub fn tiles_matching(
+        right: Option<Color>,
+        top: Option<Color>,
+        left: Option<Color>,
+        bottom: Option<Color>,
+    ) -> Vec<RotatedTile> {
+        let pattern = [right, top, left, bottom];
+        Vec::from_iter(RotatedTile::values().filter(|rotated_tile| {
+            pattern
+                .iter()
+                .zip(rotated_tile.colors())
+                .all(|(&expected, actual)| expected.is_none() || expected == Some(actual))
+        }))
+    }
+
+    /// Returns the rotated tile whose colors are this one's mirrored across
+    /// `axis`, if any tile in the set has that signature.
+    ///
+    /// This is usually a different tile. It's this one only if the tile is
+    /// symmetric across `axis`.
+    pub fn flipped(self, axis: FlipAxis) -> Option<Self> {
+        let [right, top, left, bottom] = self.colors();
+        match axis {
+            FlipAxis::Vertical => Self::from_colors(left, top, right, bottom),
+            FlipAxis::Horizontal => Self::from_colors(right, bottom, left, top),
+        }
+    }
+
+    /// Returns the rotation of this tile whose [colors](Self::colors) are
+    /// lexicographically smallest, preferring the lowest rotation on a tie.
+    ///
+    /// Every rotation of a tile returns the same result, making it a canonical
+    /// form that depends only on the tile's colors.
+    pub fn min_rotation(self) -> Self {
+        Rotation::iter()
+            .map(|rotation| RotatedTile {
+                tile: self.tile,
+                rotation,
+            })
+            .min_by_key(|rotated_tile| rotated_tile.colors())
+            .unwrap()
+    }
+
     pub fn exterior_mask(self) -> ExteriorMask {
-        ExteriorMask::zero()
-            .with_right(self.color(Side::Right) == Color::EXTERIOR)
-            .with_top(self.color(Side::Top) == Color::EXTERIOR)
-            .with_left(self.color(Side::Left) == Color::EXTERIOR)
-            .with_bottom(self.color(Side::Bottom) == Color::EXTERIOR)
+        EternityIiTiles.exterior_mask(self)
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the tile's exterior sides don't match any real Eternity II
+    /// tile, such as two opposite exterior sides.
+    pub fn class(self) -> TileClass {
+        EternityIiTiles.class(self)
     }
 }
 
+/// Formats the rotated tile as its tile number and rotation, like
+/// `135@Identity`.
+impl Display for RotatedTile {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}@{:?}", self.tile, self.rotation)
+    }
+}
+
+/// Parses a rotated tile from a tile number, like `135`, which has no
+/// rotation; a tile number and rotation as [`Display`]ed, like
+/// `135@HalfTurn`; or four colors in [`Side`] order, like `jaar`, as looked up
+/// by [`RotatedTile::from_colors`].
+impl FromStr for RotatedTile {
+    type Err = RotatedTileParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_tile = |tile: &str| {
+            tile.parse::<u8>()
+                .map(Tile::from_primitive)
+                .map_err(|_| RotatedTileParseError::UnknownTile(tile.to_string()))
+        };
+
+        if let Some((tile, rotation)) = s.split_once('@') {
+            Ok(Self {
+                tile: parse_tile(tile)?,
+                rotation: Rotation::iter()
+                    .find(|r| format!("{r:?}") == rotation)
+                    .ok_or_else(|| RotatedTileParseError::UnknownRotation(rotation.to_string()))?,
+            })
+        } else if s.bytes().all(|b| b.is_ascii_digit()) {
+            Ok(Self {
+                tile: parse_tile(s)?,
+                rotation: Rotation::Identity,
+            })
+        } else {
+            let colors: Vec<_> = s.chars().map(Color::from_char).collect();
+            match colors[..] {
+                [Some(right), Some(top), Some(left), Some(bottom)] => {
+                    Self::from_colors(right, top, left, bottom)
+                        .ok_or_else(|| RotatedTileParseError::NoMatchingTile(s.to_string()))
+                }
+                _ => Err(RotatedTileParseError::InvalidColors(s.to_string())),
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RotatedTileParseError {
+    UnknownTile(String),
+    UnknownRotation(String),
+    InvalidColors(String),
+    NoMatchingTile(String),
+}
+
+impl Display for RotatedTileParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownTile(tile) => write!(f, "unknown tile {tile:?}, expected 0 to 255"),
+            Self::UnknownRotation(rotation) => write!(f, "unknown rotation {rotation:?}"),
+            Self::InvalidColors(colors) => {
+                write!(f, "{colors:?} is not four colors from 'a' to 'w'")
+            }
+            Self::NoMatchingTile(colors) => write!(f, "colors {colors:?} match no tile"),
+        }
+    }
+}
+
+impl std::error::Error for RotatedTileParseError {}
+
 impl Add<Rotation> for RotatedTile {
     type Output = Self;
 
@@ -249,8 +734,47 @@ impl AddAssign<Rotation> for RotatedTile {
     }
 }
 
+/// A board cell as `(x, y)`, using the coordinate system described on
+/// [`sat::Variable`].
+pub type Coord = (U4, U4);
+
+/// A rotated tile fixed at a board cell by a published clue.
+pub type Clue = (U4, U4, RotatedTile);
+
+/// Returns the cell across the given side of a cell, if it is on the board.
+pub fn neighbor((x, y): Coord, side: Side) -> Option<Coord> {
+    let (x, y) = (x.to_primitive(), y.to_primitive());
+    let (x, y) = match side {
+        Side::Right if x < 15 => (x + 1, y),
+        Side::Top if y > 0 => (x, y - 1),
+        Side::Left if x > 0 => (x - 1, y),
+        Side::Bottom if y < 15 => (x, y + 1),
+        _ => return None,
+    };
+    Some((U4::new_masked(x), U4::new_masked(y)))
+}
+
+/// Returns the edge colors directly forced by the given clue placements.
+///
+/// Each clue fixes the colors of its own four sides, and the facing side of
+/// each neighboring cell must carry the same color. Sides facing off the board
+/// are skipped.
+pub fn forced_edge_colors(clues: &[Clue]) -> Vec<(Coord, Side, Color)> {
+    let mut result = Vec::new();
+    for &(x, y, rotated_tile) in clues {
+        for side in Side::iter() {
+            if let Some(neighbor) = neighbor((x, y), side) {
+                let color = rotated_tile.color(side);
+                result.push(((x, y), side, color));
+                result.push((neighbor, side.opposite(), color));
+            }
+        }
+    }
+    result
+}
+
 #[bitint_literals]
-pub fn hints() -> impl Iterator<Item = (U4, U4, RotatedTile)> {
+pub fn hints() -> impl Iterator<Item = Clue> {
     [
         (
             7_U4,
@@ -296,12 +820,68 @@ pub fn hints() -> impl Iterator<Item = (U4, U4, RotatedTile)> {
     .into_iter()
 }
 
+/// Returns the published clues as an array, in the same order as [`hints`].
+pub fn clue_tiles() -> [Clue; 5] {
+    let mut hints = hints();
+    std::array::from_fn(|_| hints.next().unwrap())
+}
+
+/// Returns whether `tile` is placed by one of the published clues.
+pub fn is_clue_tile(tile: Tile) -> bool {
+    clue_tiles()
+        .iter()
+        .any(|&(_, _, rotated_tile)| rotated_tile.tile == tile)
+}
+
+/// Returns a clue for the hint-free puzzle that breaks the board's rotational
+/// symmetry: the lowest-numbered corner tile, pinned to the top-left cell.
+///
+/// Every solution has that tile in some corner, and rotating the board moves it
+/// to each of the others, so exactly one rotation of each solution remains.
+/// This is an alternative to [`hints`], which already break the symmetry while
+/// constraining much more. Combining the two would in general rule out every
+/// solution.
+#[bitint_literals]
+pub fn symmetry_break() -> impl Iterator<Item = Clue> {
+    let rotated_tile = RotatedTile::values()
+        .find(|&rotated_tile| {
+            let mask = rotated_tile.exterior_mask();
+            rotated_tile.class() == TileClass::Corner && mask.top() && mask.left()
+        })
+        .unwrap();
+    std::iter::once((0_U4, 0_U4, rotated_tile))
+}
+
+/// Returns how many tiles are corners, edges, and centers, in that order.
+///
+/// A tile's class doesn't depend on its rotation, so each tile is classified
+/// once, unrotated.
+pub fn tile_class_counts() -> (usize, usize, usize) {
+    let (mut corners, mut edges, mut centers) = (0, 0, 0);
+    for tile in Tile::values() {
+        let rotated_tile = RotatedTile {
+            tile,
+            rotation: Rotation::Identity,
+        };
+        match rotated_tile.class() {
+            TileClass::Corner => corners += 1,
+            TileClass::Edge => edges += 1,
+            TileClass::Center => centers += 1,
+        }
+    }
+    (corners, edges, centers)
+}
+
 #[bitint_literals]
 #[cfg(test)]
 mod tests {
     use strum::IntoEnumIterator;
 
-    use super::{Color, RotatedTile, Rotation, Side, Tile};
+    use super::{
+        clue_tiles, forced_edge_colors, hints, is_clue_tile, symmetry_break, tile_class_counts,
+        Color, ExteriorMask, FlipAxis, RotatedTile, RotatedTileParseError, Rotation, Side, Tile,
+        TileClass,
+    };
 
     #[test]
     fn rotate_side() {
@@ -369,6 +949,224 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_rotated_tile() {
+        let tile = Tile::from_primitive;
+        assert_eq!(
+            "135".parse(),
+            Ok(RotatedTile {
+                tile: tile(135),
+                rotation: Rotation::Identity,
+            }),
+        );
+        assert_eq!(
+            "135@HalfTurn".parse(),
+            Ok(RotatedTile {
+                tile: tile(135),
+                rotation: Rotation::HalfTurn,
+            }),
+        );
+        assert_eq!(
+            "rjaa".parse(),
+            Ok(RotatedTile {
+                tile: tile(0),
+                rotation: Rotation::QuarterTurnLeft,
+            }),
+        );
+        for rotated_tile in [RotatedTile::ZERO, RotatedTile::MAX] {
+            assert_eq!(rotated_tile.to_string().parse(), Ok(rotated_tile));
+        }
+
+        assert_eq!(
+            "135@Sideways".parse::<RotatedTile>(),
+            Err(RotatedTileParseError::UnknownRotation(
+                "Sideways".to_string()
+            )),
+        );
+        assert_eq!(
+            "256".parse::<RotatedTile>().unwrap_err().to_string(),
+            "unknown tile \"256\", expected 0 to 255",
+        );
+    }
+
+    #[test]
+    fn tiles_matching_right_edge() {
+        let color = Color::from_char('j').unwrap();
+        let matches = RotatedTile::tiles_matching(Some(color), None, None, None);
+        // Each side of each tile with the color can be rotated to the right.
+        let sides = Tile::values()
+            .flat_map(Tile::colors)
+            .filter(|&side_color| side_color == color)
+            .count();
+        assert_eq!(matches.len(), sides);
+        assert!(matches
+            .iter()
+            .all(|rotated_tile| rotated_tile.color(Side::Right) == color));
+        assert!(matches.contains(&RotatedTile {
+            tile: Tile::from_primitive(0),
+            rotation: Rotation::Identity,
+        }));
+        assert_eq!(
+            RotatedTile::tiles_matching(None, None, None, None).len(),
+            1024,
+        );
+    }
+
+    #[test]
+    fn from_colors() {
+        let color = |c| Color::from_char(c).unwrap();
+        assert_eq!(
+            RotatedTile::from_colors(color('j'), color('a'), color('a'), color('r')),
+            Some(RotatedTile {
+                tile: Tile::from_primitive(0),
+                rotation: Rotation::Identity,
+            }),
+        );
+        assert_eq!(
+            RotatedTile::from_colors(color('r'), color('j'), color('a'), color('a')),
+            Some(RotatedTile {
+                tile: Tile::from_primitive(0),
+                rotation: Rotation::QuarterTurnLeft,
+            }),
+        );
+        assert_eq!(
+            RotatedTile::from_colors(color('a'), color('a'), color('a'), color('a')),
+            None,
+        );
+    }
+
+    #[test]
+    fn flipped() {
+        let rotated_tile = |tile_id, rotation| RotatedTile {
+            tile: Tile::from_primitive(tile_id),
+            rotation,
+        };
+
+        // Symmetric across the vertical axis.
+        let symmetric = rotated_tile(16, Rotation::QuarterTurnLeft);
+        assert_eq!(symmetric.flipped(FlipAxis::Vertical), Some(symmetric));
+
+        // Tiles 0 and 255 are mirror images of each other.
+        let corner = rotated_tile(0, Rotation::Identity);
+        let partner = rotated_tile(255, Rotation::QuarterTurnLeft);
+        assert_eq!(corner.flipped(FlipAxis::Vertical), Some(partner));
+        assert_eq!(partner.flipped(FlipAxis::Vertical), Some(corner));
+
+        // No tile mirrors tile 1.
+        for axis in FlipAxis::iter() {
+            assert_eq!(rotated_tile(1, Rotation::Identity).flipped(axis), None);
+        }
+    }
+
+    #[test]
+    fn min_rotation() {
+        for tile in Tile::values() {
+            let rotations = tile.rotations();
+            let min = rotations[0].min_rotation();
+            for rotated_tile in rotations {
+                assert_eq!(rotated_tile.min_rotation(), min);
+                assert!(min.colors() <= rotated_tile.colors());
+            }
+        }
+    }
+
+    #[test]
+    fn side_and_rotation_from_u8() {
+        assert_eq!(Side::from_u8(3), Some(Side::Bottom));
+        assert_eq!(Side::from_u8(4), None);
+        assert_eq!(Rotation::from_u8(3), Some(Rotation::QuarterTurnRight));
+        assert_eq!(Rotation::from_u8(4), None);
+        for (value, side) in Side::iter().enumerate() {
+            assert_eq!(Side::from_u8(value as u8), Some(side));
+        }
+        for (value, rotation) in Rotation::iter().enumerate() {
+            assert_eq!(Rotation::from_u8(value as u8), Some(rotation));
+        }
+    }
+
+    #[test]
+    fn rotation_arithmetic() {
+        assert_eq!(Rotation::Identity.times(5), Rotation::QuarterTurnLeft);
+        assert_eq!(Rotation::HalfTurn.times(3), Rotation::QuarterTurnLeft);
+        assert_eq!(
+            Vec::from_iter(Rotation::iter()),
+            Rotation::QUARTER_TURNS.to_vec()
+        );
+        for (n, &quarter_turns) in Rotation::QUARTER_TURNS.iter().enumerate() {
+            assert_eq!(Rotation::Identity.times(n as u8), quarter_turns);
+        }
+        for a in Rotation::iter() {
+            assert_eq!(a.times(4), a);
+            assert_eq!(a + Rotation::Identity, a);
+            assert_eq!(a.times(2).times(2), a);
+            for b in Rotation::iter() {
+                assert_eq!(a + b, b + a);
+                let mut sum = a;
+                sum += b;
+                assert_eq!(sum, a + b);
+            }
+        }
+    }
+
+    #[test]
+    fn tile_rotations() {
+        let tile = Tile::from_primitive(17);
+        let rotations = tile.rotations();
+        assert!(rotations
+            .iter()
+            .all(|rotated_tile| rotated_tile.tile == tile));
+        assert_eq!(
+            rotations.map(|rotated_tile| rotated_tile.rotation),
+            [
+                Rotation::Identity,
+                Rotation::QuarterTurnLeft,
+                Rotation::HalfTurn,
+                Rotation::QuarterTurnRight,
+            ],
+        );
+        assert!(rotations
+            .iter()
+            .map(|rotated_tile| rotated_tile.rotation)
+            .eq(Rotation::iter()));
+    }
+
+    #[test]
+    fn class() {
+        for (tile_id, class) in [
+            (0, TileClass::Corner),
+            (128, TileClass::Edge),
+            (135, TileClass::Center),
+        ] {
+            for rotation in Rotation::iter() {
+                let tile = Tile::from_primitive(tile_id);
+                assert_eq!(RotatedTile { tile, rotation }.class(), class);
+            }
+        }
+    }
+
+    #[test]
+    fn eternity_ii_tile_class_counts() {
+        assert_eq!(tile_class_counts(), (4, 56, 196));
+    }
+
+    #[test]
+    fn is_valid_tile_mask() {
+        for mask in 0..16 {
+            let valid = matches!(
+                mask,
+                0b0000 | 0b0001 | 0b0010 | 0b0100 | 0b1000 | 0b0011 | 0b0110 | 0b1100 | 0b1001,
+            );
+            assert_eq!(
+                ExteriorMask::new_masked(mask).is_valid_tile_mask(),
+                valid,
+                "{mask:#06b}",
+            );
+        }
+        for rotated_tile in RotatedTile::values() {
+            assert!(rotated_tile.exterior_mask().is_valid_tile_mask());
+        }
+    }
+
     #[test]
     fn edge_colors() {
         let t0 = Tile::from_primitive(0);
@@ -383,4 +1181,126 @@ mod tests {
         assert_eq!(Color::from_char('f'), Some(t128.color(Side::Bottom)));
         assert_eq!(Color::from_char('a'), Some(t128.color(Side::Left)));
     }
+
+    #[test]
+    fn clues_force_their_own_edges() {
+        let clues = Vec::from_iter(hints());
+        let forced = forced_edge_colors(&clues);
+
+        // No clue is on the perimeter, so every side of every clue is forced
+        // along with the facing side of its neighbor.
+        assert_eq!(forced.len(), 40);
+        for &(x, y, rotated_tile) in &clues {
+            let own = Vec::from_iter(
+                forced
+                    .iter()
+                    .filter(|&&(coord, _, _)| coord == (x, y))
+                    .map(|&(_, side, color)| (side, color)),
+            );
+            assert_eq!(
+                own,
+                Vec::from_iter(Side::iter().map(|side| (side, rotated_tile.color(side)))),
+            );
+        }
+
+        let (x, y, rotated_tile) = clues[0];
+        assert!(forced.contains(&((x + 1_U4, y), Side::Left, rotated_tile.color(Side::Right))));
+        assert!(forced.contains(&((x, y - 1_U4), Side::Bottom, rotated_tile.color(Side::Top))));
+    }
+
+    #[test]
+    fn clue_tiles_match_hints() {
+        assert_eq!(Vec::from_iter(hints()), clue_tiles());
+        assert_eq!(
+            Vec::from_iter(
+                Tile::values()
+                    .filter(|&tile| is_clue_tile(tile))
+                    .map(|tile| tile.to_primitive())
+            ),
+            [76, 125, 135, 179, 211],
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Tile::from_primitive(0).to_string(), "0");
+        assert_eq!(Tile::from_primitive(135).to_string(), "135");
+        assert_eq!(
+            RotatedTile {
+                tile: Tile::from_primitive(0),
+                rotation: Rotation::Identity,
+            }
+            .to_string(),
+            "0@Identity",
+        );
+        assert_eq!(
+            RotatedTile {
+                tile: Tile::from_primitive(135),
+                rotation: Rotation::QuarterTurnRight,
+            }
+            .to_string(),
+            "135@QuarterTurnRight",
+        );
+    }
+
+    #[test]
+    fn color_frequencies() {
+        let frequencies = Tile::color_frequencies();
+        assert_eq!(frequencies.iter().sum::<usize>(), 1024);
+        // The board has 16 exterior sides along each of its four edges.
+        assert_eq!(frequencies[Color::EXTERIOR.to_primitive() as usize], 64);
+        assert_eq!(frequencies[Color::B.to_primitive() as usize], 24);
+        assert_eq!(frequencies[Color::C.to_primitive() as usize], 48);
+        assert_eq!(frequencies[Color::W.to_primitive() as usize], 50);
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(
+            Tile::from_primitive(0).colors(),
+            [b'j', b'a', b'a', b'r'].map(|b| Color::from_byte_char(b).unwrap()),
+        );
+        for rotated_tile in RotatedTile::values() {
+            assert_eq!(
+                rotated_tile.colors(),
+                [Side::Right, Side::Top, Side::Left, Side::Bottom]
+                    .map(|side| rotated_tile.color(side)),
+            );
+        }
+    }
+
+    #[test]
+    fn symmetry_break_pins_first_corner() {
+        assert_eq!(
+            Vec::from_iter(symmetry_break()),
+            [(
+                0_U4,
+                0_U4,
+                RotatedTile {
+                    tile: Tile::from_primitive(0),
+                    rotation: Rotation::Identity,
+                },
+            )],
+        );
+    }
+
+    #[test]
+    fn no_tile_is_rotationally_symmetric() {
+        assert_eq!(
+            Tile::values()
+                .map(|tile| tile.distinct_rotations() as usize)
+                .sum::<usize>(),
+            1024,
+        );
+        assert!(!Tile::values().any(Tile::is_rotationally_symmetric));
+    }
+
+    #[test]
+    fn rotated_tile_values() {
+        let values = Vec::from_iter(RotatedTile::values());
+        assert_eq!(values.len(), 1024);
+        assert_eq!(values.first(), Some(&RotatedTile::ZERO));
+        assert_eq!(values.last(), Some(&RotatedTile::MAX));
+        assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+    }
 }
@@ -17,13 +17,52 @@ macro_rules! rotated_tile {
     };
 }
 
+/// Builds an [`ArrayMosaic`](crate::mosaic::ArrayMosaic) from rows of cells,
+/// each a tile id, a tile id and [`Rotation`](crate::Rotation) variant, or
+/// `@` and a [`RotatedTile`](crate::RotatedTile) expression.
 #[macro_export]
 macro_rules! mosaic {
-    ($( [$( $( $tile_id:literal )? $( $rotation:ident )? $( @$rotated_tile:expr )? ),+] ),+) => {
+    ($( [$( $cells:tt )*] ),+ $(,)?) => {
         $crate::mosaic::ArrayMosaic {
-            tiles: [$( [$(
-                $crate::rotated_tile!($( $tile_id )? $( $rotation )? $( @$rotated_tile )?)
-            ),+] ),+],
+            tiles: [$( $crate::mosaic_row!($( $cells )*) ),+],
+        }
+    };
+}
+
+/// Builds one row of [`mosaic!`] by munching cells into an array of rotated
+/// tiles. Cells can't be matched with a single repetition because all three
+/// forms are optional, which would make a trailing comma ambiguous.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! mosaic_row {
+    (@munch [$( $done:expr, )*]) => {
+        [$( $done ),*]
+    };
+    (@munch [$( $done:expr, )*] $tile_id:literal $rotation:ident $(, $( $rest:tt )* )?) => {
+        $crate::mosaic_row!(
+            @munch [$( $done, )* $crate::rotated_tile!($tile_id $rotation),] $($( $rest )*)?
+        )
+    };
+    (@munch [$( $done:expr, )*] $tile_id:literal $(, $( $rest:tt )* )?) => {
+        $crate::mosaic_row!(
+            @munch [$( $done, )* $crate::rotated_tile!($tile_id),] $($( $rest )*)?
+        )
+    };
+    (@munch [$( $done:expr, )*] @$rotated_tile:expr $(, $( $rest:tt )* )?) => {
+        $crate::mosaic_row!(@munch [$( $done, )* $rotated_tile,] $($( $rest )*)?)
+    };
+    ($( $cells:tt )+) => {
+        $crate::mosaic_row!(@munch [] $( $cells )+)
+    };
+}
+
+/// Builds an [`ArrayMosaic`](crate::mosaic::ArrayMosaic) from rows of
+/// [`RotatedTile`](crate::RotatedTile) expressions.
+#[macro_export]
+macro_rules! mosaic_rt {
+    ($( [$( $rotated_tile:expr ),+ $(,)?] ),+ $(,)?) => {
+        $crate::mosaic::ArrayMosaic {
+            tiles: [$( [$( $rotated_tile ),+] ),+],
         }
     };
 }
@@ -47,3 +86,41 @@ macro_rules! btree_set {
         result
     }}
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{RotatedTile, Rotation, Tile};
+
+    #[test]
+    fn mosaic_accepts_trailing_commas() {
+        let rotated_tile = RotatedTile {
+            tile: Tile::from_primitive(17),
+            rotation: Rotation::HalfTurn,
+        };
+        let expected = mosaic![[0, 1 QuarterTurnLeft], [16, @rotated_tile]];
+        assert_eq!(
+            mosaic![[0, 1 QuarterTurnLeft,], [16, @rotated_tile,],],
+            expected
+        );
+        assert_eq!(
+            mosaic![
+                [0, 1 QuarterTurnLeft],
+                [16, 17 HalfTurn],
+            ],
+            expected,
+        );
+    }
+
+    #[test]
+    fn mosaic_rt() {
+        let tiles = Vec::from_iter(RotatedTile::values().take(4));
+        assert_eq!(
+            mosaic_rt![[tiles[0], tiles[1]], [tiles[2], tiles[3]]],
+            mosaic![[0, 0 QuarterTurnLeft], [0 HalfTurn, 0 QuarterTurnRight]],
+        );
+        assert_eq!(
+            mosaic_rt![[tiles[0], tiles[1],], [tiles[2], tiles[3],],],
+            mosaic_rt![[tiles[0], tiles[1]], [tiles[2], tiles[3]]],
+        );
+    }
+}
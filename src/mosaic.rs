@@ -1,14 +1,17 @@
 use std::cmp::Ordering;
 use std::fmt::{self, Debug, Display, Formatter};
-use std::hash::Hash;
-use std::iter::repeat;
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::ops::{Add, AddAssign};
 
+use bitvec::bitarr;
+use strum::IntoEnumIterator;
+
 use crate::edge::ArrayEdge;
 use crate::rectangular::{
-    HorizontalSide, RectangularRotation, RectangularSide, SideExt, VerticalSide,
+    HorizontalSide, RectangularRotation, RectangularSide, Reflection, SideExt, VerticalSide,
 };
-use crate::{RotatedTile, Rotation, Side, Tile};
+use crate::{is_clue_tile, Color, ExteriorMask, RotatedTile, Rotation, Side, Tile};
 
 pub trait MosaicBounds: Clone + Debug + PartialEq + Eq + PartialOrd + Ord + Hash {}
 
@@ -53,6 +56,16 @@ pub trait RectangularMosaic<const W: usize, const H: usize>: MosaicBounds {
         edge
     }
 
+    /// Returns the right, top, left, and bottom edges, in that order.
+    fn all_edges(&self) -> (ArrayEdge<H>, ArrayEdge<W>, ArrayEdge<H>, ArrayEdge<W>) {
+        (
+            self.vertical_edge(VerticalSide::Right),
+            self.horizontal_edge(HorizontalSide::Top),
+            self.vertical_edge(VerticalSide::Left),
+            self.horizontal_edge(HorizontalSide::Bottom),
+        )
+    }
+
     type WithRectangularRotation<'a>: RectangularMosaic<W, H> + 'a
     where
         Self: 'a;
@@ -62,10 +75,21 @@ pub trait RectangularMosaic<const W: usize, const H: usize>: MosaicBounds {
         rotation: RectangularRotation,
     ) -> Self::WithRectangularRotation<'_>;
 
-    fn display(&self, indent: usize) -> MosaicDisplay<W, H, Self> {
+    fn display(&self, indent: usize) -> MosaicDisplay<'_, W, H, Self> {
+        MosaicDisplay {
+            mosaic: self,
+            indent,
+            ansi: false,
+        }
+    }
+
+    /// Like [`Self::display`], but colors each edge label for a terminal using
+    /// 24-bit escapes for its [`Color::rgb`].
+    fn display_ansi(&self, indent: usize) -> MosaicDisplay<'_, W, H, Self> {
         MosaicDisplay {
             mosaic: self,
             indent,
+            ansi: true,
         }
     }
 
@@ -80,6 +104,103 @@ pub trait RectangularMosaic<const W: usize, const H: usize>: MosaicBounds {
         }
         result
     }
+
+    /// Copies the `SW` by `SH` window of tiles whose top-left corner is at
+    /// (`x0`, `y0`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window extends past the right or bottom of this mosaic.
+    fn subgrid<const SW: usize, const SH: usize>(
+        &self,
+        x0: usize,
+        y0: usize,
+    ) -> ArrayMosaic<SW, SH> {
+        assert!(x0 + SW <= W && y0 + SH <= H);
+        let mut result = ArrayMosaic {
+            tiles: [[RotatedTile::ZERO; SW]; SH],
+        };
+        for y in 0..SH {
+            for x in 0..SW {
+                result.tiles[y][x] = self.get(x0 + x, y0 + y);
+            }
+        }
+        result
+    }
+
+    /// Returns a view of this mosaic with its tile grid mirrored.
+    ///
+    /// See [`Reflection`] for why this is not a physical operation.
+    fn with_reflection(&self, reflection: Reflection) -> ReflectedMosaic<'_, W, H, Self> {
+        ReflectedMosaic {
+            mosaic: self,
+            reflection,
+        }
+    }
+
+    /// Writes this mosaic as a single-line JSON object holding row-major
+    /// `tiles` and `rotations` arrays, with rotations as quarter turns left.
+    fn write_json(&self, mut w: impl Write) -> io::Result<()> {
+        let grid = |field: fn(RotatedTile) -> u8| {
+            Vec::from_iter((0..H).map(|y| {
+                let row = Vec::from_iter((0..W).map(|x| field(self.get(x, y)).to_string()));
+                format!("[{}]", row.join(","))
+            }))
+            .join(",")
+        };
+        write!(
+            w,
+            "{{\"tiles\":[{}],\"rotations\":[{}]}}",
+            grid(|rotated_tile| rotated_tile.tile.to_primitive()),
+            grid(|rotated_tile| rotated_tile.rotation.to_primitive()),
+        )
+    }
+
+    /// Returns the least of this mosaic's rectangular rotations.
+    ///
+    /// Every rectangular rotation of a mosaic has the same canonical form.
+    fn rectangular_canonical(&self) -> ArrayMosaic<W, H> {
+        RectangularRotation::iter()
+            .map(|rotation| self.with_rectangular_rotation(rotation))
+            .min()
+            .unwrap()
+            .to_array_mosaic()
+    }
+
+    /// Returns the number of interior shared edges whose abutting colors
+    /// differ.
+    fn mismatch_count(&self) -> usize {
+        let mut count = 0;
+        for y in 0..H {
+            for x in 0..W {
+                let rotated_tile = self.get(x, y);
+                if x + 1 < W
+                    && rotated_tile.color(Side::Right) != self.get(x + 1, y).color(Side::Left)
+                {
+                    count += 1;
+                }
+                if y + 1 < H
+                    && rotated_tile.color(Side::Bottom) != self.get(x, y + 1).color(Side::Top)
+                {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// Returns whether this mosaic is a complete board: every interior edge
+    /// matches and every perimeter side is [`Color::EXTERIOR`].
+    fn is_valid(&self) -> bool {
+        self.mismatch_count() == 0
+            && self
+                .vertical_edge(VerticalSide::Right)
+                .iter()
+                .chain(self.vertical_edge(VerticalSide::Left).iter())
+                .chain(self.horizontal_edge(HorizontalSide::Top).iter())
+                .chain(self.horizontal_edge(HorizontalSide::Bottom).iter())
+                .all(|color| color == Color::EXTERIOR)
+    }
 }
 
 pub trait SquareMosaic<const N: usize>: RectangularMosaic<N, N> {
@@ -95,26 +216,51 @@ pub trait SquareMosaic<const N: usize>: RectangularMosaic<N, N> {
         Self: 'a;
 
     fn with_square_rotation(&self, rotation: Rotation) -> Self::WithSquareRotation<'_>;
+
+    /// Returns the least of this mosaic's square rotations.
+    ///
+    /// Every square rotation of a mosaic has the same canonical form.
+    fn canonical(&self) -> ArrayMosaic<N, N> {
+        Rotation::iter()
+            .map(|rotation| self.with_square_rotation(rotation))
+            .min()
+            .unwrap()
+            .to_array_mosaic()
+    }
 }
 
 pub struct MosaicDisplay<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> {
     mosaic: &'a M,
     indent: usize,
+    ansi: bool,
+}
+
+impl<const W: usize, const H: usize, M: RectangularMosaic<W, H>> MosaicDisplay<'_, W, H, M> {
+    /// Returns `text` wrapped in escape codes for `color` if this display uses
+    /// ANSI colors.
+    fn paint(&self, color: Color, text: String) -> String {
+        if self.ansi {
+            let (r, g, b) = color.rgb();
+            format!("\x1b[38;2;{r};{g};{b}m{text}\x1b[0m")
+        } else {
+            text
+        }
+    }
 }
 
 impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> Display
     for MosaicDisplay<'a, W, H, M>
 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let indent = String::from_iter(repeat(' ').take(self.indent));
+        let indent = " ".repeat(self.indent);
         for y in 0..H {
             let write_top_row = |f: &mut Formatter| {
                 write!(f, "{indent}")?;
                 for x in 0..W {
                     let rotated_tile = self.mosaic.get(x, y);
-                    let left_is_border = rotated_tile.color(Side::Left).is_border();
-                    let top_is_border = rotated_tile.color(Side::Top).is_border();
-                    let right_is_border = rotated_tile.color(Side::Right).is_border();
+                    let left_is_border = rotated_tile.color(Side::Left).is_exterior();
+                    let top_is_border = rotated_tile.color(Side::Top).is_exterior();
+                    let right_is_border = rotated_tile.color(Side::Right).is_exterior();
                     let top_left = match (left_is_border, top_is_border) {
                         (true, _) => "▄",
                         (false, true) => "▗",
@@ -129,11 +275,9 @@ impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> Display
                         (true, false) => "▖",
                         (false, false) => "┐",
                     };
-                    write!(
-                        f,
-                        "{top_left}{top}{:^2}{top}{top_right}",
-                        rotated_tile.color(Side::Top)
-                    )?;
+                    let color = rotated_tile.color(Side::Top);
+                    let color = self.paint(color, format!("{color:^2}"));
+                    write!(f, "{top_left}{top}{color}{top}{top_right}")?;
                 }
                 writeln!(f)?;
                 write!(f, "{indent}")?;
@@ -143,8 +287,8 @@ impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> Display
                 |f: &mut Formatter, arrow_row: Option<u8>, clue_row: Option<u8>| {
                     for x in 0..W {
                         let rotated_tile = self.mosaic.get(x, y);
-                        let left_is_border = rotated_tile.color(Side::Left).is_border();
-                        let right_is_border = rotated_tile.color(Side::Right).is_border();
+                        let left_is_border = rotated_tile.color(Side::Left).is_exterior();
+                        let right_is_border = rotated_tile.color(Side::Right).is_exterior();
                         let left = match left_is_border {
                             true => "█",
                             false => "│",
@@ -156,7 +300,7 @@ impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> Display
                             _ => " ",
                         };
                         let clue = match (clue_row, rotated_tile.tile.to_primitive()) {
-                            (Some(0), 76 | 125 | 135 | 179 | 211) => "CLUE",
+                            (Some(0), _) if is_clue_tile(rotated_tile.tile) => "CLUE",
                             (Some(1), 76) => " C3 ",
                             (Some(1), 179) => "C14 ",
                             (Some(1), 135) => " I8 ",
@@ -183,12 +327,14 @@ impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> Display
             let write_middle_row = |f: &mut Formatter| {
                 for x in 0..W {
                     let rotated_tile = self.mosaic.get(x, y);
+                    let left = rotated_tile.color(Side::Left);
+                    let right = rotated_tile.color(Side::Right);
                     write!(
                         f,
                         "{}{:^8}{}",
-                        rotated_tile.color(Side::Left),
+                        self.paint(left, left.to_string()),
                         rotated_tile.tile.to_primitive(),
-                        rotated_tile.color(Side::Right),
+                        self.paint(right, right.to_string()),
                     )?;
                 }
                 writeln!(f)?;
@@ -198,9 +344,9 @@ impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> Display
             let write_bottom_row = |f: &mut Formatter| {
                 for x in 0..W {
                     let rotated_tile = self.mosaic.get(x, y);
-                    let left_is_border = rotated_tile.color(Side::Left).is_border();
-                    let bottom_is_border = rotated_tile.color(Side::Bottom).is_border();
-                    let right_is_border = rotated_tile.color(Side::Right).is_border();
+                    let left_is_border = rotated_tile.color(Side::Left).is_exterior();
+                    let bottom_is_border = rotated_tile.color(Side::Bottom).is_exterior();
+                    let right_is_border = rotated_tile.color(Side::Right).is_exterior();
                     let bottom_left = match (left_is_border, bottom_is_border) {
                         (true, _) => "▀",
                         (false, true) => "▝",
@@ -215,11 +361,9 @@ impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> Display
                         (true, false) => "▘",
                         (false, false) => "┘",
                     };
-                    write!(
-                        f,
-                        "{bottom_left}{bottom}{:^2}{bottom}{bottom_right}",
-                        rotated_tile.color(Side::Bottom),
-                    )?;
+                    let color = rotated_tile.color(Side::Bottom);
+                    let color = self.paint(color, format!("{color:^2}"));
+                    write!(f, "{bottom_left}{bottom}{color}{bottom}{bottom_right}")?;
                 }
                 writeln!(f)?;
                 Ok(())
@@ -235,7 +379,132 @@ impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> Display
     }
 }
 
-#[derive(Debug, Hash)]
+/// RGB values for drawing each [`Color`], indexed by its primitive value. Read
+/// them through [`Color::rgb`].
+///
+/// The exterior is gray. The other colors are Kelly's colors of maximum
+/// contrast, with teal standing in for Kelly's gray.
+pub const PALETTE: [[u8; 3]; 23] = [
+    [0x80, 0x80, 0x80],
+    [0xf2, 0xf3, 0xf4],
+    [0x22, 0x22, 0x22],
+    [0xf3, 0xc3, 0x00],
+    [0x87, 0x56, 0x92],
+    [0xf3, 0x84, 0x00],
+    [0xa1, 0xca, 0xf1],
+    [0xbe, 0x00, 0x32],
+    [0xc2, 0xb2, 0x80],
+    [0x00, 0x80, 0x80],
+    [0x00, 0x88, 0x56],
+    [0xe6, 0x8f, 0xac],
+    [0x00, 0x67, 0xa5],
+    [0xf9, 0x93, 0x79],
+    [0x60, 0x4e, 0x97],
+    [0xf6, 0xa6, 0x00],
+    [0xb3, 0x44, 0x6c],
+    [0xdc, 0xd3, 0x00],
+    [0x88, 0x2d, 0x17],
+    [0x8d, 0xb6, 0x00],
+    [0x65, 0x45, 0x22],
+    [0xe2, 0x58, 0x22],
+    [0x2b, 0x3d, 0x26],
+];
+
+/// Renders a mosaic as a PNG image with square cells `cell_px` pixels wide.
+///
+/// Each tile is split along its diagonals into four triangles filled with the
+/// [`PALETTE`] entry for the color on that side. The diagonals are drawn black.
+#[cfg(feature = "image")]
+pub fn render_png<const W: usize, const H: usize, O: Write>(
+    mosaic: &impl RectangularMosaic<W, H>,
+    cell_px: u32,
+    out: O,
+) -> io::Result<()> {
+    let width = W as u32 * cell_px;
+    let height = H as u32 * cell_px;
+    let mut data = Vec::with_capacity(3 * width as usize * height as usize);
+    for py in 0..height {
+        for px in 0..width {
+            let rotated_tile = mosaic.get((px / cell_px) as usize, (py / cell_px) as usize);
+
+            // Work in doubled coordinates measured to pixel centers so the
+            // diagonals fall on exact comparisons.
+            let u = 2 * (px % cell_px) + 1;
+            let v = 2 * (py % cell_px) + 1;
+            let size = 2 * cell_px;
+            let side = if u == v || u + v == size {
+                None
+            } else if v < u && u + v < size {
+                Some(Side::Top)
+            } else if v > u && u + v > size {
+                Some(Side::Bottom)
+            } else if u < v {
+                Some(Side::Left)
+            } else {
+                Some(Side::Right)
+            };
+            data.extend(match side {
+                Some(side) => {
+                    let (r, g, b) = rotated_tile.color(side).rgb();
+                    [r, g, b]
+                }
+                None => [0, 0, 0],
+            });
+        }
+    }
+
+    let mut encoder = png::Encoder::new(out, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&data)?;
+    Ok(())
+}
+
+/// Renders a mosaic as an SVG document with square cells `cell_px` units wide.
+///
+/// Like [`render_png`], each tile is split along its diagonals into four
+/// triangles filled with the [`Color::rgb`] of the color on that side and
+/// outlined in black. It needs no image dependency, so it suits the web.
+pub fn render_svg<const W: usize, const H: usize>(
+    mosaic: &impl RectangularMosaic<W, H>,
+    cell_px: u32,
+) -> String {
+    use std::fmt::Write as _;
+
+    let width = W as u32 * cell_px;
+    let height = H as u32 * cell_px;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\">",
+    );
+    for y in 0..H {
+        for x in 0..W {
+            let rotated_tile = mosaic.get(x, y);
+            let (left, top) = (x as u32 * cell_px, y as u32 * cell_px);
+            let (right, bottom) = (left + cell_px, top + cell_px);
+            let center_x = left as f64 + cell_px as f64 / 2.0;
+            let center_y = top as f64 + cell_px as f64 / 2.0;
+            for (side, (ax, ay), (bx, by)) in [
+                (Side::Top, (left, top), (right, top)),
+                (Side::Right, (right, top), (right, bottom)),
+                (Side::Bottom, (right, bottom), (left, bottom)),
+                (Side::Left, (left, bottom), (left, top)),
+            ] {
+                let (r, g, b) = rotated_tile.color(side).rgb();
+                write!(
+                    svg,
+                    "<polygon points=\"{ax},{ay} {bx},{by} {center_x},{center_y}\" \
+                     fill=\"#{r:02x}{g:02x}{b:02x}\" stroke=\"black\"/>",
+                )
+                .unwrap();
+            }
+        }
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+#[derive(Debug)]
 pub struct RotatedRectangularMosaic<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>>
 {
     pub mosaic: &'a M,
@@ -279,10 +548,7 @@ impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> Clone
     for RotatedRectangularMosaic<'a, W, H, M>
 {
     fn clone(&self) -> Self {
-        Self {
-            mosaic: self.mosaic,
-            rotation: self.rotation,
-        }
+        *self
     }
 }
 
@@ -311,6 +577,18 @@ impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> Eq
 {
 }
 
+impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> Hash
+    for RotatedRectangularMosaic<'a, W, H, M>
+{
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        for y in 0..H {
+            for x in 0..W {
+                self.get(x, y).hash(state);
+            }
+        }
+    }
+}
+
 impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> PartialOrd
     for RotatedRectangularMosaic<'a, W, H, M>
 {
@@ -365,7 +643,7 @@ impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> AddAssign<R
     }
 }
 
-#[derive(Debug, Hash)]
+#[derive(Debug)]
 pub struct RotatedSquareMosaic<'a, const N: usize, M: SquareMosaic<N>> {
     pub mosaic: &'a M,
     pub rotation: Rotation,
@@ -421,10 +699,7 @@ impl<'a, const N: usize, M: SquareMosaic<N>> SquareMosaic<N> for RotatedSquareMo
 
 impl<'a, const N: usize, M: SquareMosaic<N>> Clone for RotatedSquareMosaic<'a, N, M> {
     fn clone(&self) -> Self {
-        Self {
-            mosaic: self.mosaic,
-            rotation: self.rotation,
-        }
+        *self
     }
 }
 
@@ -445,6 +720,16 @@ impl<'a, const N: usize, M: SquareMosaic<N>> PartialEq for RotatedSquareMosaic<'
 
 impl<'a, const N: usize, M: SquareMosaic<N>> Eq for RotatedSquareMosaic<'a, N, M> {}
 
+impl<'a, const N: usize, M: SquareMosaic<N>> Hash for RotatedSquareMosaic<'a, N, M> {
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        for y in 0..N {
+            for x in 0..N {
+                self.get(x, y).hash(state);
+            }
+        }
+    }
+}
+
 impl<'a, const N: usize, M: SquareMosaic<N>> PartialOrd for RotatedSquareMosaic<'a, N, M> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -489,25 +774,52 @@ impl<'a, const N: usize, M: SquareMosaic<N>> AddAssign<Rotation> for RotatedSqua
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct ArrayMosaic<const W: usize, const H: usize> {
-    pub tiles: [[RotatedTile; W]; H],
+impl<'a, const N: usize, M: SquareMosaic<N>> RotatedSquareMosaic<'a, N, M> {
+    /// Copies the underlying mosaic into an [`OwnedRotatedMosaic`] with the
+    /// same rotation, which doesn't borrow from this one.
+    pub fn to_owned(&self) -> OwnedRotatedMosaic<N> {
+        OwnedRotatedMosaic {
+            mosaic: self.mosaic.to_array_mosaic(),
+            rotation: self.rotation,
+        }
+    }
 }
 
-impl<const W: usize, const H: usize> RectangularMosaic<W, H> for ArrayMosaic<W, H> {
+/// Like [`RotatedSquareMosaic`], but owning a copy of its mosaic, so it can be
+/// stored or returned without a lifetime.
+///
+/// Compares, hashes, and orders by its rotated tiles, like the borrowing
+/// version.
+#[derive(Clone, Copy, Debug)]
+pub struct OwnedRotatedMosaic<const N: usize> {
+    pub mosaic: ArrayMosaic<N, N>,
+    pub rotation: Rotation,
+}
+
+impl<const N: usize> OwnedRotatedMosaic<N> {
+    /// Returns the equivalent borrowing view.
+    pub fn as_rotated(&self) -> RotatedSquareMosaic<'_, N, ArrayMosaic<N, N>> {
+        RotatedSquareMosaic {
+            mosaic: &self.mosaic,
+            rotation: self.rotation,
+        }
+    }
+}
+
+impl<const N: usize> RectangularMosaic<N, N> for OwnedRotatedMosaic<N> {
     fn width(&self) -> usize {
-        W
+        N
     }
 
     fn height(&self) -> usize {
-        H
+        N
     }
 
     fn get(&self, x: usize, y: usize) -> RotatedTile {
-        self.tiles[y][x]
+        self.as_rotated().get(x, y)
     }
 
-    type WithRectangularRotation<'a> = RotatedRectangularMosaic<'a, W, H, Self>
+    type WithRectangularRotation<'a> = OwnedRotatedMosaic<N>
     where
         Self: 'a;
 
@@ -515,61 +827,73 @@ impl<const W: usize, const H: usize> RectangularMosaic<W, H> for ArrayMosaic<W,
         &self,
         rotation: RectangularRotation,
     ) -> Self::WithRectangularRotation<'_> {
-        RotatedRectangularMosaic {
-            mosaic: self,
-            rotation,
-        }
+        *self + rotation.to_square()
     }
 }
 
-impl<const N: usize> SquareMosaic<N> for ArrayMosaic<N, N> {
-    type WithSquareRotation<'a> = RotatedSquareMosaic<'a, N, Self>
+impl<const N: usize> SquareMosaic<N> for OwnedRotatedMosaic<N> {
+    type WithSquareRotation<'a> = OwnedRotatedMosaic<N>
     where
         Self: 'a;
 
     fn with_square_rotation(&self, rotation: Rotation) -> Self::WithSquareRotation<'_> {
-        RotatedSquareMosaic {
-            mosaic: self,
-            rotation,
-        }
+        *self + rotation
     }
 }
 
-impl<'a, const W: usize, const H: usize> Add<RectangularRotation> for &'a ArrayMosaic<W, H> {
-    type Output = RotatedRectangularMosaic<'a, W, H, ArrayMosaic<W, H>>;
+impl<const N: usize> PartialEq for OwnedRotatedMosaic<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_rotated() == other.as_rotated()
+    }
+}
 
-    fn add(self, rhs: RectangularRotation) -> Self::Output {
-        RotatedRectangularMosaic {
-            mosaic: self,
-            rotation: rhs,
-        }
+impl<const N: usize> Eq for OwnedRotatedMosaic<N> {}
+
+impl<const N: usize> Hash for OwnedRotatedMosaic<N> {
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        self.as_rotated().hash(state);
     }
 }
 
-impl<'a, const N: usize> Add<Rotation> for &'a ArrayMosaic<N, N> {
-    type Output = RotatedSquareMosaic<'a, N, ArrayMosaic<N, N>>;
+impl<const N: usize> PartialOrd for OwnedRotatedMosaic<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-    fn add(self, rhs: Rotation) -> Self::Output {
-        RotatedSquareMosaic {
-            mosaic: self,
-            rotation: rhs,
-        }
+impl<const N: usize> Ord for OwnedRotatedMosaic<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_rotated().cmp(&other.as_rotated())
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct PackedQuadRotatedTile {
-    pub tiles: [Tile; 4],
-    pub rotations: [Rotation; 4],
+impl<const N: usize> Add<Rotation> for OwnedRotatedMosaic<N> {
+    type Output = Self;
+
+    fn add(mut self, rhs: Rotation) -> Self {
+        self += rhs;
+        self
+    }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct PackedArrayMosaic<const W: usize, const H: usize, const QW: usize, const QH: usize> {
-    pub tiles: [[PackedQuadRotatedTile; QW]; QH],
+impl<const N: usize> AddAssign<Rotation> for OwnedRotatedMosaic<N> {
+    fn add_assign(&mut self, rhs: Rotation) {
+        self.rotation += rhs;
+    }
 }
 
-impl<const W: usize, const H: usize, const QW: usize, const QH: usize> RectangularMosaic<W, H>
-    for PackedArrayMosaic<W, H, QW, QH>
+/// A mosaic with its tile grid mirrored. Individual tiles keep their colors and
+/// rotations.
+///
+/// See [`Reflection`] for why this is not a physical operation.
+#[derive(Debug)]
+pub struct ReflectedMosaic<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> {
+    pub mosaic: &'a M,
+    pub reflection: Reflection,
+}
+
+impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> RectangularMosaic<W, H>
+    for ReflectedMosaic<'a, W, H, M>
 {
     fn width(&self) -> usize {
         W
@@ -580,17 +904,15 @@ impl<const W: usize, const H: usize, const QW: usize, const QH: usize> Rectangul
     }
 
     fn get(&self, x: usize, y: usize) -> RotatedTile {
-        let quad = &self.tiles[y / 2][x / 2];
-        let index = 2 * (y % 2) + x % 2;
-        RotatedTile {
-            tile: quad.tiles[index],
-            rotation: quad.rotations[index],
+        match self.reflection {
+            Reflection::Identity => self.mosaic.get(x, y),
+            Reflection::Horizontal => self.mosaic.get(W - 1 - x, y),
         }
     }
 
-    type WithRectangularRotation<'a> = RotatedRectangularMosaic<'a, W, H, Self>
+    type WithRectangularRotation<'b> = RotatedRectangularMosaic<'b, W, H, Self>
     where
-        Self: 'a;
+        Self: 'b;
 
     fn with_rectangular_rotation(
         &self,
@@ -603,10 +925,10 @@ impl<const W: usize, const H: usize, const QW: usize, const QH: usize> Rectangul
     }
 }
 
-impl<const N: usize, const QN: usize> SquareMosaic<N> for PackedArrayMosaic<N, N, QN, QN> {
-    type WithSquareRotation<'a> = RotatedSquareMosaic<'a, N, Self>
+impl<'a, const N: usize, M: SquareMosaic<N>> SquareMosaic<N> for ReflectedMosaic<'a, N, N, M> {
+    type WithSquareRotation<'b> = RotatedSquareMosaic<'b, N, Self>
     where
-        Self: 'a;
+        Self: 'b;
 
     fn with_square_rotation(&self, rotation: Rotation) -> Self::WithSquareRotation<'_> {
         RotatedSquareMosaic {
@@ -616,54 +938,1120 @@ impl<const N: usize, const QN: usize> SquareMosaic<N> for PackedArrayMosaic<N, N
     }
 }
 
-impl<'a, const W: usize, const H: usize, const QW: usize, const QH: usize> Add<RectangularRotation>
-    for &'a PackedArrayMosaic<W, H, QW, QH>
+impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> Clone
+    for ReflectedMosaic<'a, W, H, M>
 {
-    type Output = RotatedRectangularMosaic<'a, W, H, PackedArrayMosaic<W, H, QW, QH>>;
+    fn clone(&self) -> Self {
+        *self
+    }
+}
 
-    fn add(self, rhs: RectangularRotation) -> Self::Output {
-        RotatedRectangularMosaic {
-            mosaic: self,
-            rotation: rhs,
+impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> Copy
+    for ReflectedMosaic<'a, W, H, M>
+{
+}
+
+impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> PartialEq
+    for ReflectedMosaic<'a, W, H, M>
+{
+    fn eq(&self, other: &Self) -> bool {
+        for y in 0..H {
+            for x in 0..W {
+                if self.get(x, y) != other.get(x, y) {
+                    return false;
+                }
+            }
         }
+        true
     }
 }
 
-impl<'a, const N: usize, const QN: usize> Add<Rotation> for &'a PackedArrayMosaic<N, N, QN, QN> {
-    type Output = RotatedSquareMosaic<'a, N, PackedArrayMosaic<N, N, QN, QN>>;
+impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> Eq
+    for ReflectedMosaic<'a, W, H, M>
+{
+}
 
-    fn add(self, rhs: Rotation) -> Self::Output {
-        RotatedSquareMosaic {
-            mosaic: self,
-            rotation: rhs,
+impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> Hash
+    for ReflectedMosaic<'a, W, H, M>
+{
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        for y in 0..H {
+            for x in 0..W {
+                self.get(x, y).hash(state);
+            }
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::mosaic::{RotatedRectangularMosaic, SquareMosaic};
-    use crate::rectangular::{HorizontalSide, RectangularRotation, VerticalSide};
-    use crate::{RotatedTile, Rotation, Side, Tile};
+impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> PartialOrd
+    for ReflectedMosaic<'a, W, H, M>
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H>> Ord
+    for ReflectedMosaic<'a, W, H, M>
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        for y in 0..H {
+            for x in 0..W {
+                match self.get(x, y).cmp(&other.get(x, y)) {
+                    Ordering::Equal => (),
+                    x => return x,
+                }
+            }
+        }
+        Ordering::Equal
+    }
+}
 
-    use super::{ArrayMosaic, RectangularMosaic, RotatedSquareMosaic};
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ArrayMosaic<const W: usize, const H: usize> {
+    pub tiles: [[RotatedTile; W]; H],
+}
 
-    #[test]
-    fn to_array_mosaic() {
-        let mosaic = mosaic![[0, 1, 2], [10, 11, 12]];
-        assert_eq!(mosaic.to_array_mosaic(), mosaic);
+impl<const W: usize, const H: usize> RectangularMosaic<W, H> for ArrayMosaic<W, H> {
+    fn width(&self) -> usize {
+        W
     }
 
-    #[test]
-    fn rotated_square_mosaic() {
-        let tile_a = Tile::from_primitive(0);
-        let tile_b = Tile::from_primitive(1);
-        let tile_c = Tile::from_primitive(16);
-        let tile_d = Tile::from_primitive(17);
-        let mosaic = ArrayMosaic {
-            tiles: [
-                [
-                    RotatedTile {
+    fn height(&self) -> usize {
+        H
+    }
+
+    fn get(&self, x: usize, y: usize) -> RotatedTile {
+        self.tiles[y][x]
+    }
+
+    type WithRectangularRotation<'a> = RotatedRectangularMosaic<'a, W, H, Self>
+    where
+        Self: 'a;
+
+    fn with_rectangular_rotation(
+        &self,
+        rotation: RectangularRotation,
+    ) -> Self::WithRectangularRotation<'_> {
+        RotatedRectangularMosaic {
+            mosaic: self,
+            rotation,
+        }
+    }
+}
+
+impl<const N: usize> SquareMosaic<N> for ArrayMosaic<N, N> {
+    type WithSquareRotation<'a> = RotatedSquareMosaic<'a, N, Self>
+    where
+        Self: 'a;
+
+    fn with_square_rotation(&self, rotation: Rotation) -> Self::WithSquareRotation<'_> {
+        RotatedSquareMosaic {
+            mosaic: self,
+            rotation,
+        }
+    }
+}
+
+impl<const W: usize, const H: usize> ArrayMosaic<W, H> {
+    /// Returns a copy rotated by `rotation`, with each tile's rotation
+    /// composed accordingly. This is the materialized form of
+    /// [`RectangularMosaic::with_rectangular_rotation`].
+    pub fn rotated_rect(self, rotation: RectangularRotation) -> Self {
+        self.with_rectangular_rotation(rotation).to_array_mosaic()
+    }
+
+    /// Assembles a mosaic from smaller parts, each placed with its top left
+    /// cell at the given `(x, y)` offset, such as two halves or four quadrants
+    /// of a board.
+    ///
+    /// Parts are placed in order. Each placed tile must match the edges of its
+    /// neighbors placed before it, no tile may be used twice, and together the
+    /// parts must cover every cell exactly once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a part doesn't fit at its offset.
+    pub fn overlay<const PW: usize, const PH: usize>(
+        parts: &[((usize, usize), ArrayMosaic<PW, PH>)],
+    ) -> Result<Self, OverlayError> {
+        let mut check = tile_reuse_checker();
+        let mut cells: [[Option<RotatedTile>; W]; H] = [[None; W]; H];
+        for ((part_x, part_y), part) in parts {
+            assert!(
+                part_x + PW <= W && part_y + PH <= H,
+                "a {PW}x{PH} part at ({part_x}, {part_y}) doesn't fit in {W}x{H}",
+            );
+            for part_cell_y in 0..PH {
+                for part_cell_x in 0..PW {
+                    let (x, y) = (part_x + part_cell_x, part_y + part_cell_y);
+                    if cells[y][x].is_some() {
+                        return Err(OverlayError::Overlap { x, y });
+                    }
+                    let rotated_tile = check(part.get(part_cell_x, part_cell_y))?;
+                    for side in Side::iter() {
+                        let neighbor = match side {
+                            Side::Right => cells[y].get(x + 1).copied().flatten(),
+                            Side::Top => y.checked_sub(1).and_then(|y| cells[y][x]),
+                            Side::Left => x.checked_sub(1).and_then(|x| cells[y][x]),
+                            Side::Bottom => cells.get(y + 1).and_then(|row| row[x]),
+                        };
+                        if let Some(neighbor) = neighbor {
+                            if neighbor.color(side.opposite()) != rotated_tile.color(side) {
+                                return Err(OverlayError::EdgeMismatch { x, y, side });
+                            }
+                        }
+                    }
+                    cells[y][x] = Some(rotated_tile);
+                }
+            }
+        }
+
+        let mut mosaic = ArrayMosaic {
+            tiles: [[RotatedTile::ZERO; W]; H],
+        };
+        for (y, row) in cells.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                mosaic.tiles[y][x] = cell.ok_or(OverlayError::Uncovered { x, y })?;
+            }
+        }
+        Ok(mosaic)
+    }
+}
+
+impl<const N: usize> ArrayMosaic<N, N> {
+    /// Returns a copy rotated by `rotation`, with each tile's rotation
+    /// composed accordingly. This is the materialized form of
+    /// [`SquareMosaic::with_square_rotation`].
+    pub fn rotated(self, rotation: Rotation) -> Self {
+        self.with_square_rotation(rotation).to_array_mosaic()
+    }
+}
+
+impl<'a, const W: usize, const H: usize> Add<RectangularRotation> for &'a ArrayMosaic<W, H> {
+    type Output = RotatedRectangularMosaic<'a, W, H, ArrayMosaic<W, H>>;
+
+    fn add(self, rhs: RectangularRotation) -> Self::Output {
+        RotatedRectangularMosaic {
+            mosaic: self,
+            rotation: rhs,
+        }
+    }
+}
+
+impl<'a, const N: usize> Add<Rotation> for &'a ArrayMosaic<N, N> {
+    type Output = RotatedSquareMosaic<'a, N, ArrayMosaic<N, N>>;
+
+    fn add(self, rhs: Rotation) -> Self::Output {
+        RotatedSquareMosaic {
+            mosaic: self,
+            rotation: rhs,
+        }
+    }
+}
+
+/// Returns the placements around the perimeter of a board in clockwise order,
+/// starting from the top-left corner.
+///
+/// Fails on the first placement, in that order, whose exterior sides don't
+/// face off the board.
+pub fn perimeter_tiles<const N: usize>(
+    board: &ArrayMosaic<N, N>,
+) -> Result<Vec<RotatedTile>, IllegalPerimeterPlacement> {
+    let top = (0..N).map(|x| (x, 0));
+    let right = (1..N).map(|y| (N - 1, y));
+    let bottom = (0..N - 1).rev().map(|x| (x, N - 1));
+    let left = (1..N - 1).rev().map(|y| (0, y));
+
+    let mut result = Vec::new();
+    for (x, y) in top.chain(right).chain(bottom).chain(left) {
+        let rotated_tile = board.get(x, y);
+        let expected = ExteriorMask::zero()
+            .with_right(x == N - 1)
+            .with_top(y == 0)
+            .with_left(x == 0)
+            .with_bottom(y == N - 1);
+        if rotated_tile.exterior_mask() != expected {
+            return Err(IllegalPerimeterPlacement { x, y, rotated_tile });
+        }
+        result.push(rotated_tile);
+    }
+    Ok(result)
+}
+
+/// The error from [`perimeter_tiles`] when a placement's exterior sides don't
+/// face off the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IllegalPerimeterPlacement {
+    pub x: usize,
+    pub y: usize,
+    pub rotated_tile: RotatedTile,
+}
+
+impl Display for IllegalPerimeterPlacement {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} at ({}, {}) is not a legal perimeter placement",
+            self.rotated_tile, self.x, self.y,
+        )
+    }
+}
+
+impl std::error::Error for IllegalPerimeterPlacement {}
+
+/// Returns the position of a cell's side in a bucas.name `board_edges` string.
+///
+/// Each cell takes four characters, in order top, right, bottom, left. Cells
+/// are in row-major order.
+pub fn bucas_edge_index(x: usize, y: usize, side: Side) -> usize {
+    64 * y
+        + 4 * x
+        + match side {
+            Side::Top => 0,
+            Side::Right => 1,
+            Side::Bottom => 2,
+            Side::Left => 3,
+        }
+}
+
+/// Lays out a board's edges as a bucas.name `board_edges` string.
+pub fn to_bucas_board_edges<M: RectangularMosaic<16, 16>>(mosaic: &M) -> String {
+    let mut board_edges = [b'_'; 1024];
+    for y in 0..16 {
+        for x in 0..16 {
+            for side in Side::iter() {
+                board_edges[bucas_edge_index(x, y, side)] =
+                    mosaic.get(x, y).color(side).to_byte_char();
+            }
+        }
+    }
+    String::from_utf8(board_edges.to_vec()).unwrap()
+}
+
+/// Returns the bucas.name URL that displays a board given its `board_edges`.
+pub fn bucas_url(board_edges: &str) -> String {
+    format!(
+        "https://e2.bucas.name/#board_w=16&board_h=16&board_edges={board_edges}&motifs_order=jblackwood",
+    )
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    WrongLength { len: usize },
+    Placeholder { x: usize, y: usize },
+    InvalidColor { x: usize, y: usize, byte: u8 },
+    NoMatchingTile { x: usize, y: usize },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Self::WrongLength { len } => write!(f, "expected 1024 edge characters, got {len}"),
+            Self::Placeholder { x, y } => write!(f, "cell ({x}, {y}) is not filled in"),
+            Self::InvalidColor { x, y, byte } => write!(
+                f,
+                "cell ({x}, {y}) has invalid color {:?}",
+                byte.escape_ascii().to_string(),
+            ),
+            Self::NoMatchingTile { x, y } => write!(f, "cell ({x}, {y}) matches no tile"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses the `board_edges` parameter of a bucas.name URL, as printed by
+/// `translate_to_url`.
+pub fn from_bucas_board_edges(s: &str) -> Result<ArrayMosaic<16, 16>, ParseError> {
+    let s = s.as_bytes();
+    if s.len() != 1024 {
+        return Err(ParseError::WrongLength { len: s.len() });
+    }
+
+    let mut mosaic = ArrayMosaic {
+        tiles: [[RotatedTile::ZERO; 16]; 16],
+    };
+    for y in 0..16 {
+        for x in 0..16 {
+            let color = |side| {
+                let byte = s[bucas_edge_index(x, y, side)];
+                if byte == b'_' {
+                    return Err(ParseError::Placeholder { x, y });
+                }
+                Color::from_byte_char(byte).ok_or(ParseError::InvalidColor { x, y, byte })
+            };
+            mosaic.tiles[y][x] = RotatedTile::from_colors(
+                color(Side::Right)?,
+                color(Side::Top)?,
+                color(Side::Left)?,
+                color(Side::Bottom)?,
+            )
+            .ok_or(ParseError::NoMatchingTile { x, y })?;
+        }
+    }
+    Ok(mosaic)
+}
+
+/// Writes a board in the plain `.e2` grid format: one line per row, top to
+/// bottom, each holding a `tile rotation` pair per cell, left to right, all
+/// separated by spaces.
+///
+/// Rows are Y and columns are X, as on [`sat::Variable`](crate::sat::Variable).
+/// Rotations are written as their primitive values, from 0 for
+/// [`Rotation::Identity`] to 3 for [`Rotation::QuarterTurnRight`].
+pub fn write_e2<W: Write>(mosaic: &ArrayMosaic<16, 16>, mut w: W) -> io::Result<()> {
+    for row in &mosaic.tiles {
+        let pairs = Vec::from_iter(row.iter().map(|rotated_tile| {
+            format!(
+                "{} {}",
+                rotated_tile.tile,
+                rotated_tile.rotation.to_primitive(),
+            )
+        }));
+        writeln!(w, "{}", pairs.join(" "))?;
+    }
+    Ok(())
+}
+
+/// Reads a board written by [`write_e2`]. Blank lines are skipped.
+pub fn read_e2<R: Read>(r: R) -> Result<ArrayMosaic<16, 16>, E2Error> {
+    let mut rows = Vec::new();
+    for line in BufReader::new(r).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            rows.push(line);
+        }
+    }
+    if rows.len() != 16 {
+        return Err(E2Error::WrongRowCount { rows: rows.len() });
+    }
+
+    let mut mosaic = ArrayMosaic {
+        tiles: [[RotatedTile::ZERO; 16]; 16],
+    };
+    for (y, line) in rows.iter().enumerate() {
+        let values = Vec::from_iter(line.split_ascii_whitespace());
+        if values.len() != 32 {
+            return Err(E2Error::WrongRowLength {
+                y,
+                values: values.len(),
+            });
+        }
+        for (x, pair) in values.chunks(2).enumerate() {
+            mosaic.tiles[y][x] = RotatedTile {
+                tile: pair[0]
+                    .parse()
+                    .map(Tile::from_primitive)
+                    .map_err(|_| E2Error::InvalidTile { x, y })?,
+                rotation: pair[1]
+                    .parse()
+                    .ok()
+                    .and_then(Rotation::from_u8)
+                    .ok_or(E2Error::InvalidRotation { x, y })?,
+            };
+        }
+    }
+    Ok(mosaic)
+}
+
+#[derive(Debug)]
+pub enum E2Error {
+    Io(io::Error),
+    WrongRowCount { rows: usize },
+    WrongRowLength { y: usize, values: usize },
+    InvalidTile { x: usize, y: usize },
+    InvalidRotation { x: usize, y: usize },
+}
+
+impl From<io::Error> for E2Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl Display for E2Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::WrongRowCount { rows } => write!(f, "expected 16 rows, got {rows}"),
+            Self::WrongRowLength { y, values } => {
+                write!(f, "row {y}: expected 32 values, got {values}")
+            }
+            Self::InvalidTile { x, y } => write!(f, "cell ({x}, {y}) has an invalid tile"),
+            Self::InvalidRotation { x, y } => {
+                write!(f, "cell ({x}, {y}) has an invalid rotation")
+            }
+        }
+    }
+}
+
+impl std::error::Error for E2Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// The error from [`concat_horizontal`] and [`concat_vertical`] when the two
+/// mosaics would use a tile twice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TileReuseError {
+    pub tile: Tile,
+}
+
+impl Display for TileReuseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "tile {} is used more than once",
+            self.tile.to_primitive()
+        )
+    }
+}
+
+impl std::error::Error for TileReuseError {}
+
+/// Returns a function that passes rotated tiles through, failing on the first
+/// tile it has already seen.
+fn tile_reuse_checker() -> impl FnMut(RotatedTile) -> Result<RotatedTile, TileReuseError> {
+    let mut used_tiles = bitarr![0; 256];
+    move |rotated_tile| {
+        let index = rotated_tile.tile.to_primitive() as usize;
+        if used_tiles.replace(index, true) {
+            return Err(TileReuseError {
+                tile: rotated_tile.tile,
+            });
+        }
+        Ok(rotated_tile)
+    }
+}
+
+/// Places `b` to the right of `a`.
+///
+/// # Panics
+///
+/// Panics if `W` is not `AW + BW`.
+pub fn concat_horizontal<const AW: usize, const BW: usize, const W: usize, const H: usize>(
+    a: impl RectangularMosaic<AW, H>,
+    b: impl RectangularMosaic<BW, H>,
+) -> Result<ArrayMosaic<W, H>, TileReuseError> {
+    assert_eq!(AW + BW, W);
+
+    let mut check = tile_reuse_checker();
+    let mut mosaic = ArrayMosaic {
+        tiles: [[RotatedTile::ZERO; W]; H],
+    };
+    for y in 0..H {
+        for x in 0..AW {
+            mosaic.tiles[y][x] = check(a.get(x, y))?;
+        }
+        for x in 0..BW {
+            mosaic.tiles[y][AW + x] = check(b.get(x, y))?;
+        }
+    }
+    Ok(mosaic)
+}
+
+/// Places `b` below `a`.
+///
+/// # Panics
+///
+/// Panics if `H` is not `AH + BH`.
+pub fn concat_vertical<const W: usize, const AH: usize, const BH: usize, const H: usize>(
+    a: impl RectangularMosaic<W, AH>,
+    b: impl RectangularMosaic<W, BH>,
+) -> Result<ArrayMosaic<W, H>, TileReuseError> {
+    assert_eq!(AH + BH, H);
+
+    let mut check = tile_reuse_checker();
+    let mut mosaic = ArrayMosaic {
+        tiles: [[RotatedTile::ZERO; W]; H],
+    };
+    for y in 0..AH {
+        for x in 0..W {
+            mosaic.tiles[y][x] = check(a.get(x, y))?;
+        }
+    }
+    for y in 0..BH {
+        for x in 0..W {
+            mosaic.tiles[AH + y][x] = check(b.get(x, y))?;
+        }
+    }
+    Ok(mosaic)
+}
+
+/// The error from [`ArrayMosaic::overlay`]. An edge mismatch is reported at
+/// the later of the two cells, on the side facing the earlier one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverlayError {
+    TileReused { tile: Tile },
+    Overlap { x: usize, y: usize },
+    EdgeMismatch { x: usize, y: usize, side: Side },
+    Uncovered { x: usize, y: usize },
+}
+
+impl From<TileReuseError> for OverlayError {
+    fn from(e: TileReuseError) -> Self {
+        Self::TileReused { tile: e.tile }
+    }
+}
+
+impl Display for OverlayError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Self::TileReused { tile } => write!(f, "tile {tile} is used more than once"),
+            Self::Overlap { x, y } => write!(f, "cell ({x}, {y}) is covered more than once"),
+            Self::EdgeMismatch { x, y, side } => {
+                write!(f, "cell ({x}, {y}) doesn't match its {side:?} neighbor")
+            }
+            Self::Uncovered { x, y } => write!(f, "cell ({x}, {y}) is not covered"),
+        }
+    }
+}
+
+impl std::error::Error for OverlayError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackedQuadRotatedTile {
+    pub tiles: [Tile; 4],
+    pub rotations: [Rotation; 4],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackedArrayMosaic<const W: usize, const H: usize, const QW: usize, const QH: usize> {
+    pub tiles: [[PackedQuadRotatedTile; QW]; QH],
+}
+
+impl<const W: usize, const H: usize, const QW: usize, const QH: usize>
+    PackedArrayMosaic<W, H, QW, QH>
+{
+    /// Fails to compile unless `QW` and `QH` are exactly half of `W` and `H`,
+    /// which also rules out odd dimensions.
+    const QUAD_DIMENSIONS_MATCH: () = assert!(W == 2 * QW && H == 2 * QH);
+
+    /// Packs the tiles of `mosaic` into 2x2 quads.
+    pub fn from_array_mosaic(mosaic: &ArrayMosaic<W, H>) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::QUAD_DIMENSIONS_MATCH;
+        Self {
+            tiles: std::array::from_fn(|qy| {
+                std::array::from_fn(|qx| {
+                    let quad = [(0, 0), (1, 0), (0, 1), (1, 1)]
+                        .map(|(dx, dy)| mosaic.get(2 * qx + dx, 2 * qy + dy));
+                    PackedQuadRotatedTile {
+                        tiles: quad.map(|rotated_tile| rotated_tile.tile),
+                        rotations: quad.map(|rotated_tile| rotated_tile.rotation),
+                    }
+                })
+            }),
+        }
+    }
+}
+
+impl<const W: usize, const H: usize, const QW: usize, const QH: usize> From<ArrayMosaic<W, H>>
+    for PackedArrayMosaic<W, H, QW, QH>
+{
+    fn from(value: ArrayMosaic<W, H>) -> Self {
+        Self::from_array_mosaic(&value)
+    }
+}
+
+impl<const W: usize, const H: usize, const QW: usize, const QH: usize> RectangularMosaic<W, H>
+    for PackedArrayMosaic<W, H, QW, QH>
+{
+    fn width(&self) -> usize {
+        W
+    }
+
+    fn height(&self) -> usize {
+        H
+    }
+
+    fn get(&self, x: usize, y: usize) -> RotatedTile {
+        let quad = &self.tiles[y / 2][x / 2];
+        let index = 2 * (y % 2) + x % 2;
+        RotatedTile {
+            tile: quad.tiles[index],
+            rotation: quad.rotations[index],
+        }
+    }
+
+    type WithRectangularRotation<'a> = RotatedRectangularMosaic<'a, W, H, Self>
+    where
+        Self: 'a;
+
+    fn with_rectangular_rotation(
+        &self,
+        rotation: RectangularRotation,
+    ) -> Self::WithRectangularRotation<'_> {
+        RotatedRectangularMosaic {
+            mosaic: self,
+            rotation,
+        }
+    }
+}
+
+impl<const N: usize, const QN: usize> SquareMosaic<N> for PackedArrayMosaic<N, N, QN, QN> {
+    type WithSquareRotation<'a> = RotatedSquareMosaic<'a, N, Self>
+    where
+        Self: 'a;
+
+    fn with_square_rotation(&self, rotation: Rotation) -> Self::WithSquareRotation<'_> {
+        RotatedSquareMosaic {
+            mosaic: self,
+            rotation,
+        }
+    }
+}
+
+impl<'a, const W: usize, const H: usize, const QW: usize, const QH: usize> Add<RectangularRotation>
+    for &'a PackedArrayMosaic<W, H, QW, QH>
+{
+    type Output = RotatedRectangularMosaic<'a, W, H, PackedArrayMosaic<W, H, QW, QH>>;
+
+    fn add(self, rhs: RectangularRotation) -> Self::Output {
+        RotatedRectangularMosaic {
+            mosaic: self,
+            rotation: rhs,
+        }
+    }
+}
+
+impl<'a, const N: usize, const QN: usize> Add<Rotation> for &'a PackedArrayMosaic<N, N, QN, QN> {
+    type Output = RotatedSquareMosaic<'a, N, PackedArrayMosaic<N, N, QN, QN>>;
+
+    fn add(self, rhs: Rotation) -> Self::Output {
+        RotatedSquareMosaic {
+            mosaic: self,
+            rotation: rhs,
+        }
+    }
+}
+
+/// A full board stored in 320 bytes: one byte per tile, with the rotations
+/// packed four to a byte alongside. [`ArrayMosaic<16, 16>`] takes 512.
+///
+/// Meant for holding many candidate boards in memory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackedMosaic16 {
+    tiles: [Tile; 256],
+    rotations: [u8; 64],
+}
+
+impl PackedMosaic16 {
+    pub fn from_array_mosaic(mosaic: &ArrayMosaic<16, 16>) -> Self {
+        let mut result = Self {
+            tiles: [Tile::from_primitive(0); 256],
+            rotations: [0; 64],
+        };
+        for y in 0..16 {
+            for x in 0..16 {
+                let index = 16 * y + x;
+                let rotated_tile = mosaic.get(x, y);
+                result.tiles[index] = rotated_tile.tile;
+                result.rotations[index / 4] |=
+                    rotated_tile.rotation.to_primitive() << (2 * (index % 4));
+            }
+        }
+        result
+    }
+}
+
+impl From<ArrayMosaic<16, 16>> for PackedMosaic16 {
+    fn from(value: ArrayMosaic<16, 16>) -> Self {
+        Self::from_array_mosaic(&value)
+    }
+}
+
+impl RectangularMosaic<16, 16> for PackedMosaic16 {
+    fn width(&self) -> usize {
+        16
+    }
+
+    fn height(&self) -> usize {
+        16
+    }
+
+    fn get(&self, x: usize, y: usize) -> RotatedTile {
+        assert!(x < 16 && y < 16);
+        let index = 16 * y + x;
+        RotatedTile {
+            tile: self.tiles[index],
+            rotation: Rotation::new_masked(self.rotations[index / 4] >> (2 * (index % 4))),
+        }
+    }
+
+    type WithRectangularRotation<'a> = RotatedRectangularMosaic<'a, 16, 16, Self>
+    where
+        Self: 'a;
+
+    fn with_rectangular_rotation(
+        &self,
+        rotation: RectangularRotation,
+    ) -> Self::WithRectangularRotation<'_> {
+        RotatedRectangularMosaic {
+            mosaic: self,
+            rotation,
+        }
+    }
+}
+
+impl SquareMosaic<16> for PackedMosaic16 {
+    type WithSquareRotation<'a> = RotatedSquareMosaic<'a, 16, Self>
+    where
+        Self: 'a;
+
+    fn with_square_rotation(&self, rotation: Rotation) -> Self::WithSquareRotation<'_> {
+        RotatedSquareMosaic {
+            mosaic: self,
+            rotation,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use crate::mosaic::{RotatedRectangularMosaic, SquareMosaic};
+    use crate::rectangular::{HorizontalSide, RectangularRotation, Reflection, VerticalSide};
+    use crate::{ExteriorMask, RotatedTile, Rotation, Side, Tile};
+
+    use super::{
+        bucas_edge_index, concat_horizontal, concat_vertical, from_bucas_board_edges,
+        perimeter_tiles, read_e2, to_bucas_board_edges, write_e2, ArrayMosaic, E2Error,
+        IllegalPerimeterPlacement, OverlayError, OwnedRotatedMosaic, PackedArrayMosaic,
+        PackedMosaic16, ParseError, RectangularMosaic, RotatedSquareMosaic, TileReuseError,
+    };
+
+    #[test]
+    fn packed_mosaic_16_get() {
+        let mut mosaic = ArrayMosaic {
+            tiles: [[RotatedTile::ZERO; 16]; 16],
+        };
+        for y in 0..16 {
+            for x in 0..16 {
+                mosaic.tiles[y][x] = RotatedTile {
+                    tile: Tile::from_primitive((16 * y + x) as u8),
+                    rotation: Rotation::new_masked((x + 3 * y) as u8),
+                };
+            }
+        }
+
+        let packed = PackedMosaic16::from(mosaic);
+        assert_eq!(std::mem::size_of::<PackedMosaic16>(), 320);
+        for y in 0..16 {
+            for x in 0..16 {
+                assert_eq!(packed.get(x, y), mosaic.get(x, y), "({x}, {y})");
+            }
+        }
+        assert_eq!(packed.to_array_mosaic(), mosaic);
+        assert_eq!(packed.canonical(), mosaic.canonical());
+    }
+
+    #[test]
+    fn all_edges() {
+        let mosaic = mosaic![[0, 1 QuarterTurnLeft, 2], [16 HalfTurn, 17, 18]];
+        let (right, top, left, bottom) = mosaic.all_edges();
+        assert_eq!(right, mosaic.vertical_edge(VerticalSide::Right));
+        assert_eq!(top, mosaic.horizontal_edge(HorizontalSide::Top));
+        assert_eq!(left, mosaic.vertical_edge(VerticalSide::Left));
+        assert_eq!(bottom, mosaic.horizontal_edge(HorizontalSide::Bottom));
+
+        // A half turn swaps opposite edges, since edges read clockwise.
+        assert_eq!(
+            mosaic
+                .with_rectangular_rotation(RectangularRotation::HalfTurn)
+                .all_edges(),
+            (left, bottom, right, top),
+        );
+    }
+
+    #[test]
+    fn rotated_materializes_views() {
+        let mosaic = mosaic![[0, 1 QuarterTurnLeft], [16 HalfTurn, 17]];
+        let mut rotated = mosaic;
+        for _ in 0..4 {
+            let next = rotated.rotated(Rotation::QuarterTurnLeft);
+            assert_eq!(
+                next,
+                rotated
+                    .with_square_rotation(Rotation::QuarterTurnLeft)
+                    .to_array_mosaic(),
+            );
+            assert_ne!(next, rotated);
+            rotated = next;
+        }
+        assert_eq!(rotated, mosaic);
+
+        let mosaic = mosaic![[0, 1 QuarterTurnLeft, 2], [16 HalfTurn, 17, 18]];
+        let rotated = mosaic.rotated_rect(RectangularRotation::HalfTurn);
+        assert_eq!(
+            rotated,
+            mosaic
+                .with_rectangular_rotation(RectangularRotation::HalfTurn)
+                .to_array_mosaic(),
+        );
+        assert_eq!(rotated.rotated_rect(RectangularRotation::HalfTurn), mosaic);
+    }
+
+    #[test]
+    fn owned_rotated_mosaic_matches_borrowed() {
+        let mosaic = mosaic![[0, 1 QuarterTurnLeft], [16 HalfTurn, 17]];
+        for rotation in Rotation::iter() {
+            let borrowed = RotatedSquareMosaic {
+                mosaic: &mosaic,
+                rotation,
+            };
+            let owned: OwnedRotatedMosaic<2> = borrowed.to_owned();
+            for y in 0..2 {
+                for x in 0..2 {
+                    assert_eq!(owned.get(x, y), borrowed.get(x, y));
+                }
+            }
+            for other in Rotation::iter() {
+                assert_eq!(
+                    owned.with_square_rotation(other).to_array_mosaic(),
+                    borrowed.with_square_rotation(other).to_array_mosaic(),
+                );
+            }
+            assert_eq!(owned.canonical(), borrowed.canonical());
+        }
+    }
+
+    #[test]
+    fn ansi_display_is_plain_display_with_escapes() {
+        let mosaic = mosaic![[0, 1], [16, 17]];
+        let plain = mosaic.display(2).to_string();
+        let colored = mosaic.display_ansi(2).to_string();
+        // Tile 0 is a corner, so its exterior sides are drawn gray.
+        assert!(colored.contains("\x1b[38;2;128;128;128m"));
+
+        let mut stripped = String::new();
+        let mut chars = colored.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                for c in chars.by_ref() {
+                    if c == 'm' {
+                        break;
+                    }
+                }
+            } else {
+                stripped.push(c);
+            }
+        }
+        assert_eq!(stripped, plain);
+    }
+
+    #[test]
+    fn to_array_mosaic() {
+        let mosaic = mosaic![[0, 1, 2], [10, 11, 12]];
+        assert_eq!(mosaic.to_array_mosaic(), mosaic);
+    }
+
+    #[test]
+    fn concat() {
+        let mosaic = mosaic![[0, 1, 2], [16, 17, 18]];
+        assert_eq!(
+            concat_horizontal(mosaic![[0], [16]], mosaic![[1, 2], [17, 18]]),
+            Ok(mosaic),
+        );
+        assert_eq!(
+            concat_vertical(mosaic![[0, 1, 2]], mosaic![[16, 17, 18]]),
+            Ok(mosaic),
+        );
+        assert_eq!(
+            concat_horizontal::<1, 2, 3, 2>(mosaic![[0], [17]], mosaic![[1, 2], [17, 18]]),
+            Err(TileReuseError {
+                tile: Tile::from_primitive(17),
+            }),
+        );
+        assert_eq!(
+            concat_vertical::<3, 1, 1, 2>(mosaic![[0, 1, 2]], mosaic![[16, 1 HalfTurn, 18]]),
+            Err(TileReuseError {
+                tile: Tile::from_primitive(1),
+            }),
+        );
+    }
+
+    #[test]
+    fn subgrid() {
+        let mosaic = mosaic![[0, 1, 2, 3], [16, 17, 18, 19], [32, 33, 34, 35]];
+        assert_eq!(mosaic.subgrid::<2, 2>(0, 0), mosaic![[0, 1], [16, 17]]);
+        assert_eq!(mosaic.subgrid::<2, 2>(2, 1), mosaic![[18, 19], [34, 35]]);
+        assert_eq!(mosaic.subgrid::<4, 3>(0, 0), mosaic);
+    }
+
+    #[test]
+    #[should_panic]
+    fn subgrid_out_of_bounds() {
+        let mosaic = mosaic![[0, 1, 2, 3], [16, 17, 18, 19], [32, 33, 34, 35]];
+        mosaic.subgrid::<2, 2>(3, 0);
+    }
+
+    #[test]
+    fn canonical() {
+        let mosaic = mosaic![[0, 1], [16, 17]];
+        let canonical = mosaic.canonical();
+        for rotation in Rotation::iter() {
+            let rotated = mosaic.with_square_rotation(rotation);
+            assert_eq!(rotated.canonical(), canonical);
+            assert!(canonical <= rotated.to_array_mosaic());
+        }
+
+        let mosaic = mosaic![[0, 1, 2], [16, 17, 18]];
+        let canonical = mosaic.rectangular_canonical();
+        for rotation in RectangularRotation::iter() {
+            let rotated = mosaic.with_rectangular_rotation(rotation);
+            assert_eq!(rotated.rectangular_canonical(), canonical);
+            assert!(canonical <= rotated.to_array_mosaic());
+        }
+    }
+
+    #[test]
+    fn reflected_mosaic() {
+        let mosaic = mosaic![[0, 1, 2 HalfTurn], [16, 17, 18]];
+
+        let reflected = mosaic.with_reflection(Reflection::Horizontal);
+        assert_eq!(
+            reflected.to_array_mosaic(),
+            mosaic![[2 HalfTurn, 1, 0], [18, 17, 16]],
+        );
+        assert_eq!(
+            reflected
+                .with_reflection(Reflection::Horizontal)
+                .to_array_mosaic(),
+            mosaic,
+        );
+        assert_eq!(
+            mosaic
+                .with_reflection(Reflection::Identity)
+                .to_array_mosaic(),
+            mosaic,
+        );
+    }
+
+    #[test]
+    fn perimeter_tiles_order() {
+        // Give each cell the next unused tile whose exterior sides face off the
+        // board there. Neighbors don't match, which perimeter_tiles() ignores.
+        let mut rotated_tiles = Vec::from_iter(RotatedTile::values());
+        let mut board = ArrayMosaic::<4, 4> {
+            tiles: [[RotatedTile::ZERO; 4]; 4],
+        };
+        for y in 0..4 {
+            for x in 0..4 {
+                let expected = ExteriorMask::zero()
+                    .with_right(x == 3)
+                    .with_top(y == 0)
+                    .with_left(x == 0)
+                    .with_bottom(y == 3);
+                let index = rotated_tiles
+                    .iter()
+                    .position(|rotated_tile| rotated_tile.exterior_mask() == expected)
+                    .unwrap();
+                let rotated_tile = rotated_tiles[index];
+                rotated_tiles.retain(|other| other.tile != rotated_tile.tile);
+                board.tiles[y][x] = rotated_tile;
+            }
+        }
+
+        let ring = perimeter_tiles(&board).unwrap();
+        assert_eq!(ring.len(), 12);
+        assert_eq!(
+            ring,
+            [
+                (0, 0),
+                (1, 0),
+                (2, 0),
+                (3, 0),
+                (3, 1),
+                (3, 2),
+                (3, 3),
+                (2, 3),
+                (1, 3),
+                (0, 3),
+                (0, 2),
+                (0, 1),
+            ]
+            .map(|(x, y)| board.get(x, y)),
+        );
+
+        // A center tile on the perimeter.
+        board.tiles[2][3] = board.get(1, 1);
+        assert_eq!(
+            perimeter_tiles(&board),
+            Err(IllegalPerimeterPlacement {
+                x: 3,
+                y: 2,
+                rotated_tile: board.get(1, 1),
+            }),
+        );
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn render_png() {
+        use super::PALETTE;
+
+        let mosaic = mosaic![[0, 1], [16, 17]];
+        let mut output = Vec::new();
+        super::render_png(&mosaic, 8, &mut output).unwrap();
+
+        let mut reader = png::Decoder::new(&output[..]).read_info().unwrap();
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data).unwrap();
+        assert_eq!((info.width, info.height), (16, 16));
+
+        let pixel = |x: usize, y: usize| -> [u8; 3] {
+            let i = 3 * (16 * y + x);
+            data[i..i + 3].try_into().unwrap()
+        };
+        let palette = |tile_id, side| {
+            let rotated_tile = RotatedTile {
+                tile: Tile::from_primitive(tile_id),
+                rotation: Rotation::Identity,
+            };
+            PALETTE[rotated_tile.color(side).to_primitive() as usize]
+        };
+        assert_eq!(pixel(4, 0), palette(0, Side::Top));
+        assert_eq!(pixel(7, 4), palette(0, Side::Right));
+        assert_eq!(pixel(12, 7), palette(1, Side::Bottom));
+        assert_eq!(pixel(8, 12), palette(17, Side::Left));
+        assert_eq!(pixel(0, 0), [0, 0, 0]);
+    }
+
+    #[test]
+    fn render_svg() {
+        let mosaic = mosaic![[0, 1]];
+        let svg = super::render_svg(&mosaic, 8);
+
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"16\" height=\"8\" \
+             viewBox=\"0 0 16 8\">",
+        ));
+        assert!(svg.ends_with("</svg>"));
+        assert_eq!(svg.matches("<polygon ").count(), 8);
+
+        let (r, g, b) = mosaic.get(1, 0).color(Side::Right).rgb();
+        assert!(svg.contains(&format!(
+            "<polygon points=\"16,0 16,8 12,4\" fill=\"#{r:02x}{g:02x}{b:02x}\" stroke=\"black\"/>",
+        )));
+    }
+
+    #[test]
+    fn rotated_square_mosaic() {
+        let tile_a = Tile::from_primitive(0);
+        let tile_b = Tile::from_primitive(1);
+        let tile_c = Tile::from_primitive(16);
+        let tile_d = Tile::from_primitive(17);
+        let mosaic = ArrayMosaic {
+            tiles: [
+                [
+                    RotatedTile {
                         tile: tile_a,
                         rotation: Rotation::Identity,
                     },
@@ -872,4 +2260,156 @@ mod tests {
         assert_eq!(left(rotated), "ftd");
         assert_eq!(bottom(rotated), "aaaa");
     }
+
+    #[test]
+    fn bucas_board_edges_round_trip() {
+        let mut mosaic = ArrayMosaic {
+            tiles: [[RotatedTile::ZERO; 16]; 16],
+        };
+        for y in 0..16 {
+            for x in 0..16 {
+                mosaic.tiles[y][x] = RotatedTile {
+                    tile: Tile::from_primitive((16 * y + x) as u8),
+                    rotation: Rotation::new_masked((x + y) as u8),
+                };
+            }
+        }
+
+        let board_edges = &to_bucas_board_edges(&mosaic);
+        assert_eq!(from_bucas_board_edges(board_edges), Ok(mosaic));
+
+        let mut with_placeholder = board_edges.to_string();
+        with_placeholder.replace_range(
+            bucas_edge_index(3, 5, Side::Left)..=bucas_edge_index(3, 5, Side::Left),
+            "_",
+        );
+        assert_eq!(
+            from_bucas_board_edges(&with_placeholder),
+            Err(ParseError::Placeholder { x: 3, y: 5 }),
+        );
+        assert_eq!(
+            from_bucas_board_edges("abcd"),
+            Err(ParseError::WrongLength { len: 4 }),
+        );
+    }
+
+    #[test]
+    fn e2_round_trip() {
+        let mut mosaic = ArrayMosaic {
+            tiles: [[RotatedTile::ZERO; 16]; 16],
+        };
+        mosaic.tiles[0][1] = RotatedTile {
+            tile: Tile::from_primitive(135),
+            rotation: Rotation::HalfTurn,
+        };
+        mosaic.tiles[15][0] = RotatedTile::MAX;
+
+        let mut e2 = Vec::new();
+        write_e2(&mosaic, &mut e2).unwrap();
+        let e2 = String::from_utf8(e2).unwrap();
+        assert_eq!(e2.lines().count(), 16);
+        assert!(e2.starts_with("0 0 135 2 0 0 "));
+        assert!(e2.contains("\n255 3 0 0 "));
+        assert_eq!(read_e2(e2.as_bytes()).unwrap(), mosaic);
+
+        let bad_rotation = e2.replacen("135 2", "135 4", 1);
+        assert!(matches!(
+            read_e2(bad_rotation.as_bytes()),
+            Err(E2Error::InvalidRotation { x: 1, y: 0 }),
+        ));
+        assert!(matches!(
+            read_e2("0 0\n".as_bytes()),
+            Err(E2Error::WrongRowCount { rows: 1 }),
+        ));
+    }
+
+    #[test]
+    fn mismatch_count() {
+        // A 2x2 corner built by the set builders.
+        let good = mosaic![
+            [15 QuarterTurnLeft, 31 QuarterTurnLeft],
+            [208 Identity, 50 QuarterTurnLeft]
+        ];
+        assert_eq!(good.mismatch_count(), 0);
+        // Only the top and left sides are exterior.
+        assert!(!good.is_valid());
+
+        let broken = mosaic![
+            [15 QuarterTurnLeft, 31 QuarterTurnLeft],
+            [208 Identity, 50 Identity]
+        ];
+        assert_eq!(broken.mismatch_count(), 2);
+        assert!(!broken.is_valid());
+    }
+
+    #[test]
+    fn overlay() {
+        // The 2x2 corner from `mismatch_count`, split into columns.
+        let good = mosaic![
+            [15 QuarterTurnLeft, 31 QuarterTurnLeft],
+            [208 Identity, 50 QuarterTurnLeft]
+        ];
+        let left = mosaic![[15 QuarterTurnLeft], [208 Identity]];
+        let right = mosaic![[31 QuarterTurnLeft], [50 QuarterTurnLeft]];
+        assert_eq!(
+            ArrayMosaic::<2, 2>::overlay(&[((0, 0), left), ((1, 0), right)]),
+            Ok(good),
+        );
+
+        assert_eq!(
+            ArrayMosaic::<2, 2>::overlay(&[((0, 0), left), ((1, 0), left)]),
+            Err(OverlayError::TileReused {
+                tile: Tile::from_primitive(15),
+            }),
+        );
+        assert_eq!(
+            ArrayMosaic::<2, 2>::overlay(&[((0, 0), left), ((0, 0), right)]),
+            Err(OverlayError::Overlap { x: 0, y: 0 }),
+        );
+        let broken_right = mosaic![[31 QuarterTurnLeft], [50 Identity]];
+        assert_eq!(
+            ArrayMosaic::<2, 2>::overlay(&[((0, 0), left), ((1, 0), broken_right)]),
+            Err(OverlayError::EdgeMismatch {
+                x: 1,
+                y: 1,
+                side: Side::Top,
+            }),
+        );
+        assert_eq!(
+            ArrayMosaic::<2, 2>::overlay(&[((0, 0), left)]),
+            Err(OverlayError::Uncovered { x: 1, y: 0 }),
+        );
+    }
+
+    #[test]
+    fn packed_from_array_mosaic() {
+        let mosaic = mosaic![
+            [0, 1 QuarterTurnLeft, 2, 3 HalfTurn],
+            [16, 17, 18 QuarterTurnRight, 19],
+            [32 QuarterTurnLeft, 33, 34, 35],
+            [48, 49, 50, 51 QuarterTurnLeft],
+        ];
+        let packed = PackedArrayMosaic::<4, 4, 2, 2>::from_array_mosaic(&mosaic);
+        for y in 0..4 {
+            for x in 0..4 {
+                assert_eq!(packed.get(x, y), mosaic.get(x, y));
+            }
+        }
+        assert_eq!(packed.to_array_mosaic(), mosaic);
+    }
+
+    #[test]
+    fn packed_round_trip() {
+        let mosaic = mosaic![
+            [0, 1 QuarterTurnLeft, 2 HalfTurn, 3 QuarterTurnRight],
+            [16 QuarterTurnRight, 17 HalfTurn, 18 QuarterTurnLeft, 19],
+            [32, 33, 34 HalfTurn, 35],
+            [48 QuarterTurnLeft, 49, 50, 51 HalfTurn],
+        ];
+        for rotation in Rotation::iter() {
+            let rotated = mosaic.with_square_rotation(rotation).to_array_mosaic();
+            let packed = PackedArrayMosaic::<4, 4, 2, 2>::from(rotated);
+            assert_eq!(packed.to_array_mosaic(), rotated);
+        }
+    }
 }
@@ -10,6 +10,16 @@ pub enum RectangularSide {
     Horizontal(HorizontalSide),
 }
 
+impl RectangularSide {
+    /// The inverse of [`SideExt::to_rectangular`].
+    pub fn to_square(self) -> Side {
+        match self {
+            Self::Vertical(side) => side.to_square(),
+            Self::Horizontal(side) => side.to_square(),
+        }
+    }
+}
+
 bitfield! {
     pub enum VerticalSide: 1 {
         Right,
@@ -100,8 +110,19 @@ bitfield! {
 }
 
 impl RectangularRotation {
+    /// Every rotation, in the order `[Identity, HalfTurn]`, which is also the
+    /// order of [`Self::to_index`].
+    pub fn all() -> [Self; 2] {
+        [Self::Identity, Self::HalfTurn]
+    }
+
+    /// This rotation's position in [`Self::all`].
+    pub fn to_index(self) -> usize {
+        self.to_primitive() as usize
+    }
+
     pub fn to_square(self) -> Rotation {
-        Rotation::new_masked(2 * self.to_primitive())
+        Rotation::Identity.times(2 * self.to_primitive())
     }
 }
 
@@ -119,11 +140,48 @@ impl AddAssign for RectangularRotation {
     }
 }
 
+bitfield! {
+    /// A mirror image of a mosaic's tile grid.
+    ///
+    /// Eternity II pieces cannot be flipped, so this is not a physical
+    /// operation. Reflecting a mosaic moves tiles without mirroring their
+    /// colors, producing a mosaic that is comparable to others but generally
+    /// not valid. It is only meaningful for counting symmetry classes.
+    #[derive(PartialOrd, Ord, EnumIter)]
+    pub enum Reflection: 1 {
+        Identity,
+        Horizontal,
+    }
+}
+
+impl Add for Reflection {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        self.to_bitint().wrapping_add(rhs.to_bitint()).into()
+    }
+}
+
+impl AddAssign for Reflection {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use strum::IntoEnumIterator;
+
     use crate::{Rotation, Side};
 
-    use super::{HorizontalSide, RectangularRotation, VerticalSide};
+    use super::{HorizontalSide, RectangularRotation, Reflection, SideExt, VerticalSide};
+
+    #[test]
+    fn side_round_trips_through_rectangular() {
+        for side in Side::iter() {
+            assert_eq!(side.to_rectangular().to_square(), side);
+        }
+    }
 
     #[test]
     fn rotation_from_right() {
@@ -149,6 +207,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn all() {
+        assert_eq!(
+            RectangularRotation::all(),
+            [RectangularRotation::Identity, RectangularRotation::HalfTurn],
+        );
+        for (index, rotation) in RectangularRotation::all().into_iter().enumerate() {
+            assert_eq!(rotation.to_index(), index);
+        }
+    }
+
     #[test]
     fn to_square() {
         assert_eq!(VerticalSide::Right.to_square(), Side::Right);
@@ -233,4 +302,20 @@ mod tests {
         rotation += RectangularRotation::HalfTurn;
         assert_eq!(rotation, RectangularRotation::Identity);
     }
+
+    #[test]
+    fn reflection_add() {
+        assert_eq!(
+            Reflection::Identity + Reflection::Identity,
+            Reflection::Identity,
+        );
+        assert_eq!(
+            Reflection::Identity + Reflection::Horizontal,
+            Reflection::Horizontal,
+        );
+        assert_eq!(
+            Reflection::Horizontal + Reflection::Horizontal,
+            Reflection::Identity,
+        );
+    }
 }
@@ -1,13 +1,55 @@
+use std::io::{self, Write};
 use std::time::Instant;
 
-use num_format::{SystemLocale, ToFormattedString};
+#[cfg(feature = "num-format")]
+use num_format::{Format, ToFormattedString};
+#[cfg(all(feature = "num-format", not(feature = "std-format")))]
+use num_format::{Locale, SystemLocale};
 
 use crate::mosaic::{ArrayMosaic, RectangularMosaic, SquareMosaic};
+use crate::set::builder::counting_sampling::Samples;
 use crate::set::rectangle::RectangularMosaicSet;
 use crate::set::square::SquareMosaicSet;
+use crate::set::FanOutStats;
 
+/// The locale used when the system locale can't be read, which groups digits
+/// with a comma every three places.
+#[cfg(all(feature = "num-format", not(feature = "std-format")))]
+const FALLBACK_LOCALE: Locale = Locale::en;
+
+/// Formats `count` with digit grouping for the system locale, or for
+/// [`FALLBACK_LOCALE`] if the system locale is unavailable.
+#[cfg(all(feature = "num-format", not(feature = "std-format")))]
+pub fn format_count(count: usize) -> String {
+    match SystemLocale::default() {
+        Ok(locale) => format_count_with(count, &locale),
+        Err(_) => format_count_with(count, &FALLBACK_LOCALE),
+    }
+}
+
+/// Formats `count` with [`format_count_grouped`], without consulting the
+/// system locale.
+#[cfg(any(not(feature = "num-format"), feature = "std-format"))]
 pub fn format_count(count: usize) -> String {
-    count.to_formatted_string(&SystemLocale::default().unwrap())
+    format_count_grouped(count)
+}
+
+#[cfg(feature = "num-format")]
+pub fn format_count_with(count: usize, locale: &impl Format) -> String {
+    count.to_formatted_string(locale)
+}
+
+/// Formats `count` with a comma every three digits, without `num-format`.
+pub fn format_count_grouped(count: usize) -> String {
+    let digits = count.to_string();
+    let mut result = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            result.push(',');
+        }
+        result.push(digit);
+    }
+    result
 }
 
 pub fn format_ratio(numerator: usize, denominator: usize) -> String {
@@ -74,7 +116,7 @@ fn build_and_time<T>(
     (
         TableRow {
             title: title.to_string(),
-            count: count_string,
+            count: Some(count),
             notes: times,
         },
         value,
@@ -84,19 +126,101 @@ fn build_and_time<T>(
 fn print_rectangular_example<const W: usize, const H: usize, M: RectangularMosaic<W, H>>(
     set: &RectangularMosaicSet<W, H, M>,
 ) {
-    print!("{}", set.iter_mosaics().next().unwrap().display(4));
+    let example = set
+        .iter_mosaics()
+        .min_by_key(|mosaic| mosaic.rectangular_canonical())
+        .unwrap();
+    print!("{}", example.display(4));
 }
 
 fn print_square_example<const N: usize, M: SquareMosaic<N>>(set: &SquareMosaicSet<N, M>) {
     print!("{}", set.iter_mosaics().next().unwrap().display(4));
 }
 
-fn print_sample<const W: usize, const H: usize>((_, sample): &(usize, Option<ArrayMosaic<W, H>>)) {
-    if let Some(sample) = sample.as_ref() {
-        print!("{}", sample.display(4));
-    } else {
+fn print_samples<const W: usize, const H: usize, S: Samples<ArrayMosaic<W, H>>>(
+    (_, samples): &(usize, S),
+) {
+    if samples.as_slice().is_empty() {
         println!("    (zero elements)");
     }
+    for sample in samples.as_slice() {
+        print!("{}", sample.display(4));
+    }
+}
+
+/// Prints how many tiles are in each rotation, which catches builders that only
+/// produce some orientations.
+fn print_rotation_histogram(histogram: [usize; 4]) {
+    println!(
+        "* Rotations: {} identity, {} quarter turn left, {} half turn, {} quarter turn right",
+        format_count(histogram[0]),
+        format_count(histogram[1]),
+        format_count(histogram[2]),
+        format_count(histogram[3]),
+    );
+}
+
+/// Prints the right edge shared by the most rotated mosaics, a proxy for the
+/// worst fan-out when the set is joined on its right edges.
+fn print_largest_right_edge_bucket<const W: usize, const H: usize, M: RectangularMosaic<W, H>>(
+    set: &RectangularMosaicSet<W, H, M>,
+) {
+    if let Some((edge, count)) = set
+        .right_edge_key_histogram()
+        .into_iter()
+        .max_by_key(|&(_, count)| count)
+    {
+        println!(
+            "* Largest right edge bucket: {} mosaics share {edge}",
+            format_count(count),
+        );
+    }
+}
+
+/// Prints a join's fan-out summary and histogram, which shows whether a build's
+/// output comes from a few heavy edges or is spread evenly.
+pub fn print_fan_out(stats: &FanOutStats) {
+    println!(
+        "* Fan-out over {} shared edges: min {}, mean {:.2}, max {}",
+        format_count(stats.edges),
+        format_count(stats.min),
+        stats.mean(),
+        format_count(stats.max),
+    );
+    for (bucket, &count) in stats.histogram.iter().enumerate() {
+        if count > 0 {
+            let range = FanOutStats::bucket_range(bucket);
+            println!(
+                "  * {} to {}: {} edges",
+                format_count(range.start),
+                format_count(range.end - 1),
+                format_count(count),
+            );
+        }
+    }
+}
+
+/// A count that a build step should produce, computed independently of the
+/// build.
+#[derive(Clone, Copy, Debug)]
+pub enum ExpectedCount {
+    Exact(usize),
+    AtMost(usize),
+}
+
+impl ExpectedCount {
+    /// # Panics
+    ///
+    /// Panics if `actual` doesn't satisfy the expectation.
+    pub fn check(self, actual: usize) {
+        match self {
+            Self::Exact(expected) => assert_eq!(actual, expected),
+            Self::AtMost(bound) => assert!(
+                actual <= bound,
+                "count {actual} exceeds upper bound {bound}",
+            ),
+        }
+    }
 }
 
 #[derive(Default)]
@@ -106,7 +230,8 @@ pub struct Table {
 
 struct TableRow {
     title: String,
-    count: String,
+    /// `None` for the separators added by [`Table::push_hr`].
+    count: Option<usize>,
     notes: String,
 }
 
@@ -118,12 +243,13 @@ impl Table {
     ) {
         println!("Checking {title}...");
         set.assert_distinct();
+        print_rotation_histogram(set.rotation_histogram());
         println!("Passed! Example element:");
         print_square_example(set);
 
         self.rows.push(TableRow {
             title: title.to_string(),
-            count: format_count(set.len()),
+            count: Some(set.len()),
             notes: "".to_string(),
         });
     }
@@ -136,7 +262,39 @@ impl Table {
         let (row, result) = build_and_time(
             title,
             build,
-            Some(&|set: &RectangularMosaicSet<W, H, M>| set.assert_distinct()),
+            Some(&|set: &RectangularMosaicSet<W, H, M>| {
+                set.assert_distinct();
+                print_rotation_histogram(set.rotation_histogram());
+                print_largest_right_edge_bucket(set);
+            }),
+            |set| set.len(),
+            Some(&print_rectangular_example),
+        );
+        self.rows.push(row);
+        result
+    }
+
+    /// Like [`Self::track_build_rectangle`], but also checks the count against
+    /// `expected`.
+    pub fn track_build_rectangle_expecting<
+        const W: usize,
+        const H: usize,
+        M: RectangularMosaic<W, H>,
+    >(
+        &mut self,
+        title: &str,
+        expected: ExpectedCount,
+        build: impl FnOnce() -> RectangularMosaicSet<W, H, M>,
+    ) -> RectangularMosaicSet<W, H, M> {
+        let (row, result) = build_and_time(
+            title,
+            build,
+            Some(&|set: &RectangularMosaicSet<W, H, M>| {
+                set.assert_distinct();
+                expected.check(set.len());
+                print_rotation_histogram(set.rotation_histogram());
+                print_largest_right_edge_bucket(set);
+            }),
             |set| set.len(),
             Some(&print_rectangular_example),
         );
@@ -152,7 +310,10 @@ impl Table {
         let (row, result) = build_and_time(
             title,
             build,
-            Some(&|set: &SquareMosaicSet<N, M>| set.assert_distinct()),
+            Some(&|set: &SquareMosaicSet<N, M>| {
+                set.assert_distinct();
+                print_rotation_histogram(set.rotation_histogram());
+            }),
             |set| set.len(),
             Some(&print_square_example),
         );
@@ -160,17 +321,18 @@ impl Table {
         result
     }
 
-    pub fn track_count_and_sample<const W: usize, const H: usize>(
+    /// Tracks a build that only counts, printing every sample it returns.
+    pub fn track_count_and_sample<const W: usize, const H: usize, S: Samples<ArrayMosaic<W, H>>>(
         &mut self,
         title: &str,
-        build: impl FnOnce() -> (usize, Option<ArrayMosaic<W, H>>),
+        build: impl FnOnce() -> (usize, S),
     ) -> usize {
         let (row, (result, _)) = build_and_time(
             title,
             build,
             None::<&dyn Fn(&_)>,
             |&(count, _)| count,
-            Some(&print_sample),
+            Some(&print_samples),
         );
         self.rows.push(row);
         result
@@ -179,7 +341,7 @@ impl Table {
     pub fn push(&mut self, title: &str, count: usize, notes: String) {
         self.rows.push(TableRow {
             title: title.to_string(),
-            count: format_count(count),
+            count: Some(count),
             notes,
         });
     }
@@ -187,32 +349,153 @@ impl Table {
     pub fn push_hr(&mut self) {
         self.rows.push(TableRow {
             title: "----".to_string(),
-            count: "".to_string(),
+            count: None,
             notes: "".to_string(),
         });
     }
 
     pub fn print(&mut self) {
+        let counts = Vec::from_iter(
+            self.rows
+                .iter()
+                .map(|row| row.count.map(format_count).unwrap_or_default()),
+        );
         let mut title_width = 0;
         let mut count_width = 0;
         let mut notes_width = 0;
-        for row in &self.rows {
+        for (row, count) in self.rows.iter().zip(&counts) {
             title_width = title_width.max(row.title.len());
-            count_width = count_width.max(row.count.len());
+            count_width = count_width.max(count.len());
             notes_width = notes_width.max(row.notes.len());
         }
 
         println!();
         println!("Summary");
         println!("====");
-        for row in &self.rows {
+        for (row, count) in self.rows.iter().zip(&counts) {
             println!(
                 "{:<title_width$}  {:>count_width$}  {:<notes_width$}",
-                row.title, row.count, row.notes,
+                row.title, count, row.notes,
             );
         }
         println!();
 
         self.push_hr();
     }
+
+    /// Returns the rows as a Markdown table. Separators are skipped.
+    pub fn to_markdown(&self) -> String {
+        let cell = |text: &str| text.replace('|', "\\|");
+        let mut result = String::from("| Title | Count | Notes |\n| --- | ---: | --- |\n");
+        for row in &self.rows {
+            if let Some(count) = row.count {
+                result.push_str(&format!(
+                    "| {} | {} | {} |\n",
+                    cell(&row.title),
+                    format_count(count),
+                    cell(&row.notes),
+                ));
+            }
+        }
+        result
+    }
+
+    /// Writes the rows as a JSON array of `{"title", "count", "notes"}`
+    /// objects, with counts as plain integers. Separators are skipped.
+    pub fn write_json<W: Write>(&self, mut w: W) -> io::Result<()> {
+        write!(w, "[")?;
+        let rows = self.rows.iter().filter_map(|row| Some((row, row.count?)));
+        for (index, (row, count)) in rows.enumerate() {
+            if index > 0 {
+                write!(w, ",")?;
+            }
+            write!(
+                w,
+                "{{\"title\":{},\"count\":{count},\"notes\":{}}}",
+                json_string(&row.title),
+                json_string(&row.notes),
+            )?;
+        }
+        writeln!(w, "]")
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut result = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_count_grouped, Table};
+
+    #[cfg(all(feature = "num-format", not(feature = "std-format")))]
+    #[test]
+    fn fallback_locale_groups_by_thousands() {
+        use super::{format_count_with, FALLBACK_LOCALE};
+
+        assert_eq!(
+            format_count_with(20_382_606_825, &FALLBACK_LOCALE),
+            "20,382,606,825",
+        );
+        assert_eq!(format_count_with(999, &FALLBACK_LOCALE), "999");
+        assert_eq!(format_count_with(1_234_567, &FALLBACK_LOCALE), "1,234,567");
+    }
+
+    #[test]
+    fn grouped_by_thousands() {
+        assert_eq!(format_count_grouped(1_234_567), "1,234,567");
+        assert_eq!(format_count_grouped(20_382_606_825), "20,382,606,825");
+        assert_eq!(format_count_grouped(100_000), "100,000");
+        assert_eq!(format_count_grouped(999), "999");
+        assert_eq!(format_count_grouped(0), "0");
+    }
+
+    #[test]
+    fn to_markdown() {
+        let mut table = Table::default();
+        table.push("1x1 corners", 4, "".to_string());
+        table.push_hr();
+        table.push("1x1 edges", 56, "a|b".to_string());
+
+        assert_eq!(
+            table.to_markdown(),
+            "| Title | Count | Notes |\n\
+             | --- | ---: | --- |\n\
+             | 1x1 corners | 4 |  |\n\
+             | 1x1 edges | 56 | a\\|b |\n",
+        );
+    }
+
+    #[test]
+    fn write_json() {
+        let mut table = Table::default();
+        table.push("1x1 corners", 16, "".to_string());
+        table.push_hr();
+        table.push(
+            "2x2 \"corners\"",
+            20_382_606_825,
+            "ratio 0.5000".to_string(),
+        );
+
+        let mut json = Vec::new();
+        table.write_json(&mut json).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!([
+                {"title": "1x1 corners", "count": 16, "notes": ""},
+                {"title": "2x2 \"corners\"", "count": 20_382_606_825_u64, "notes": "ratio 0.5000"},
+            ]),
+        );
+    }
 }
@@ -1,7 +1,12 @@
 use bitint::prelude::*;
-use std::io::{self, Write};
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::ops::Range;
 
-use crate::{Color, RotatedTile, Rotation, Tile};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::mosaic::{bucas_edge_index, ArrayMosaic};
+use crate::{Color, RotatedTile, Rotation, Side, Tile};
 
 #[derive(Clone, Copy)]
 pub struct Literal(isize);
@@ -20,6 +25,45 @@ impl Literal {
     {
         Self(-(variable.into() as isize))
     }
+
+    fn print_smt2_fragment<W: Write>(self, mut w: W) -> io::Result<()> {
+        if self.0 < 0 {
+            write!(w, "(not v{})", self.0.unsigned_abs())
+        } else {
+            write!(w, "v{}", self.0)
+        }
+    }
+}
+
+/// Writes a clause as an SMT-LIB 2 `assert`. A single literal is asserted
+/// directly, since `or` takes at least two arguments.
+fn print_smt2_clause<W: Write>(literals: &[Literal], mut w: W) -> io::Result<()> {
+    match literals {
+        [] => writeln!(w, "(assert false)"),
+        [literal] => {
+            write!(w, "(assert ")?;
+            literal.print_smt2_fragment(w.by_ref())?;
+            writeln!(w, ")")
+        }
+        _ => {
+            write!(w, "(assert (or")?;
+            for literal in literals {
+                write!(w, " ")?;
+                literal.print_smt2_fragment(w.by_ref())?;
+            }
+            writeln!(w, "))")
+        }
+    }
+}
+
+/// Writes a block of iCNF assumptions as one `a` line: the literals assumed
+/// true for one solve, then `0`, like `a 1 -2 0`.
+pub fn print_icnf_assumptions<W: Write>(literals: &[Literal], mut w: W) -> io::Result<()> {
+    write!(w, "a")?;
+    for literal in literals {
+        write!(w, " {}", literal.0)?;
+    }
+    writeln!(w, " 0")
 }
 
 pub struct BinaryClause {
@@ -34,6 +78,10 @@ impl BinaryClause {
     pub fn print_dimacs_fragment<W: Write>(&self, mut w: W) -> io::Result<()> {
         writeln!(w, "{} {} 0", self.literals[0].0, self.literals[1].0)
     }
+
+    pub fn print_smt2_fragment<W: Write>(&self, w: W) -> io::Result<()> {
+        print_smt2_clause(&self.literals, w)
+    }
 }
 
 pub struct LongClause {
@@ -54,15 +102,54 @@ impl LongClause {
         }
         writeln!(w, " 0")
     }
+
+    pub fn print_smt2_fragment<W: Write>(&self, w: W) -> io::Result<()> {
+        print_smt2_clause(&self.literals, w)
+    }
 }
 
-#[derive(Default)]
 pub struct Clauses {
     binary: Vec<BinaryClause>,
     long: Vec<LongClause>,
+    sections: Vec<Section>,
+    /// The number of variables in the instance's [`Variable`] encoding.
+    instance_variables: usize,
+    /// The number of auxiliary variables declared after the instance's
+    /// variables.
+    aux_variables: usize,
+    /// Assumption blocks for [`Self::write_icnf`], in order.
+    assumption_blocks: Vec<Vec<Literal>>,
+}
+
+/// A named run of clauses, starting at the given offsets into the binary and
+/// long clause lists.
+struct Section {
+    name: String,
+    binary_start: usize,
+    long_start: usize,
+}
+
+/// Returns clauses for the full 16x16 puzzle, declaring every [`FullVariable`].
+impl Default for Clauses {
+    fn default() -> Self {
+        Self::for_instance::<16, 256>()
+    }
 }
 
 impl Clauses {
+    /// Returns empty clauses for an instance encoded with
+    /// `Variable<SIZE, TILES>`, whose variables the `p cnf` header declares.
+    pub fn for_instance<const SIZE: usize, const TILES: usize>() -> Self {
+        Self {
+            binary: Vec::new(),
+            long: Vec::new(),
+            sections: Vec::new(),
+            instance_variables: Variable::<SIZE, TILES>::COUNT,
+            aux_variables: 0,
+            assumption_blocks: Vec::new(),
+        }
+    }
+
     pub fn push_binary(&mut self, a: Literal, b: Literal) {
         self.binary.push(BinaryClause::new(a, b));
     }
@@ -79,6 +166,150 @@ impl Clauses {
         self.binary.len() + self.long.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.binary.is_empty() && self.long.is_empty()
+    }
+
+    /// Returns the variable count for the `p cnf` header: every variable of
+    /// the instance given to [`Self::for_instance`], then any auxiliary
+    /// variables.
+    pub fn variable_count(&self) -> usize {
+        self.instance_variables + self.aux_variables
+    }
+
+    /// Declares `count` more auxiliary variables, which the caller numbers
+    /// from `self.variable_count() + 1`.
+    pub fn add_aux_variables(&mut self, count: usize) {
+        self.aux_variables += count;
+    }
+
+    /// Returns the number of distinct variables that appear in at least one
+    /// clause, regardless of polarity.
+    pub fn used_variable_count(&self) -> usize {
+        self.used_variables()
+            .into_iter()
+            .filter(|&used| used)
+            .count()
+    }
+
+    /// Returns a vector indexed by variable of whether it appears in at least
+    /// one clause.
+    fn used_variables(&self) -> Vec<bool> {
+        let mut used = Vec::new();
+        let literals = self
+            .binary
+            .iter()
+            .flat_map(|clause| clause.literals.iter())
+            .chain(self.long.iter().flat_map(|clause| clause.literals.iter()));
+        for literal in literals {
+            let index = literal.0.unsigned_abs();
+            if index >= used.len() {
+                used.resize(index + 1, false);
+            }
+            used[index] = true;
+        }
+        used
+    }
+
+    /// Appends `other`'s clauses after this one's. Sections in `other` are
+    /// dropped, so its clauses join this one's current section.
+    pub fn append(&mut self, mut other: Self) {
+        self.binary.append(&mut other.binary);
+        self.long.append(&mut other.long);
+    }
+
+    /// Calls `emit` for each region across rayon threads, each into its own
+    /// buffer, then appends the buffers in region order. The result is the
+    /// same as emitting the regions one after another, so output stays
+    /// deterministic. Every buffer is held until all regions finish, so this
+    /// briefly needs twice the memory of the clauses it emits.
+    pub fn emit_regions<I>(&mut self, regions: I, emit: impl Fn(&mut Clauses, I::Item) + Sync)
+    where
+        I: IntoParallelIterator,
+    {
+        let buffers: Vec<Clauses> = regions
+            .into_par_iter()
+            .map(|region| {
+                let mut buffer = Clauses::default();
+                emit(&mut buffer, region);
+                buffer
+            })
+            .collect();
+        for buffer in buffers {
+            self.append(buffer);
+        }
+    }
+
+    /// Starts a new named section. Clauses pushed from now until the next call
+    /// belong to it. Sections only affect [`Self::print_annotated_dimacs_fragment`].
+    pub fn begin_section(&mut self, name: impl Into<String>) {
+        self.sections.push(Section {
+            name: name.into(),
+            binary_start: self.binary.len(),
+            long_start: self.long.len(),
+        });
+    }
+
+    /// Writes a complete DIMACS CNF file: the `p cnf` header followed by all
+    /// clauses.
+    pub fn write_dimacs<W: Write>(&self, mut w: W) -> io::Result<()> {
+        self.print_dimacs_header(w.by_ref())?;
+        self.print_dimacs_fragment(w)
+    }
+
+    /// Like [`Self::write_dimacs`], but with the clauses annotated as in
+    /// [`Self::print_annotated_dimacs_fragment`].
+    pub fn write_annotated_dimacs<W: Write>(&self, mut w: W) -> io::Result<()> {
+        self.print_dimacs_header(w.by_ref())?;
+        self.print_annotated_dimacs_fragment(w)
+    }
+
+    /// Adds a block of assumptions for [`Self::write_icnf`] to write after the
+    /// clauses. Assumptions don't count as clauses.
+    pub fn push_assumptions(&mut self, literals: Vec<Literal>) {
+        self.assumption_blocks.push(literals);
+    }
+
+    /// Writes the problem in the incremental iCNF format: a `p inccnf` header,
+    /// the clauses as in DIMACS, and then each block of assumptions from
+    /// [`Self::push_assumptions`] as written by [`print_icnf_assumptions`].
+    ///
+    /// A solver reading iCNF solves under each block of assumptions in turn,
+    /// keeping what it learned from the clauses, so more blocks can be
+    /// appended to the file later without rewriting the clauses.
+    pub fn write_icnf<W: Write>(&self, mut w: W) -> io::Result<()> {
+        writeln!(w, "p inccnf")?;
+        self.print_dimacs_fragment(w.by_ref())?;
+        for literals in &self.assumption_blocks {
+            print_icnf_assumptions(literals, w.by_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Writes the problem in SMT-LIB 2 as an alternative to DIMACS: a
+    /// `declare-const vN Bool` for each variable used by a clause, an `assert`
+    /// for each clause, and then `check-sat` and `get-model`.
+    pub fn write_smt2<W: Write>(&self, mut w: W) -> io::Result<()> {
+        writeln!(w, "(set-logic QF_UF)")?;
+        for (index, used) in self.used_variables().into_iter().enumerate() {
+            if used {
+                writeln!(w, "(declare-const v{index} Bool)")?;
+            }
+        }
+        for clause in &self.binary {
+            clause.print_smt2_fragment(w.by_ref())?;
+        }
+        for clause in &self.long {
+            clause.print_smt2_fragment(w.by_ref())?;
+        }
+        writeln!(w, "(check-sat)")?;
+        writeln!(w, "(get-model)")
+    }
+
+    fn print_dimacs_header<W: Write>(&self, mut w: W) -> io::Result<()> {
+        writeln!(w, "p cnf {} {}", self.variable_count(), self.len())
+    }
+
     pub fn print_dimacs_fragment<W: Write>(&self, mut w: W) -> io::Result<()> {
         for clause in &self.binary {
             clause.print_dimacs_fragment(w.by_ref())?;
@@ -89,6 +320,39 @@ impl Clauses {
         Ok(())
     }
 
+    /// Like [`Self::print_dimacs_fragment`], but prints each section's clauses
+    /// together, preceded by a comment line naming the section. Clauses pushed
+    /// before the first section are printed first, without a comment.
+    pub fn print_annotated_dimacs_fragment<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let names = std::iter::once(None).chain(
+            self.sections
+                .iter()
+                .map(|section| Some(section.name.as_str())),
+        );
+        let ends = self
+            .sections
+            .iter()
+            .map(|section| (section.binary_start, section.long_start))
+            .chain([(self.binary.len(), self.long.len())]);
+
+        let mut binary_start = 0;
+        let mut long_start = 0;
+        for (name, (binary_end, long_end)) in names.zip(ends) {
+            if let Some(name) = name {
+                writeln!(w, "c {name}")?;
+            }
+            for clause in &self.binary[binary_start..binary_end] {
+                clause.print_dimacs_fragment(w.by_ref())?;
+            }
+            for clause in &self.long[long_start..long_end] {
+                clause.print_dimacs_fragment(w.by_ref())?;
+            }
+            binary_start = binary_end;
+            long_start = long_end;
+        }
+        Ok(())
+    }
+
     pub fn emit_at_most_one_of<V>(&mut self, variables: &[V])
     where
         V: Copy + Into<usize>,
@@ -112,99 +376,341 @@ impl Clauses {
                 .collect(),
         );
     }
+
+    pub fn emit_exactly_one_of<V>(&mut self, variables: &[V])
+    where
+        V: Copy + Into<usize>,
+    {
+        self.emit_at_most_one_of(variables);
+        self.emit_at_least_one_of(variables);
+    }
+
+    /// Emits clauses allowing at most `k` of `variables` to be true, using
+    /// Sinz's sequential counter. `aux` is called for each fresh auxiliary
+    /// variable, `(n - 1) * k` in all, which must not collide with any other.
+    ///
+    /// Unlike [`Self::emit_at_most_one_of`], this takes a number of clauses
+    /// linear in `n * k` rather than quadratic in `n`.
+    pub fn emit_at_most_k_of<V>(
+        &mut self,
+        variables: &[V],
+        k: usize,
+        aux: &mut dyn FnMut() -> usize,
+    ) where
+        V: Copy + Into<usize>,
+    {
+        let n = variables.len();
+        if k >= n {
+            return;
+        }
+        if k == 0 {
+            for &variable in variables {
+                self.push_unit(Literal::negative(variable));
+            }
+            return;
+        }
+
+        // counts[j] is true if at least j + 1 of the variables so far are true.
+        let mut counts = Vec::from_iter((0..k).map(|_| aux()));
+        self.push_binary(
+            Literal::negative(variables[0]),
+            Literal::positive(counts[0]),
+        );
+        for &count in &counts[1..] {
+            self.push_unit(Literal::negative(count));
+        }
+        for &variable in &variables[1..n - 1] {
+            let next_counts = Vec::from_iter((0..k).map(|_| aux()));
+            self.push_binary(
+                Literal::negative(variable),
+                Literal::positive(next_counts[0]),
+            );
+            for (j, (&count, &next_count)) in counts.iter().zip(&next_counts).enumerate() {
+                self.push_binary(Literal::negative(count), Literal::positive(next_count));
+                if j > 0 {
+                    self.push_long(vec![
+                        Literal::negative(variable),
+                        Literal::negative(counts[j - 1]),
+                        Literal::positive(next_count),
+                    ]);
+                }
+            }
+            self.push_binary(
+                Literal::negative(variable),
+                Literal::negative(counts[k - 1]),
+            );
+            counts = next_counts;
+        }
+        self.push_binary(
+            Literal::negative(variables[n - 1]),
+            Literal::negative(counts[k - 1]),
+        );
+    }
+}
+
+/// Summary statistics for a CNF instance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InstanceManifest {
+    /// The variable count declared in the `p cnf` header.
+    pub declared_variables: usize,
+    /// The number of distinct variables mentioned by at least one clause.
+    pub used_variables: usize,
+    pub clauses: usize,
+}
+
+impl InstanceManifest {
+    pub fn new(declared_variables: usize, clauses: &Clauses) -> Self {
+        Self {
+            declared_variables,
+            used_variables: clauses.used_variable_count(),
+            clauses: clauses.len(),
+        }
+    }
+
+    /// Returns the ratio of clauses to used variables, a rough proxy for
+    /// instance difficulty.
+    pub fn clause_variable_ratio(&self) -> f64 {
+        self.clauses as f64 / self.used_variables as f64
+    }
+
+    /// Writes the manifest as DIMACS comment lines.
+    pub fn print_dimacs_comments<W: Write>(&self, w: W) -> io::Result<()> {
+        self.print_comments("c", w)
+    }
+
+    /// Writes the manifest as SMT-LIB 2 comment lines.
+    pub fn print_smt2_comments<W: Write>(&self, w: W) -> io::Result<()> {
+        self.print_comments(";", w)
+    }
+
+    fn print_comments<W: Write>(&self, prefix: &str, mut w: W) -> io::Result<()> {
+        writeln!(
+            w,
+            "{prefix} declared variables: {}",
+            self.declared_variables
+        )?;
+        writeln!(w, "{prefix} used variables: {}", self.used_variables)?;
+        writeln!(w, "{prefix} clauses: {}", self.clauses)?;
+        writeln!(
+            w,
+            "{prefix} clause/variable ratio: {:.3}",
+            self.clause_variable_ratio(),
+        )
+    }
+}
+
+/// The number of rotated tiles that can be placed in a cell when choosing among
+/// `tiles` tiles.
+const fn rotated_tile_count(tiles: usize) -> usize {
+    4 * tiles
+}
+
+/// The number of edge colors that can appear between two cells: every color
+/// but exterior.
+const NON_EXTERIOR_COLOR_COUNT: usize = 22;
+
+/// Panics with a clear message unless `color` is one of the colors that edge
+/// color variables encode. Exterior would otherwise underflow the encoding.
+fn assert_encodable_edge_color(color: Color) {
+    assert!(
+        color.is_valid_non_border_color(),
+        "edge color variables can't encode color {}, which is exterior or out of range",
+        color.to_primitive(),
+    );
 }
 
+/// A SAT variable for a board with `SIZE` cells on a side, choosing among tiles
+/// `0..TILES`. A sub-puzzle can use fewer cells than tiles, such as a 4x4
+/// corner drawn from all 256 tiles.
+///
 /// Coordinate system:
 ///
-/// - X denotes column, 0..16, increasing from left to right.
-/// - Y denotes row, 0..16, increasing from top to bottom.
+/// - X denotes column, 0..SIZE, increasing from left to right.
+/// - Y denotes row, 0..SIZE, increasing from top to bottom.
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Variable(usize);
+pub struct Variable<const SIZE: usize, const TILES: usize>(usize);
+
+/// A variable for the full 16x16 puzzle.
+pub type FullVariable = Variable<16, 256>;
 
 #[bitint_literals]
-impl Variable {
+impl<const SIZE: usize, const TILES: usize> Variable<SIZE, TILES> {
+    /// Fails to compile unless cells fit in a [`U4`] and tiles fit in a
+    /// [`Tile`].
+    const SIZE_IS_SUPPORTED: () = {
+        assert!(SIZE >= 2);
+        assert!(SIZE <= 16);
+        assert!(TILES >= 1);
+        assert!(TILES <= 256);
+    };
+
+    const TILE_PLACEMENT_X_STRIDE: usize = rotated_tile_count(TILES);
+    const TILE_PLACEMENT_Y_STRIDE: usize = SIZE * Self::TILE_PLACEMENT_X_STRIDE;
     const TILE_PLACEMENT_BASE: usize = 1;
-    const TILE_PLACEMENT_COUNT: usize = 16 * 16 * 1024;
+    const TILE_PLACEMENT_COUNT: usize = SIZE * Self::TILE_PLACEMENT_Y_STRIDE;
 
+    const RIGHT_EDGE_COLOR_Y_STRIDE: usize = (SIZE - 1) * NON_EXTERIOR_COLOR_COUNT;
     const RIGHT_EDGE_COLOR_BASE: usize = Self::TILE_PLACEMENT_BASE + Self::TILE_PLACEMENT_COUNT;
-    const RIGHT_EDGE_COLOR_COUNT: usize = 15 * 16 * 22;
+    const RIGHT_EDGE_COLOR_COUNT: usize = SIZE * Self::RIGHT_EDGE_COLOR_Y_STRIDE;
 
+    const BOTTOM_EDGE_COLOR_Y_STRIDE: usize = SIZE * NON_EXTERIOR_COLOR_COUNT;
     const BOTTOM_EDGE_COLOR_BASE: usize =
         Self::RIGHT_EDGE_COLOR_BASE + Self::RIGHT_EDGE_COLOR_COUNT;
-    const BOTTOM_EDGE_COLOR_COUNT: usize = 16 * 15 * 22;
+    const BOTTOM_EDGE_COLOR_COUNT: usize = (SIZE - 1) * Self::BOTTOM_EDGE_COLOR_Y_STRIDE;
 
     // NOTE: Subtract one because count is zero-based, but variable indices are one-based.
     pub const COUNT: usize = Self::BOTTOM_EDGE_COLOR_BASE + Self::BOTTOM_EDGE_COLOR_COUNT - 1;
 
+    fn new(index: usize) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::SIZE_IS_SUPPORTED;
+        Self(index)
+    }
+
     pub fn for_tile_placement(x: U4, y: U4, rotated_tile: RotatedTile) -> Self {
-        Self(
-            16384 * y.to_primitive() as usize
-                + 1024 * x.to_primitive() as usize
+        assert!((x.to_primitive() as usize) < SIZE);
+        assert!((y.to_primitive() as usize) < SIZE);
+        assert!((rotated_tile.tile.to_primitive() as usize) < TILES);
+        Self::new(
+            Self::TILE_PLACEMENT_Y_STRIDE * y.to_primitive() as usize
+                + Self::TILE_PLACEMENT_X_STRIDE * x.to_primitive() as usize
                 + 4 * rotated_tile.tile.to_primitive() as usize
                 + rotated_tile.rotation.to_primitive() as usize
                 + Self::TILE_PLACEMENT_BASE,
         )
+        .debug_assert_decodes_to(VariableKind::TilePlacement { x, y, rotated_tile })
     }
 
+    /// # Panics
+    ///
+    /// Panics if `color` is exterior or out of range, since only the 22 other
+    /// colors are encoded.
     pub fn for_right_edge_color(x: U4, y: U4, color: Color) -> Self {
-        assert!(x < 15_U4);
-        assert!(color.is_valid_non_border_color());
-        Self(
-            330 * y.to_primitive() as usize
-                + 22 * x.to_primitive() as usize
+        assert!((x.to_primitive() as usize) < SIZE - 1);
+        assert!((y.to_primitive() as usize) < SIZE);
+        assert_encodable_edge_color(color);
+        Self::new(
+            Self::RIGHT_EDGE_COLOR_Y_STRIDE * y.to_primitive() as usize
+                + NON_EXTERIOR_COLOR_COUNT * x.to_primitive() as usize
                 + (color.to_primitive() as usize - 1)
                 + Self::RIGHT_EDGE_COLOR_BASE,
         )
+        .debug_assert_decodes_to(VariableKind::RightEdgeColor { x, y, color })
     }
 
+    /// # Panics
+    ///
+    /// Panics if `color` is exterior or out of range, since only the 22 other
+    /// colors are encoded.
     pub fn for_bottom_edge_color(x: U4, y: U4, color: Color) -> Self {
-        assert!(y < 15_U4);
-        assert!(color.is_valid_non_border_color());
-        Self(
-            352 * y.to_primitive() as usize
-                + 22 * x.to_primitive() as usize
+        assert!((x.to_primitive() as usize) < SIZE);
+        assert!((y.to_primitive() as usize) < SIZE - 1);
+        assert_encodable_edge_color(color);
+        Self::new(
+            Self::BOTTOM_EDGE_COLOR_Y_STRIDE * y.to_primitive() as usize
+                + NON_EXTERIOR_COLOR_COUNT * x.to_primitive() as usize
                 + (color.to_primitive() as usize - 1)
                 + Self::BOTTOM_EDGE_COLOR_BASE,
         )
+        .debug_assert_decodes_to(VariableKind::BottomEdgeColor { x, y, color })
+    }
+
+    /// In debug builds, checks that this variable decodes back to the `kind`
+    /// it was encoded from, which would catch two inputs sharing an index.
+    fn debug_assert_decodes_to(self, kind: VariableKind) -> Self {
+        debug_assert!(
+            (1..=Self::COUNT).contains(&self.0),
+            "{kind:?} encodes to {}, outside 1..={}",
+            self.0,
+            Self::COUNT,
+        );
+        debug_assert_eq!(self.kind(), kind, "variable {} collides", self.0);
+        self
     }
 
+    /// Decodes this variable's meaning.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is outside `1..=Self::COUNT`, such as an auxiliary
+    /// variable numbered after the board's own.
     pub fn kind(self) -> VariableKind {
+        assert!(
+            (1..=Self::COUNT).contains(&self.0),
+            "variable {} is outside 1..={}",
+            self.0,
+            Self::COUNT,
+        );
         if self.0 < Self::RIGHT_EDGE_COLOR_BASE {
             let i = self.0 - Self::TILE_PLACEMENT_BASE;
             VariableKind::TilePlacement {
-                x: U4::new_masked((i / 1024) as u8),
-                y: U4::new_masked((i / 16384) as u8),
+                x: U4::new_masked((i / Self::TILE_PLACEMENT_X_STRIDE % SIZE) as u8),
+                y: U4::new_masked((i / Self::TILE_PLACEMENT_Y_STRIDE) as u8),
                 rotated_tile: RotatedTile {
-                    tile: Tile::from_primitive((i / 4) as u8),
+                    tile: Tile::from_primitive((i / 4 % TILES) as u8),
                     rotation: Rotation::new_masked(i as u8),
                 },
             }
         } else if self.0 < Self::BOTTOM_EDGE_COLOR_BASE {
             let i = self.0 - Self::RIGHT_EDGE_COLOR_BASE;
             VariableKind::RightEdgeColor {
-                x: U4::new_masked((i / 22 % 15) as u8),
-                y: U4::new_masked((i / 330) as u8),
-                color: Color::new_masked((i % 22 + 1) as u8),
+                x: U4::new_masked((i / NON_EXTERIOR_COLOR_COUNT % (SIZE - 1)) as u8),
+                y: U4::new_masked((i / Self::RIGHT_EDGE_COLOR_Y_STRIDE) as u8),
+                color: Color::new_masked((i % NON_EXTERIOR_COLOR_COUNT + 1) as u8),
             }
         } else {
             let i = self.0 - Self::BOTTOM_EDGE_COLOR_BASE;
             VariableKind::BottomEdgeColor {
-                x: U4::new_masked((i / 22) as u8),
-                y: U4::new_masked((i / 352) as u8),
-                color: Color::new_masked((i % 22 + 1) as u8),
+                x: U4::new_masked((i / NON_EXTERIOR_COLOR_COUNT % SIZE) as u8),
+                y: U4::new_masked((i / Self::BOTTOM_EDGE_COLOR_Y_STRIDE) as u8),
+                color: Color::new_masked((i % NON_EXTERIOR_COLOR_COUNT + 1) as u8),
             }
         }
     }
+
+    /// Returns the range of indices used by each kind of variable, named after
+    /// its [`VariableKind`] variant. The ranges are in order and together cover
+    /// `1..=COUNT`.
+    pub fn kind_ranges() -> [(&'static str, Range<usize>); 3] {
+        [
+            (
+                "TilePlacement",
+                Self::TILE_PLACEMENT_BASE..Self::RIGHT_EDGE_COLOR_BASE,
+            ),
+            (
+                "RightEdgeColor",
+                Self::RIGHT_EDGE_COLOR_BASE..Self::BOTTOM_EDGE_COLOR_BASE,
+            ),
+            (
+                "BottomEdgeColor",
+                Self::BOTTOM_EDGE_COLOR_BASE
+                    ..Self::BOTTOM_EDGE_COLOR_BASE + Self::BOTTOM_EDGE_COLOR_COUNT,
+            ),
+        ]
+    }
+
+    /// Writes one line per [`Self::kind_ranges`] entry, giving the name and
+    /// the first and last variable, inclusive:
+    ///
+    /// ```text
+    /// TilePlacement 1 262144
+    /// ```
+    pub fn write_kind_ranges<W: Write>(mut w: W) -> io::Result<()> {
+        for (name, range) in Self::kind_ranges() {
+            writeln!(w, "{name} {} {}", range.start, range.end - 1)?;
+        }
+        Ok(())
+    }
 }
 
-impl From<usize> for Variable {
+impl<const SIZE: usize, const TILES: usize> From<usize> for Variable<SIZE, TILES> {
     fn from(index: usize) -> Self {
         Self(index)
     }
 }
 
-impl From<Variable> for usize {
-    fn from(variable: Variable) -> usize {
+impl<const SIZE: usize, const TILES: usize> From<Variable<SIZE, TILES>> for usize {
+    fn from(variable: Variable<SIZE, TILES>) -> usize {
         variable.0
     }
 }
@@ -228,45 +734,763 @@ pub enum VariableKind {
     },
 }
 
+/// Returns the number of seams of each color on a solved 16x16 board, indexed
+/// by the color's primitive value.
+///
+/// Every non-exterior side of every tile meets exactly one other side, so each
+/// color's seams number half its [frequency](Tile::color_frequencies). That's
+/// 12, 24, or 25 for each color, 480 seams in all, and none for exterior.
+pub fn seam_color_counts() -> [usize; 23] {
+    let mut counts = Tile::color_frequencies().map(|frequency| frequency / 2);
+    counts[Color::EXTERIOR.to_primitive() as usize] = 0;
+    counts
+}
+
+/// Emits redundant constraints that each color is assigned to at most its
+/// [`seam_color_counts`] entry of the 480 right and bottom edge color
+/// variables, declaring the auxiliary variables they need.
+///
+/// The edge implications already give every seam exactly one color, and the
+/// counts sum to the number of seams, so each count is met exactly. The
+/// constraints add no solutions or remove any, but they let a solver see
+/// color budgets run out early.
+pub fn emit_seam_color_counts(clauses: &mut Clauses) {
+    let counts = seam_color_counts();
+    let first_aux = clauses.variable_count() + 1;
+    let mut next_aux = first_aux;
+    for color in Color::iter().filter(|color| !color.is_exterior()) {
+        let mut variables = Vec::new();
+        for y in 0..16 {
+            for x in 0..16 {
+                let (cell_x, cell_y) = (U4::new_masked(x as u8), U4::new_masked(y as u8));
+                if x < 15 {
+                    variables.push(FullVariable::for_right_edge_color(cell_x, cell_y, color));
+                }
+                if y < 15 {
+                    variables.push(FullVariable::for_bottom_edge_color(cell_x, cell_y, color));
+                }
+            }
+        }
+        clauses.emit_at_most_k_of(
+            &variables,
+            counts[color.to_primitive() as usize],
+            &mut || {
+                next_aux += 1;
+                next_aux - 1
+            },
+        );
+    }
+    clauses.add_aux_variables(next_aux - first_aux);
+}
+
+/// Returns the board variables that a model's DIMACS literals set true.
+///
+/// Auxiliary variables, such as those of [`emit_seam_color_counts`], are
+/// numbered after [`FullVariable::COUNT`] and skipped.
+fn true_board_variables(
+    literals: impl IntoIterator<Item = isize>,
+) -> impl Iterator<Item = FullVariable> {
+    literals
+        .into_iter()
+        .filter(|&literal| literal > 0 && literal.unsigned_abs() <= FullVariable::COUNT)
+        .map(|literal| FullVariable::from(literal.unsigned_abs()))
+}
+
+/// Builds the board assigned by a solver's model, given as DIMACS literals.
+///
+/// Only positive tile placement literals are considered. Edge color and
+/// auxiliary variables are ignored, so the resulting mosaic should still be
+/// checked for matching edges.
+pub fn model_to_mosaic(
+    literals: impl IntoIterator<Item = isize>,
+) -> Result<ArrayMosaic<16, 16>, ModelError> {
+    let mut cells = [[None; 16]; 16];
+    for variable in true_board_variables(literals) {
+        if let VariableKind::TilePlacement { x, y, rotated_tile } = variable.kind() {
+            let cell = &mut cells[y.to_primitive() as usize][x.to_primitive() as usize];
+            if cell.is_some() {
+                return Err(ModelError::ConflictingPlacements { x, y });
+            }
+            *cell = Some(rotated_tile);
+        }
+    }
+
+    for (y, row) in cells.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            if cell.is_none() {
+                return Err(ModelError::MissingPlacement {
+                    x: U4::new_masked(x as u8),
+                    y: U4::new_masked(y as u8),
+                });
+            }
+        }
+    }
+    Ok(ArrayMosaic {
+        tiles: cells.map(|row| row.map(Option::unwrap)),
+    })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModelError {
+    MissingPlacement { x: U4, y: U4 },
+    ConflictingPlacements { x: U4, y: U4 },
+}
+
+impl Display for ModelError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Self::MissingPlacement { x, y } => write!(f, "no tile placed at ({x}, {y})"),
+            Self::ConflictingPlacements { x, y } => {
+                write!(f, "more than one tile placed at ({x}, {y})")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ModelError {}
+
+/// Builds the bucas.name `board_edges` for a solver's model, given as DIMACS
+/// literals, along with every problem found: tiles placed more than once or
+/// not at all, and true edge color variables that disagree with the tiles on
+/// either side of their edge.
+///
+/// The board is built even when there are problems, so a partly bad model can
+/// still be viewed. Cells without a tile are left as `_`. The board is ASCII.
+///
+/// Edge conflicts are expected in models of problems emitted without the edge
+/// implications, which leave edge color variables unconstrained. Auxiliary
+/// variables are ignored.
+pub fn reconstruct_board(
+    literals: impl IntoIterator<Item = isize>,
+) -> ([u8; 1024], Vec<ReconstructError>) {
+    // Put tile placements before edge colors so edges can be checked as they
+    // come.
+    let mut variables = Vec::from_iter(true_board_variables(literals));
+    variables.sort_unstable();
+
+    let mut problems = Vec::new();
+    let mut used_tiles = [false; 256];
+    let mut board_edges = [b'_'; 1024];
+    let check_edge = |board_edges: &[u8; 1024], x: usize, y: usize, side: Side, color: Color| {
+        (board_edges[bucas_edge_index(x, y, side)] != color.to_byte_char()).then(|| {
+            ReconstructError::EdgeConflict {
+                x: U4::new_masked(x as u8),
+                y: U4::new_masked(y as u8),
+                side,
+            }
+        })
+    };
+    for variable in variables {
+        match variable.kind() {
+            VariableKind::TilePlacement { x, y, rotated_tile } => {
+                let used = &mut used_tiles[rotated_tile.tile.to_primitive() as usize];
+                if *used {
+                    problems.push(ReconstructError::TileUsedTwice {
+                        tile: rotated_tile.tile,
+                    });
+                }
+                *used = true;
+                for side in [Side::Right, Side::Top, Side::Left, Side::Bottom] {
+                    board_edges[bucas_edge_index(
+                        x.to_primitive() as usize,
+                        y.to_primitive() as usize,
+                        side,
+                    )] = rotated_tile.color(side).to_byte_char();
+                }
+            }
+            VariableKind::RightEdgeColor { x, y, color } => {
+                let (x, y) = (x.to_primitive() as usize, y.to_primitive() as usize);
+                problems.extend(check_edge(&board_edges, x, y, Side::Right, color));
+                problems.extend(check_edge(&board_edges, x + 1, y, Side::Left, color));
+            }
+            VariableKind::BottomEdgeColor { x, y, color } => {
+                let (x, y) = (x.to_primitive() as usize, y.to_primitive() as usize);
+                problems.extend(check_edge(&board_edges, x, y, Side::Bottom, color));
+                problems.extend(check_edge(&board_edges, x, y + 1, Side::Top, color));
+            }
+        }
+    }
+
+    problems.extend(
+        Tile::values()
+            .filter(|tile| !used_tiles[tile.to_primitive() as usize])
+            .map(|tile| ReconstructError::TileUnused { tile }),
+    );
+    (board_edges, problems)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReconstructError {
+    TileUsedTwice {
+        tile: Tile,
+    },
+    TileUnused {
+        tile: Tile,
+    },
+    /// A true edge color variable disagrees with the given side of the tile at
+    /// `(x, y)`, or there's no tile there. Only an error if the problem
+    /// included the edge implications.
+    EdgeConflict {
+        x: U4,
+        y: U4,
+        side: Side,
+    },
+}
+
+impl Display for ReconstructError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Self::TileUsedTwice { tile } => write!(f, "tile {tile} used more than once"),
+            Self::TileUnused { tile } => write!(f, "tile {tile} not used"),
+            Self::EdgeConflict { x, y, side } => {
+                write!(f, "conflict at ({x}, {y}) {side:?} edge")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReconstructError {}
+
+/// Reads a DIMACS CNF file and checks it against its `p cnf` header, returning
+/// the declared variable and clause counts.
+///
+/// Fails if there's no header before the first clause, if the number of
+/// clauses differs from the header's, or if any literal refers to a variable
+/// beyond the header's count. Comment lines are skipped.
+pub fn read_and_validate_dimacs<R: Read>(r: R) -> Result<(usize, usize), DimacsError> {
+    let mut header = None;
+    let mut clause_count = 0;
+    let mut clause_open = false;
+    for (index, line) in BufReader::new(r).lines().enumerate() {
+        let line_number = index + 1;
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('c') {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix('p') {
+            let counts = match rest.split_ascii_whitespace().collect::<Vec<_>>()[..] {
+                ["cnf", variables, clauses] => variables.parse().ok().zip(clauses.parse().ok()),
+                _ => None,
+            };
+            if header.is_some() || counts.is_none() {
+                return Err(DimacsError::InvalidHeader { line: line_number });
+            }
+            header = counts;
+            continue;
+        }
+
+        let Some((variable_count, _)) = header else {
+            return Err(DimacsError::MissingHeader);
+        };
+        for token in line.split_ascii_whitespace() {
+            let literal: isize = token
+                .parse()
+                .map_err(|_| DimacsError::InvalidLiteral { line: line_number })?;
+            if literal == 0 {
+                clause_count += 1;
+                clause_open = false;
+            } else if literal.unsigned_abs() > variable_count {
+                return Err(DimacsError::LiteralOutOfRange {
+                    line: line_number,
+                    literal,
+                });
+            } else {
+                clause_open = true;
+            }
+        }
+    }
+
+    let Some((variable_count, declared_clause_count)) = header else {
+        return Err(DimacsError::MissingHeader);
+    };
+    if clause_open {
+        return Err(DimacsError::UnterminatedClause);
+    }
+    if clause_count != declared_clause_count {
+        return Err(DimacsError::ClauseCountMismatch {
+            declared: declared_clause_count,
+            actual: clause_count,
+        });
+    }
+    Ok((variable_count, declared_clause_count))
+}
+
+#[derive(Debug)]
+pub enum DimacsError {
+    Io(io::Error),
+    MissingHeader,
+    InvalidHeader { line: usize },
+    InvalidLiteral { line: usize },
+    LiteralOutOfRange { line: usize, literal: isize },
+    UnterminatedClause,
+    ClauseCountMismatch { declared: usize, actual: usize },
+}
+
+impl From<io::Error> for DimacsError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl Display for DimacsError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::MissingHeader => write!(f, "no `p cnf` header before the clauses"),
+            Self::InvalidHeader { line } => write!(f, "line {line}: invalid `p cnf` header"),
+            Self::InvalidLiteral { line } => write!(f, "line {line}: invalid literal"),
+            Self::LiteralOutOfRange { line, literal } => {
+                write!(
+                    f,
+                    "line {line}: literal {literal} exceeds the variable count"
+                )
+            }
+            Self::UnterminatedClause => write!(f, "the last clause is missing its 0"),
+            Self::ClauseCountMismatch { declared, actual } => {
+                write!(f, "header declares {declared} clauses, found {actual}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DimacsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 #[bitint_literals]
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
+    use std::time::Instant;
 
     use bitint::prelude::*;
     use strum::IntoEnumIterator;
 
-    use crate::{Color, RotatedTile, Rotation, Tile};
+    use crate::mosaic::{to_bucas_board_edges, ArrayMosaic, RectangularMosaic};
+    use crate::{Color, RotatedTile, Side, Tile};
 
-    use super::{Variable, VariableKind};
+    use super::{
+        emit_seam_color_counts, model_to_mosaic, print_icnf_assumptions, read_and_validate_dimacs,
+        reconstruct_board, seam_color_counts, Clauses, DimacsError, FullVariable, InstanceManifest,
+        Literal, ModelError, ReconstructError, Variable, VariableKind,
+    };
 
     #[test]
-    fn variable_encoding_is_unique_and_round_trips() {
+    #[should_panic(expected = "edge color variables can't encode color 0")]
+    fn right_edge_color_rejects_exterior() {
+        FullVariable::for_right_edge_color(0_U4, 0_U4, Color::EXTERIOR);
+    }
+
+    #[test]
+    #[should_panic(expected = "edge color variables can't encode color 0")]
+    fn bottom_edge_color_rejects_exterior() {
+        FullVariable::for_bottom_edge_color(0_U4, 0_U4, Color::EXTERIOR);
+    }
+
+    #[test]
+    fn seam_color_counts_match_frequencies() {
+        let counts = seam_color_counts();
+        let frequencies = Tile::color_frequencies();
+        assert_eq!(counts[0], 0);
+        for color in 1..23 {
+            assert_eq!(2 * counts[color], frequencies[color], "color {color}");
+        }
+        assert_eq!(counts.iter().sum::<usize>(), 2 * 16 * 15);
+    }
+
+    #[test]
+    fn seam_color_count_constraints() {
+        let mut clauses = Clauses::default();
+        emit_seam_color_counts(&mut clauses);
+
+        // A sequential counter over all 480 seams with limit k needs 479 * k
+        // auxiliary variables, so the declared total reflects every count.
+        let counts = seam_color_counts();
+        assert_eq!(
+            clauses.variable_count(),
+            FullVariable::COUNT + 479 * counts.iter().sum::<usize>(),
+        );
+
+        // The auxiliary variables stay within the declared count.
+        let mut output = Vec::new();
+        clauses.write_dimacs(&mut output).unwrap();
+        assert_eq!(
+            read_and_validate_dimacs(&output[..]).unwrap(),
+            (clauses.variable_count(), clauses.len()),
+        );
+    }
+
+    #[test]
+    fn manifest_clause_variable_ratio() {
+        let mut clauses = Clauses::default();
+        clauses.push_binary(Literal::positive(1usize), Literal::negative(2usize));
+        clauses.push_binary(Literal::negative(1usize), Literal::positive(2usize));
+        clauses.push_long(vec![
+            Literal::positive(2usize),
+            Literal::positive(5usize),
+            Literal::negative(7usize),
+        ]);
+        clauses.push_unit(Literal::negative(5usize));
+
+        let manifest = InstanceManifest::new(10, &clauses);
+        assert_eq!(manifest.declared_variables, 10);
+        assert_eq!(manifest.used_variables, 4);
+        assert_eq!(manifest.clauses, 4);
+        assert_eq!(manifest.clause_variable_ratio(), 1.0);
+
+        clauses.push_unit(Literal::positive(7usize));
+        let manifest = InstanceManifest::new(10, &clauses);
+        assert_eq!(manifest.clause_variable_ratio(), 5.0 / 4.0);
+    }
+
+    #[test]
+    fn is_empty() {
+        let mut clauses = Clauses::default();
+        assert!(clauses.is_empty());
+        clauses.push_unit(Literal::positive(1usize));
+        assert!(!clauses.is_empty());
+    }
+
+    #[test]
+    fn exactly_one_is_at_most_one_and_at_least_one() {
+        let variables = [1usize, 2, 3, 4, 5];
+
+        let mut separate = Clauses::default();
+        separate.emit_at_most_one_of(&variables);
+        separate.emit_at_least_one_of(&variables);
+        let mut combined = Clauses::default();
+        combined.emit_exactly_one_of(&variables);
+
+        assert_eq!(combined.len(), separate.len());
+        let mut separate_output = Vec::new();
+        separate
+            .print_dimacs_fragment(&mut separate_output)
+            .unwrap();
+        let mut combined_output = Vec::new();
+        combined
+            .print_dimacs_fragment(&mut combined_output)
+            .unwrap();
+        assert_eq!(combined_output, separate_output);
+    }
+
+    /// Returns whether every clause is satisfied with the variables in
+    /// `assignment` set, by bit index.
+    fn satisfied(clauses: &Clauses, assignment: u32) -> bool {
+        let value = |literal: &Literal| {
+            let set = assignment & (1 << literal.0.unsigned_abs()) != 0;
+            set == (literal.0 > 0)
+        };
+        clauses
+            .binary
+            .iter()
+            .all(|clause| clause.literals.iter().any(value))
+            && clauses
+                .long
+                .iter()
+                .all(|clause| clause.literals.iter().any(value))
+    }
+
+    #[test]
+    fn at_most_k_of() {
+        for n in 1..=4 {
+            for k in 0..=n {
+                let variables = Vec::from_iter(1..=n);
+                let mut next_aux = n + 1;
+                let mut clauses = Clauses::default();
+                clauses.emit_at_most_k_of(&variables, k, &mut || {
+                    next_aux += 1;
+                    next_aux - 1
+                });
+                let aux_count = next_aux - n - 1;
+                assert!(aux_count <= n.saturating_sub(1) * k);
+
+                // For each assignment of the counted variables, look for any
+                // assignment of the auxiliary variables that satisfies them.
+                for chosen in 0u32..1 << n {
+                    let assignment = chosen << 1;
+                    let satisfiable = (0u32..1 << aux_count)
+                        .any(|aux| satisfied(&clauses, assignment | aux << (n + 1)));
+                    assert_eq!(
+                        satisfiable,
+                        chosen.count_ones() as usize <= k,
+                        "n {n}, k {k}, chosen {chosen:b}",
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn validate_dimacs() {
+        let mut clauses = Clauses::default();
+        clauses.push_binary(Literal::negative(1usize), Literal::negative(2usize));
+        clauses.emit_at_least_one_of(&[1usize, 2, 3]);
+        clauses.push_unit(Literal::positive(3usize));
+        let mut output = Vec::new();
+        clauses.write_annotated_dimacs(&mut output).unwrap();
+        assert_eq!(
+            read_and_validate_dimacs(&output[..]).unwrap(),
+            (FullVariable::COUNT, 3),
+        );
+
+        let valid = "c comment\np cnf 3 2\n-1 -2 0\n1 2\n3 0\n";
+        assert_eq!(read_and_validate_dimacs(valid.as_bytes()).unwrap(), (3, 2));
+
+        for (corrupted, expected) in [
+            (
+                "p cnf 3 3\n-1 -2 0\n1 2 3 0\n",
+                DimacsError::ClauseCountMismatch {
+                    declared: 3,
+                    actual: 2,
+                },
+            ),
+            (
+                "p cnf 3 2\n-1 -2 0\n1 2 -4 0\n",
+                DimacsError::LiteralOutOfRange {
+                    line: 3,
+                    literal: -4,
+                },
+            ),
+            (
+                "p cnf 3 1\n-1 x 0\n",
+                DimacsError::InvalidLiteral { line: 2 },
+            ),
+            ("p cnf 3 1\n-1 -2\n", DimacsError::UnterminatedClause),
+            ("-1 -2 0\np cnf 3 1\n", DimacsError::MissingHeader),
+            ("p cnf 3\n-1 -2 0\n", DimacsError::InvalidHeader { line: 1 }),
+        ] {
+            let error = read_and_validate_dimacs(corrupted.as_bytes()).unwrap_err();
+            assert_eq!(error.to_string(), expected.to_string(), "{corrupted:?}");
+        }
+    }
+
+    #[test]
+    fn write_icnf() {
+        let mut clauses = Clauses::default();
+        clauses.push_binary(Literal::negative(1usize), Literal::negative(2usize));
+        clauses.emit_at_least_one_of(&[1usize, 2, 3]);
+        clauses.push_unit(Literal::positive(3usize));
+        clauses.push_assumptions(vec![Literal::positive(1usize), Literal::negative(3usize)]);
+
+        let mut output = Vec::new();
+        clauses.write_icnf(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        let mut lines = output.lines();
+        assert_eq!(lines.next(), Some("p inccnf"));
+        let (assumptions, body): (Vec<_>, Vec<_>) = lines.partition(|line| line.starts_with("a "));
+        assert_eq!(body.len(), clauses.len());
+        assert!(body.iter().all(|line| line.ends_with(" 0")));
+        assert_eq!(assumptions, ["a 1 -3 0"]);
+
+        // Appending a block later gives the same result as pushing it first.
+        let mut appended = output.into_bytes();
+        print_icnf_assumptions(&[Literal::negative(2usize)], &mut appended).unwrap();
+        clauses.push_assumptions(vec![Literal::negative(2usize)]);
+        let mut pushed = Vec::new();
+        clauses.write_icnf(&mut pushed).unwrap();
+        assert_eq!(appended, pushed);
+    }
+
+    #[test]
+    fn write_dimacs_starts_with_header() {
+        let mut clauses = Clauses::default();
+        clauses.push_binary(Literal::negative(1usize), Literal::negative(2usize));
+        clauses.emit_at_least_one_of(&[1usize, 2, 3]);
+        clauses.push_unit(Literal::positive(3usize));
+
+        let mut output = Vec::new();
+        clauses.write_dimacs(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        let mut lines = output.lines();
+        assert_eq!(
+            lines.next(),
+            Some(format!("p cnf {} 3", FullVariable::COUNT).as_str()),
+        );
+        assert_eq!(Vec::from_iter(lines), ["-1 -2 0", "1 2 3 0", "3 0"]);
+    }
+
+    #[test]
+    fn header_declares_instance_variables() {
+        let mut clauses = Clauses::for_instance::<4, 16>();
+        clauses.push_unit(Literal::positive(Variable::<4, 16>::for_tile_placement(
+            0_U4,
+            0_U4,
+            RotatedTile::ZERO,
+        )));
+        let mut output = Vec::new();
+        clauses.write_dimacs(&mut output).unwrap();
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .starts_with(&format!("p cnf {} 1\n", Variable::<4, 16>::COUNT)));
+        assert!(Variable::<4, 16>::COUNT < FullVariable::COUNT);
+    }
+
+    #[test]
+    fn write_smt2() {
+        let mut clauses = Clauses::default();
+        clauses.push_binary(Literal::negative(1usize), Literal::negative(2usize));
+        clauses.emit_at_least_one_of(&[1usize, 2, 4]);
+        clauses.push_unit(Literal::positive(4usize));
+
+        let mut output = Vec::new();
+        clauses.write_smt2(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!(
+            output
+                .lines()
+                .filter(|line| line.starts_with("(assert "))
+                .count(),
+            clauses.len(),
+        );
+        assert_eq!(
+            output
+                .lines()
+                .filter(|line| line.starts_with("(declare-const "))
+                .count(),
+            clauses.used_variable_count(),
+        );
+        assert_eq!(
+            Vec::from_iter(output.lines()),
+            [
+                "(set-logic QF_UF)",
+                "(declare-const v1 Bool)",
+                "(declare-const v2 Bool)",
+                "(declare-const v4 Bool)",
+                "(assert (or (not v1) (not v2)))",
+                "(assert (or v1 v2 v4))",
+                "(assert v4)",
+                "(check-sat)",
+                "(get-model)",
+            ],
+        );
+    }
+
+    /// Emits clauses shaped like `emit_problem`'s edge implications for one
+    /// row of cells.
+    fn emit_row(clauses: &mut Clauses, y: usize) {
+        for x in 0..64 {
+            let base = 1 + 4096 * (64 * y + x);
+            clauses.emit_exactly_one_of(&Vec::from_iter(base..base + 64));
+        }
+    }
+
+    #[test]
+    fn emit_regions_matches_sequential() {
+        let mut sequential = Clauses::default();
+        for y in 0..4 {
+            emit_row(&mut sequential, y);
+        }
+        let mut parallel = Clauses::default();
+        parallel.emit_regions(0..4, emit_row);
+
+        let mut sequential_output = Vec::new();
+        sequential.write_dimacs(&mut sequential_output).unwrap();
+        let mut parallel_output = Vec::new();
+        parallel.write_dimacs(&mut parallel_output).unwrap();
+        assert_eq!(parallel_output, sequential_output);
+    }
+
+    /// Reports the speedup of [`Clauses::emit_regions`] over a sequential
+    /// loop. Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_emit_regions() {
+        let start = Instant::now();
+        let mut sequential = Clauses::default();
+        for y in 0..64 {
+            emit_row(&mut sequential, y);
+        }
+        let sequential_time = start.elapsed();
+
+        let start = Instant::now();
+        let mut parallel = Clauses::default();
+        parallel.emit_regions(0..64, emit_row);
+        let parallel_time = start.elapsed();
+
+        assert_eq!(parallel.len(), sequential.len());
+        println!(
+            "sequential {sequential_time:?}, parallel {parallel_time:?}, speedup {:.2}x",
+            sequential_time.as_secs_f64() / parallel_time.as_secs_f64(),
+        );
+    }
+
+    #[test]
+    fn annotated_output_has_headers_and_same_clauses() {
+        let mut clauses = Clauses::default();
+        clauses.push_unit(Literal::positive(9usize));
+        clauses.begin_section("first section");
+        clauses.push_binary(Literal::negative(1usize), Literal::negative(2usize));
+        clauses.emit_at_least_one_of(&[1usize, 2, 3]);
+        clauses.begin_section("second section");
+        clauses.push_binary(Literal::negative(4usize), Literal::positive(5usize));
+        clauses.push_unit(Literal::negative(6usize));
+
+        let mut plain = Vec::new();
+        clauses.print_dimacs_fragment(&mut plain).unwrap();
+        let plain = String::from_utf8(plain).unwrap();
+        let mut annotated = Vec::new();
+        clauses
+            .print_annotated_dimacs_fragment(&mut annotated)
+            .unwrap();
+        let annotated = String::from_utf8(annotated).unwrap();
+
+        assert_eq!(
+            Vec::from_iter(annotated.lines().filter(|line| line.starts_with('c'))),
+            ["c first section", "c second section"],
+        );
+        assert_eq!(
+            annotated,
+            "9 0\n\
+             c first section\n\
+             -1 -2 0\n\
+             1 2 3 0\n\
+             c second section\n\
+             -4 5 0\n\
+             -6 0\n",
+        );
+
+        let mut plain_clauses = Vec::from_iter(plain.lines());
+        let mut annotated_clauses =
+            Vec::from_iter(annotated.lines().filter(|line| !line.starts_with('c')));
+        plain_clauses.sort();
+        annotated_clauses.sort();
+        assert_eq!(plain_clauses, annotated_clauses);
+    }
+
+    fn check_variable_encoding<const SIZE: usize, const TILES: usize>() {
         let mut variables = HashSet::new();
-        for x in 0..16 {
+        for x in 0..SIZE as u8 {
             let x = U4::new_masked(x);
-            for y in 0..16 {
+            for y in 0..SIZE as u8 {
                 let y = U4::new_masked(y);
-                for tile in Tile::values() {
-                    for rotation in Rotation::iter() {
-                        let rotated_tile = RotatedTile { tile, rotation };
-                        let kind = VariableKind::TilePlacement { x, y, rotated_tile };
-                        let variable = Variable::for_tile_placement(x, y, rotated_tile);
-                        assert_eq!(variable.kind(), kind);
-                        variables.insert(variable);
-                    }
+                for rotated_tile in RotatedTile::values().take(4 * TILES) {
+                    let kind = VariableKind::TilePlacement { x, y, rotated_tile };
+                    let variable = Variable::<SIZE, TILES>::for_tile_placement(x, y, rotated_tile);
+                    assert_eq!(variable.kind(), kind);
+                    variables.insert(variable);
                 }
                 for color in Color::iter() {
                     if color != Color::EXTERIOR {
-                        if x < 15_U4 {
+                        if (x.to_primitive() as usize) < SIZE - 1 {
                             let kind = VariableKind::RightEdgeColor { x, y, color };
-                            let variable = Variable::for_right_edge_color(x, y, color);
+                            let variable =
+                                Variable::<SIZE, TILES>::for_right_edge_color(x, y, color);
                             assert_eq!(variable.kind(), kind);
                             variables.insert(variable);
                         }
-                        if y < 15_U4 {
+                        if (y.to_primitive() as usize) < SIZE - 1 {
                             let kind = VariableKind::BottomEdgeColor { x, y, color };
-                            let variable = Variable::for_bottom_edge_color(x, y, color);
+                            let variable =
+                                Variable::<SIZE, TILES>::for_bottom_edge_color(x, y, color);
                             assert_eq!(variable.kind(), kind);
                             variables.insert(variable);
                         }
@@ -274,6 +1498,217 @@ mod tests {
                 }
             }
         }
-        assert_eq!(Variable::COUNT, variables.len());
+        assert_eq!(Variable::<SIZE, TILES>::COUNT, variables.len());
+        // Variables are numbered densely from one.
+        assert_eq!(
+            variables
+                .iter()
+                .map(|&variable| usize::from(variable))
+                .max(),
+            Some(Variable::<SIZE, TILES>::COUNT),
+        );
+    }
+
+    #[test]
+    fn variable_encoding_is_unique_and_round_trips() {
+        check_variable_encoding::<16, 256>();
+        check_variable_encoding::<4, 16>();
+        check_variable_encoding::<4, 256>();
+        check_variable_encoding::<2, 4>();
+    }
+
+    fn check_kind_ranges<const SIZE: usize, const TILES: usize>() {
+        let ranges = Variable::<SIZE, TILES>::kind_ranges();
+        let mut next = 1;
+        for (_, range) in &ranges {
+            assert_eq!(range.start, next);
+            next = range.end;
+        }
+        assert_eq!(next, Variable::<SIZE, TILES>::COUNT + 1);
+
+        for (name, range) in ranges {
+            for index in range {
+                let kind = Variable::<SIZE, TILES>::from(index).kind();
+                assert!(format!("{kind:?}").starts_with(name), "{index}: {kind:?}");
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "variable 0 is outside 1..=")]
+    fn kind_rejects_zero() {
+        FullVariable::from(0).kind();
+    }
+
+    #[test]
+    #[should_panic(expected = "is outside 1..=")]
+    fn kind_rejects_auxiliary_variables() {
+        FullVariable::from(FullVariable::COUNT + 1).kind();
+    }
+
+    #[test]
+    fn kind_ranges_cover_all_variables_without_overlap() {
+        check_kind_ranges::<16, 256>();
+        check_kind_ranges::<4, 16>();
+        check_kind_ranges::<4, 256>();
+        check_kind_ranges::<2, 4>();
+    }
+
+    #[test]
+    fn model_to_mosaic_round_trips() {
+        let mosaic = ArrayMosaic::<16, 16> {
+            tiles: std::array::from_fn(|y| {
+                std::array::from_fn(|x| {
+                    RotatedTile::values()
+                        .nth(4 * (16 * y + x) + (x + y) % 4)
+                        .unwrap()
+                })
+            }),
+        };
+        let placement = |x: usize, y: usize, rotated_tile| {
+            usize::from(FullVariable::for_tile_placement(
+                U4::new_masked(x as u8),
+                U4::new_masked(y as u8),
+                rotated_tile,
+            )) as isize
+        };
+        let mut literals = Vec::new();
+        for y in 0..16 {
+            for x in 0..16 {
+                literals.push(placement(x, y, mosaic.get(x, y)));
+                // Negative literals and edge colors are ignored.
+                literals.push(-placement(x, y, RotatedTile::values().last().unwrap()));
+            }
+        }
+        literals
+            .push(usize::from(FullVariable::for_right_edge_color(0_U4, 0_U4, Color::B)) as isize);
+        assert_eq!(model_to_mosaic(literals.iter().copied()), Ok(mosaic));
+
+        assert_eq!(
+            model_to_mosaic(literals[2..].iter().copied()),
+            Err(ModelError::MissingPlacement { x: 0_U4, y: 0_U4 }),
+        );
+        let duplicate = RotatedTile {
+            tile: Tile::from_primitive(255),
+            ..mosaic.get(3, 5)
+        };
+        literals.push(placement(3, 5, duplicate));
+        assert_eq!(
+            model_to_mosaic(literals.iter().copied()),
+            Err(ModelError::ConflictingPlacements { x: 3_U4, y: 5_U4 }),
+        );
+    }
+
+    #[test]
+    fn reconstruct_board_checks_tiles_and_edges() {
+        // Every tile once, in some rotation. Neighbors don't match, but without
+        // edge color literals nothing checks them.
+        let mosaic = ArrayMosaic::<16, 16> {
+            tiles: std::array::from_fn(|y| {
+                std::array::from_fn(|x| {
+                    RotatedTile::values()
+                        .nth(4 * (16 * y + x) + (x + y) % 4)
+                        .unwrap()
+                })
+            }),
+        };
+        let placement = |x: usize, y: usize, rotated_tile| {
+            usize::from(FullVariable::for_tile_placement(
+                U4::new_masked(x as u8),
+                U4::new_masked(y as u8),
+                rotated_tile,
+            )) as isize
+        };
+        let mut literals = Vec::new();
+        for y in 0..16 {
+            for x in 0..16 {
+                literals.push(placement(x, y, mosaic.get(x, y)));
+            }
+        }
+        let (board, problems) = reconstruct_board(literals.iter().copied());
+        assert_eq!(board.to_vec(), to_bucas_board_edges(&mosaic).into_bytes());
+        assert_eq!(problems, []);
+
+        // The rest of the board is still built around a missing tile.
+        let (board, problems) = reconstruct_board(literals[1..].iter().copied());
+        assert_eq!(&board[..4], b"____");
+        assert_eq!(board[4..], to_bucas_board_edges(&mosaic).as_bytes()[4..]);
+        assert_eq!(
+            problems,
+            [ReconstructError::TileUnused {
+                tile: mosaic.get(0, 0).tile,
+            }],
+        );
+
+        let mut used_twice = literals.clone();
+        used_twice[1] = placement(1, 0, mosaic.get(0, 0));
+        assert_eq!(
+            reconstruct_board(used_twice).1,
+            [
+                ReconstructError::TileUsedTwice {
+                    tile: mosaic.get(0, 0).tile,
+                },
+                ReconstructError::TileUnused {
+                    tile: mosaic.get(1, 0).tile,
+                },
+            ],
+        );
+
+        // An interior color matching neither side of the edge.
+        let color = Color::iter()
+            .find(|&color| {
+                color.is_valid_non_border_color()
+                    && color != mosaic.get(0, 0).color(Side::Right)
+                    && color != mosaic.get(1, 0).color(Side::Left)
+            })
+            .unwrap();
+        literals.push(usize::from(FullVariable::for_right_edge_color(0_U4, 0_U4, color)) as isize);
+        let problems = reconstruct_board(literals).1;
+        assert_eq!(
+            problems,
+            [
+                ReconstructError::EdgeConflict {
+                    x: 0_U4,
+                    y: 0_U4,
+                    side: Side::Right,
+                },
+                ReconstructError::EdgeConflict {
+                    x: 1_U4,
+                    y: 0_U4,
+                    side: Side::Left,
+                },
+            ],
+        );
+        assert_eq!(problems[0].to_string(), "conflict at (0, 0) Right edge");
+    }
+
+    #[test]
+    fn models_with_seam_counts_skip_auxiliary_variables() {
+        let mosaic = ArrayMosaic::<16, 16> {
+            tiles: std::array::from_fn(|y| {
+                std::array::from_fn(|x| RotatedTile::values().nth(4 * (16 * y + x)).unwrap())
+            }),
+        };
+        let mut literals = Vec::new();
+        for y in 0..16 {
+            for x in 0..16 {
+                literals.push(usize::from(FullVariable::for_tile_placement(
+                    U4::new_masked(x as u8),
+                    U4::new_masked(y as u8),
+                    mosaic.get(x, y),
+                )) as isize);
+            }
+        }
+        // Set every counter variable, as a model of a problem emitted with
+        // --seam-counts might.
+        let mut clauses = Clauses::default();
+        emit_seam_color_counts(&mut clauses);
+        assert!(clauses.variable_count() > FullVariable::COUNT);
+        literals.extend((FullVariable::COUNT + 1..=clauses.variable_count()).map(|i| i as isize));
+
+        assert_eq!(model_to_mosaic(literals.iter().copied()), Ok(mosaic));
+        let (board, problems) = reconstruct_board(literals);
+        assert_eq!(board.to_vec(), to_bucas_board_edges(&mosaic).into_bytes());
+        assert_eq!(problems, []);
     }
 }
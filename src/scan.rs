@@ -0,0 +1,121 @@
+use bitint::prelude::*;
+
+use crate::Coord;
+
+/// The dimensions of a board, at most 16 by 16.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BoardDims {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl BoardDims {
+    pub const FULL: Self = Self {
+        width: 16,
+        height: 16,
+    };
+}
+
+/// An order in which to visit the cells of a board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScanOrder {
+    /// Left to right within each row, rows from top to bottom.
+    RowMajor,
+    /// Top to bottom within each column, columns from left to right.
+    ColumnMajor,
+    /// Clockwise around the perimeter from the top-left corner, then around
+    /// each successive inner ring. This visits the frame first.
+    Spiral,
+}
+
+/// Returns every cell of a board exactly once, in the given order.
+///
+/// # Panics
+///
+/// Panics if either dimension exceeds 16.
+pub fn scan_order(dims: BoardDims, order: ScanOrder) -> impl Iterator<Item = Coord> {
+    assert!(dims.width <= 16 && dims.height <= 16);
+
+    let mut cells = Vec::with_capacity(dims.width * dims.height);
+    match order {
+        ScanOrder::RowMajor => {
+            for y in 0..dims.height {
+                for x in 0..dims.width {
+                    cells.push((x, y));
+                }
+            }
+        }
+        ScanOrder::ColumnMajor => {
+            for x in 0..dims.width {
+                for y in 0..dims.height {
+                    cells.push((x, y));
+                }
+            }
+        }
+        ScanOrder::Spiral => {
+            let (mut left, mut top) = (0, 0);
+            let (mut right, mut bottom) = (dims.width, dims.height);
+            while left < right && top < bottom {
+                cells.extend((left..right).map(|x| (x, top)));
+                cells.extend((top + 1..bottom).map(|y| (right - 1, y)));
+                if top + 1 < bottom {
+                    cells.extend((left..right - 1).rev().map(|x| (x, bottom - 1)));
+                }
+                if left + 1 < right {
+                    cells.extend((top + 1..bottom - 1).rev().map(|y| (left, y)));
+                }
+                left += 1;
+                top += 1;
+                right -= 1;
+                bottom -= 1;
+            }
+        }
+    }
+    cells
+        .into_iter()
+        .map(|(x, y)| (U4::new_masked(x as u8), U4::new_masked(y as u8)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::{scan_order, BoardDims, ScanOrder};
+
+    #[test]
+    fn visits_every_cell_once() {
+        for (width, height) in [(16, 16), (5, 3), (3, 5), (1, 4), (4, 1), (1, 1)] {
+            let dims = BoardDims { width, height };
+            for order in [
+                ScanOrder::RowMajor,
+                ScanOrder::ColumnMajor,
+                ScanOrder::Spiral,
+            ] {
+                let cells = Vec::from_iter(scan_order(dims, order));
+                assert_eq!(cells.len(), width * height, "{dims:?} {order:?}");
+                assert_eq!(
+                    BTreeSet::from_iter(cells.iter().copied()).len(),
+                    width * height,
+                    "{dims:?} {order:?}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn spiral_starts_at_perimeter() {
+        let cells = Vec::from_iter(
+            scan_order(BoardDims::FULL, ScanOrder::Spiral)
+                .map(|(x, y)| (x.to_primitive(), y.to_primitive())),
+        );
+        assert_eq!(&cells[..3], [(0, 0), (1, 0), (2, 0)]);
+        assert_eq!(cells[15], (15, 0));
+        assert_eq!(cells[30], (15, 15));
+        assert_eq!(cells[45], (0, 15));
+        assert_eq!(cells[59], (0, 1));
+        for &(x, y) in &cells[..60] {
+            assert!(x == 0 || y == 0 || x == 15 || y == 15);
+        }
+        assert_eq!(cells[60], (1, 1));
+    }
+}
@@ -0,0 +1,217 @@
+use std::io::{self, Write};
+use std::marker::PhantomData;
+use std::mem::take;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use bitint::prelude::*;
+
+use crate::mosaic::RectangularMosaic;
+use crate::sat::FullVariable;
+use crate::set::builder::{SetBuilder, ShardBuilder};
+
+/// How many bytes a shard buffers before writing them out.
+const SHARD_BUFFER_BYTES: usize = 1 << 16;
+
+/// Writes each inserted mosaic as a block of DIMACS unit clauses, one per
+/// [tile placement](FullVariable::for_tile_placement), for a downstream solver
+/// to take as assumptions.
+///
+/// The mosaic is placed on the board with its top-left corner at the origin
+/// given to [`Self::new`]. Each block starts with a `c mosaic` comment line:
+///
+/// ```text
+/// c mosaic
+/// 1234 0
+/// 5678 0
+/// ```
+///
+/// Shards buffer whole blocks, so blocks are never interleaved, but their order
+/// follows the order shards flush in.
+pub struct AssumptionEmittingSetBuilder<const W: usize, const H: usize, T, O> {
+    output: Arc<Mutex<Output<O>>>,
+    x0: usize,
+    y0: usize,
+    tx: Sender<()>,
+    rx: Receiver<()>,
+    _phantom_t: PhantomData<fn(T)>,
+}
+
+struct Output<O> {
+    w: O,
+    /// The first write error, for [`SetBuilder::finish`] to return.
+    error: Option<io::Error>,
+}
+
+impl<O: Write> Output<O> {
+    fn write(&mut self, buffer: &[u8]) {
+        if self.error.is_none() {
+            if let Err(e) = self.w.write_all(buffer) {
+                self.error = Some(e);
+            }
+        }
+    }
+}
+
+impl<const W: usize, const H: usize, T, O: Write + Send> AssumptionEmittingSetBuilder<W, H, T, O> {
+    /// # Panics
+    ///
+    /// Panics if a `W` by `H` mosaic at (`x0`, `y0`) doesn't fit on the board.
+    pub fn new(w: O, x0: U4, y0: U4) -> Self {
+        let (x0, y0) = (x0.to_primitive() as usize, y0.to_primitive() as usize);
+        assert!(x0 + W <= 16 && y0 + H <= 16);
+        let (tx, rx) = channel();
+        Self {
+            output: Arc::new(Mutex::new(Output { w, error: None })),
+            x0,
+            y0,
+            tx,
+            rx,
+            _phantom_t: PhantomData,
+        }
+    }
+}
+
+impl<const W: usize, const H: usize, T: RectangularMosaic<W, H>, O: Write + Send> SetBuilder
+    for AssumptionEmittingSetBuilder<W, H, T, O>
+{
+    type Item = T;
+    type Shard = AssumptionEmittingShardBuilder<W, H, T, O>;
+    /// The writer, or the first error writing to it.
+    type Result = io::Result<O>;
+
+    fn new_shard(&mut self) -> AssumptionEmittingShardBuilder<W, H, T, O> {
+        AssumptionEmittingShardBuilder {
+            output: Some(Arc::clone(&self.output)),
+            x0: self.x0,
+            y0: self.y0,
+            tx: Some(self.tx.clone()),
+            buffer: Vec::new(),
+            _phantom_t: PhantomData,
+        }
+    }
+
+    fn finish(self) -> Self::Result {
+        // Wait for every shard to be dropped and flushed.
+        drop(self.tx);
+        while self.rx.recv().is_ok() {}
+
+        let Ok(output) = Arc::try_unwrap(self.output) else {
+            unreachable!("every shard has been dropped");
+        };
+        let mut output = output.into_inner().unwrap();
+        match output.error {
+            Some(e) => Err(e),
+            None => {
+                output.w.flush()?;
+                Ok(output.w)
+            }
+        }
+    }
+}
+
+pub struct AssumptionEmittingShardBuilder<const W: usize, const H: usize, T, O: Write> {
+    /// Released before signalling [`SetBuilder::finish`], which needs the
+    /// only reference.
+    output: Option<Arc<Mutex<Output<O>>>>,
+    x0: usize,
+    y0: usize,
+    tx: Option<Sender<()>>,
+    buffer: Vec<u8>,
+    _phantom_t: PhantomData<fn(T)>,
+}
+
+impl<const W: usize, const H: usize, T, O: Write> AssumptionEmittingShardBuilder<W, H, T, O> {
+    fn flush(&mut self) {
+        if !self.buffer.is_empty() {
+            let output = self.output.as_ref().unwrap();
+            output.lock().unwrap().write(&take(&mut self.buffer));
+        }
+    }
+}
+
+impl<const W: usize, const H: usize, T: RectangularMosaic<W, H>, O: Write + Send> ShardBuilder
+    for AssumptionEmittingShardBuilder<W, H, T, O>
+{
+    type Item = T;
+
+    fn insert(&mut self, item: Self::Item) {
+        writeln!(self.buffer, "c mosaic").unwrap();
+        for y in 0..H {
+            for x in 0..W {
+                let variable = FullVariable::for_tile_placement(
+                    U4::new_masked((self.x0 + x) as u8),
+                    U4::new_masked((self.y0 + y) as u8),
+                    item.get(x, y),
+                );
+                writeln!(self.buffer, "{} 0", usize::from(variable)).unwrap();
+            }
+        }
+        if self.buffer.len() >= SHARD_BUFFER_BYTES {
+            self.flush();
+        }
+    }
+
+    fn finish(self) {}
+}
+
+impl<const W: usize, const H: usize, T, O: Write> Clone
+    for AssumptionEmittingShardBuilder<W, H, T, O>
+{
+    fn clone(&self) -> Self {
+        Self {
+            output: self.output.clone(),
+            x0: self.x0,
+            y0: self.y0,
+            tx: self.tx.clone(),
+            buffer: Vec::new(),
+            _phantom_t: PhantomData,
+        }
+    }
+}
+
+impl<const W: usize, const H: usize, T, O: Write> Drop
+    for AssumptionEmittingShardBuilder<W, H, T, O>
+{
+    fn drop(&mut self) {
+        if let Some(tx) = self.tx.take() {
+            self.flush();
+            self.output = None;
+            tx.send(()).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitint::prelude::*;
+
+    use crate::sat::FullVariable;
+    use crate::set::builder::{SetBuilder, ShardBuilder};
+    use crate::{RotatedTile, Rotation, Tile};
+
+    use super::AssumptionEmittingSetBuilder;
+
+    #[test]
+    fn emits_tile_placement() {
+        let rotated_tile = RotatedTile {
+            tile: Tile::from_primitive(135),
+            rotation: Rotation::HalfTurn,
+        };
+        let mut builder = AssumptionEmittingSetBuilder::<1, 1, _, _>::new(
+            Vec::new(),
+            U4::new_masked(7),
+            U4::new_masked(8),
+        );
+        let mut shard = builder.new_shard();
+        shard.insert(mosaic![[@rotated_tile]]);
+        drop(shard);
+
+        let variable =
+            FullVariable::for_tile_placement(U4::new_masked(7), U4::new_masked(8), rotated_tile);
+        assert_eq!(
+            String::from_utf8(builder.finish().unwrap()).unwrap(),
+            format!("c mosaic\n{} 0\n", usize::from(variable)),
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+
+use crate::set::builder::{SetBuilder, ShardBuilder};
+
+/// The result of a [`BloomCountingSetBuilder`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BloomCount {
+    /// Every inserted item, counting duplicates.
+    pub items: usize,
+    /// The items that weren't already in the filter when inserted. Items that
+    /// collide with earlier ones aren't counted, which undercounts. Shards
+    /// inserting the same item at once can each set a different bit first and
+    /// both count it, which overcounts.
+    pub approximate_distinct: usize,
+    /// The distinct count estimated from the fraction of filter bits set,
+    /// which corrects for collisions. This is infinite once every bit is set.
+    pub estimated_distinct: f64,
+}
+
+/// Counts distinct items approximately in a fixed amount of memory, by
+/// inserting each item's hash into a Bloom filter shared by every shard.
+///
+/// Unlike an in-memory set, memory doesn't grow with the item count, but
+/// accuracy falls as the filter fills. The filter should have several times
+/// more bits than the expected distinct count.
+pub struct BloomCountingSetBuilder<T> {
+    tx: Sender<(usize, usize)>,
+    rx: Receiver<(usize, usize)>,
+    filter: Arc<BloomFilter>,
+    _phantom_t: PhantomData<fn(T)>,
+}
+
+impl<T> BloomCountingSetBuilder<T> {
+    /// Returns a builder whose filter has `bits` bits, rounded up to a
+    /// multiple of 64, and sets `hashes` bits per item.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` or `hashes` is zero.
+    pub fn new(bits: usize, hashes: usize) -> Self {
+        assert!(bits > 0 && hashes > 0);
+        let (tx, rx) = channel();
+        Self {
+            tx,
+            rx,
+            filter: Arc::new(BloomFilter {
+                words: (0..bits.div_ceil(64)).map(|_| AtomicU64::new(0)).collect(),
+                hashes,
+            }),
+            _phantom_t: PhantomData,
+        }
+    }
+}
+
+impl<T: Hash> SetBuilder for BloomCountingSetBuilder<T> {
+    type Item = T;
+    type Shard = BloomCountingShardBuilder<T>;
+    type Result = BloomCount;
+
+    fn new_shard(&mut self) -> BloomCountingShardBuilder<T> {
+        BloomCountingShardBuilder {
+            tx: Some(self.tx.clone()),
+            filter: Arc::clone(&self.filter),
+            items: 0,
+            novel: 0,
+            _phantom_t: PhantomData,
+        }
+    }
+
+    fn finish(self) -> Self::Result {
+        drop(self.tx);
+        let mut items = 0;
+        let mut approximate_distinct = 0;
+        while let Ok((shard_items, shard_novel)) = self.rx.recv() {
+            items += shard_items;
+            approximate_distinct += shard_novel;
+        }
+        BloomCount {
+            items,
+            approximate_distinct,
+            estimated_distinct: self.filter.estimate_distinct(),
+        }
+    }
+}
+
+struct BloomFilter {
+    words: Vec<AtomicU64>,
+    hashes: usize,
+}
+
+impl BloomFilter {
+    fn bits(&self) -> usize {
+        64 * self.words.len()
+    }
+
+    /// Sets the item's bits, returning whether any of them was clear.
+    fn insert(&self, item: &impl Hash) -> bool {
+        let mut hasher = DefaultHasher::new();
+        item.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        // Derive every index from two halves of one hash, following Kirsch and
+        // Mitzenmacher. The step is odd so it never stalls.
+        let (start, step) = (hash & 0xffff_ffff, (hash >> 32) | 1);
+        let mut novel = false;
+        for i in 0..self.hashes as u64 {
+            let bit = (start.wrapping_add(i.wrapping_mul(step)) % self.bits() as u64) as usize;
+            let mask = 1 << (bit % 64);
+            if self.words[bit / 64].fetch_or(mask, Ordering::Relaxed) & mask == 0 {
+                novel = true;
+            }
+        }
+        novel
+    }
+
+    /// Estimates the number of distinct items inserted from the number of set
+    /// bits, following Swamidass and Baldi.
+    fn estimate_distinct(&self) -> f64 {
+        let set_bits: u64 = self
+            .words
+            .iter()
+            .map(|word| u64::from(word.load(Ordering::Relaxed).count_ones()))
+            .sum();
+        let bits = self.bits() as f64;
+        -bits / self.hashes as f64 * (1.0 - set_bits as f64 / bits).ln()
+    }
+}
+
+pub struct BloomCountingShardBuilder<T> {
+    tx: Option<Sender<(usize, usize)>>,
+    filter: Arc<BloomFilter>,
+    items: usize,
+    novel: usize,
+    _phantom_t: PhantomData<fn(T)>,
+}
+
+impl<T: Hash> ShardBuilder for BloomCountingShardBuilder<T> {
+    type Item = T;
+
+    fn insert(&mut self, item: Self::Item) {
+        self.items += 1;
+        if self.filter.insert(&item) {
+            self.novel += 1;
+        }
+    }
+
+    fn finish(self) {}
+}
+
+impl<T> Clone for BloomCountingShardBuilder<T> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            filter: Arc::clone(&self.filter),
+            items: 0,
+            novel: 0,
+            _phantom_t: PhantomData,
+        }
+    }
+}
+
+impl<T> Drop for BloomCountingShardBuilder<T> {
+    fn drop(&mut self) {
+        if let Some(tx) = self.tx.take() {
+            tx.send((self.items, self.novel)).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::set::builder::{SetBuilder, ShardBuilder};
+
+    use super::BloomCountingSetBuilder;
+
+    #[test]
+    fn estimates_distinct_count() {
+        let mut builder = BloomCountingSetBuilder::new(1 << 16, 4);
+        let mut shard = builder.new_shard();
+        let mut other_shard = shard.clone();
+        for item in 0..1000u32 {
+            shard.insert(item);
+            other_shard.insert(item);
+        }
+        drop(shard);
+        drop(other_shard);
+
+        let count = builder.finish();
+        assert_eq!(count.items, 2000);
+        assert!(
+            (990..=1000).contains(&count.approximate_distinct),
+            "{count:?}",
+        );
+        assert!(
+            (count.estimated_distinct - 1000.0).abs() < 30.0,
+            "{count:?}",
+        );
+    }
+}
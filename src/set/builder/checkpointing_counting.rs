@@ -0,0 +1,233 @@
+use std::fs;
+use std::io;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use crate::set::builder::{SetBuilder, ShardBuilder};
+
+/// How many inserts a shard accumulates before adding them to the running
+/// count and writing a checkpoint.
+pub const CHECKPOINT_INTERVAL: usize = 1 << 24;
+
+/// Counts the inserted mosaics like
+/// [`CountingSetBuilder`](super::counting::CountingSetBuilder), periodically
+/// writing the running count to a file so a crashed run can report how far it
+/// got.
+///
+/// The checkpoint file is replaced atomically and looks like this:
+///
+/// ```text
+/// count 123456789
+/// finished_shards 17
+/// complete false
+/// ```
+///
+/// `finished_shards` counts dropped shards, which only marks progress: the
+/// build functions hand shards to rayon, which clones and splits them as it
+/// likes. `complete` becomes `true` once [`SetBuilder::finish`] writes the
+/// final count.
+///
+/// Only checkpointing is implemented. Each shard enumerates its share of the
+/// outer edge groups in a deterministic order, but which groups land in which
+/// shard is not deterministic. Resuming would require recording completed edge
+/// groups by their position in
+/// [`iter_by_edge`](crate::set::square::SquareMosaicSet::iter_by_edge) order
+/// alongside their counts, and a build function that skips them.
+pub struct CheckpointingCountingSetBuilder<T> {
+    checkpoint: Arc<Checkpoint>,
+    interval: usize,
+    tx: Sender<()>,
+    rx: Receiver<()>,
+    _phantom_t: PhantomData<fn(T)>,
+}
+
+impl<T> CheckpointingCountingSetBuilder<T> {
+    /// Checkpoints to `path` every [`CHECKPOINT_INTERVAL`] inserts per shard.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self::with_interval(path, CHECKPOINT_INTERVAL)
+    }
+
+    /// Checkpoints to `path` every `interval` inserts per shard.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero.
+    pub fn with_interval(path: impl Into<PathBuf>, interval: usize) -> Self {
+        assert!(interval > 0);
+        let (tx, rx) = channel();
+        Self {
+            checkpoint: Arc::new(Checkpoint {
+                path: path.into(),
+                count: AtomicUsize::new(0),
+                finished_shards: AtomicUsize::new(0),
+                error: Mutex::new(None),
+            }),
+            interval,
+            tx,
+            rx,
+            _phantom_t: PhantomData,
+        }
+    }
+}
+
+impl<T> SetBuilder for CheckpointingCountingSetBuilder<T> {
+    type Item = T;
+    type Shard = CheckpointingCountingShardBuilder<T>;
+    /// The final count, or the first error writing a checkpoint.
+    type Result = io::Result<usize>;
+
+    fn new_shard(&mut self) -> CheckpointingCountingShardBuilder<T> {
+        CheckpointingCountingShardBuilder {
+            checkpoint: Arc::clone(&self.checkpoint),
+            interval: self.interval,
+            tx: Some(self.tx.clone()),
+            pending: 0,
+            _phantom_t: PhantomData,
+        }
+    }
+
+    fn finish(self) -> Self::Result {
+        // Wait for every shard to be dropped.
+        drop(self.tx);
+        while self.rx.recv().is_ok() {}
+
+        self.checkpoint.write(true);
+        match self.checkpoint.error.lock().unwrap().take() {
+            Some(e) => Err(e),
+            None => Ok(self.checkpoint.count.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+struct Checkpoint {
+    path: PathBuf,
+    count: AtomicUsize,
+    finished_shards: AtomicUsize,
+    /// Serializes writes and holds the first write error for
+    /// [`SetBuilder::finish`] to return.
+    error: Mutex<Option<io::Error>>,
+}
+
+impl Checkpoint {
+    fn write(&self, complete: bool) {
+        let mut error = self.error.lock().unwrap();
+        if error.is_some() {
+            return;
+        }
+        let contents = format!(
+            "count {}\nfinished_shards {}\ncomplete {complete}\n",
+            self.count.load(Ordering::Relaxed),
+            self.finished_shards.load(Ordering::Relaxed),
+        );
+        if let Err(e) = write_atomically(&self.path, contents.as_bytes()) {
+            *error = Some(e);
+        }
+    }
+}
+
+/// Writes to a sibling file and renames it over `path`, so a crash never
+/// leaves a partial checkpoint.
+fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, path)
+}
+
+pub struct CheckpointingCountingShardBuilder<T> {
+    checkpoint: Arc<Checkpoint>,
+    interval: usize,
+    tx: Option<Sender<()>>,
+    pending: usize,
+    _phantom_t: PhantomData<fn(T)>,
+}
+
+impl<T> ShardBuilder for CheckpointingCountingShardBuilder<T> {
+    type Item = T;
+
+    fn insert(&mut self, _item: Self::Item) {
+        self.pending += 1;
+        if self.pending == self.interval {
+            self.checkpoint
+                .count
+                .fetch_add(self.pending, Ordering::Relaxed);
+            self.pending = 0;
+            self.checkpoint.write(false);
+        }
+    }
+
+    fn finish(self) {}
+}
+
+impl<T> Clone for CheckpointingCountingShardBuilder<T> {
+    fn clone(&self) -> Self {
+        Self {
+            checkpoint: Arc::clone(&self.checkpoint),
+            interval: self.interval,
+            tx: self.tx.clone(),
+            pending: 0,
+            _phantom_t: PhantomData,
+        }
+    }
+}
+
+impl<T> Drop for CheckpointingCountingShardBuilder<T> {
+    fn drop(&mut self) {
+        if let Some(tx) = self.tx.take() {
+            self.checkpoint
+                .count
+                .fetch_add(self.pending, Ordering::Relaxed);
+            self.checkpoint
+                .finished_shards
+                .fetch_add(1, Ordering::Relaxed);
+            self.checkpoint.write(false);
+            tx.send(()).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::set::builder::{SetBuilder, ShardBuilder};
+
+    use super::CheckpointingCountingSetBuilder;
+
+    #[test]
+    fn writes_checkpoints() {
+        let path = std::env::temp_dir().join(format!(
+            "eternity-ii-checkpoint-test-{}",
+            std::process::id(),
+        ));
+        let mut builder = CheckpointingCountingSetBuilder::with_interval(&path, 2);
+        let mut shard = builder.new_shard();
+        let mut other_shard = shard.clone();
+
+        for i in 0..5 {
+            shard.insert(i);
+        }
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "count 4\nfinished_shards 0\ncomplete false\n",
+        );
+
+        other_shard.insert(5);
+        drop(shard);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "count 5\nfinished_shards 1\ncomplete false\n",
+        );
+
+        drop(other_shard);
+        assert_eq!(builder.finish().unwrap(), 6);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "count 6\nfinished_shards 2\ncomplete true\n",
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
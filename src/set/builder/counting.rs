@@ -20,6 +20,12 @@ impl<T> CountingSetBuilder<T> {
     }
 }
 
+impl<T> Default for CountingSetBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> SetBuilder for CountingSetBuilder<T> {
     type Item = T;
     type Shard = CountingShardBuilder<T>;
@@ -1,79 +1,247 @@
+use std::marker::PhantomData;
 use std::sync::mpsc::{channel, Receiver, Sender};
 
 use crate::set::builder::{SetBuilder, ShardBuilder};
 
-pub struct CountingSamplingSetBuilder<T> {
-    tx: Sender<(usize, Option<T>)>,
-    rx: Receiver<(usize, Option<T>)>,
+/// Holds the items sampled by a [`CountingSamplingSetBuilder`].
+///
+/// `Option<T>` holds at most one sample and `Vec<T>` holds as many as the
+/// builder's capacity.
+pub trait Samples<T>: Default + Send + IntoIterator<Item = T> {
+    /// Returns the samples in ascending order.
+    fn as_slice(&self) -> &[T];
+
+    /// Adds `item` if it's among the `capacity` smallest items seen so far.
+    fn keep_smallest(&mut self, item: T, capacity: usize);
 }
 
-impl<T> CountingSamplingSetBuilder<T> {
+impl<T: Ord + Send> Samples<T> for Option<T> {
+    fn as_slice(&self) -> &[T] {
+        match self {
+            Some(item) => std::slice::from_ref(item),
+            None => &[],
+        }
+    }
+
+    fn keep_smallest(&mut self, item: T, capacity: usize) {
+        assert!(capacity <= 1);
+        let smaller = match self {
+            Some(sample) => item < *sample,
+            None => true,
+        };
+        if capacity == 1 && smaller {
+            *self = Some(item);
+        }
+    }
+}
+
+impl<T: Ord + Send> Samples<T> for Vec<T> {
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    fn keep_smallest(&mut self, item: T, capacity: usize) {
+        // Once full, most items are larger than every sample.
+        if self.len() == capacity && self.last().map_or(true, |largest| item >= *largest) {
+            return;
+        }
+        let index = self.partition_point(|sample| *sample < item);
+        if index < capacity {
+            if self.len() == capacity {
+                self.pop();
+            }
+            self.insert(index, item);
+        }
+    }
+}
+
+/// Counts the inserted items and keeps the smallest few as samples.
+///
+/// Items come from a set, so the samples are distinct. Keeping the smallest
+/// rather than the first makes the samples independent of how the build split
+/// its work across shards, so repeated runs report the same samples.
+pub struct CountingSamplingSetBuilder<T, S: Samples<T> = Option<T>> {
+    tx: Sender<(usize, S)>,
+    rx: Receiver<(usize, S)>,
+    capacity: usize,
+    _phantom_t: PhantomData<fn(T)>,
+}
+
+impl<T: Ord + Send> CountingSamplingSetBuilder<T> {
     pub fn new() -> Self {
+        Self::with_samples_capacity(1)
+    }
+}
+
+impl<T: Ord + Send> CountingSamplingSetBuilder<T, Vec<T>> {
+    /// Returns a builder that keeps up to `capacity` samples.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_samples_capacity(capacity)
+    }
+}
+
+impl<T, S: Samples<T>> CountingSamplingSetBuilder<T, S> {
+    fn with_samples_capacity(capacity: usize) -> Self {
         let (tx, rx) = channel();
-        Self { tx, rx }
+        Self {
+            tx,
+            rx,
+            capacity,
+            _phantom_t: PhantomData,
+        }
+    }
+}
+
+impl<T: Ord + Send> Default for CountingSamplingSetBuilder<T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-impl<T: Send> SetBuilder for CountingSamplingSetBuilder<T> {
+impl<T: Send, S: Samples<T>> SetBuilder for CountingSamplingSetBuilder<T, S> {
     type Item = T;
-    type Shard = CountingSamplingShardBuilder<T>;
-    type Result = (usize, Option<T>);
+    type Shard = CountingSamplingShardBuilder<T, S>;
+    type Result = (usize, S);
 
-    fn new_shard(&mut self) -> CountingSamplingShardBuilder<T> {
+    fn new_shard(&mut self) -> CountingSamplingShardBuilder<T, S> {
         CountingSamplingShardBuilder {
             tx: Some(self.tx.clone()),
             count: 0,
-            sample: None,
+            capacity: self.capacity,
+            samples: S::default(),
+            _phantom_t: PhantomData,
         }
     }
 
     fn finish(self) -> Self::Result {
         drop(self.tx);
         let mut count = 0;
-        let mut value = None;
-        while let Ok((shard_count, shard_value)) = self.rx.recv() {
+        let mut samples = S::default();
+        while let Ok((shard_count, shard_samples)) = self.rx.recv() {
             count += shard_count;
-            if value.is_none() {
-                value = shard_value;
+            for sample in shard_samples {
+                samples.keep_smallest(sample, self.capacity);
             }
         }
-        (count, value)
+        (count, samples)
     }
 }
 
-pub struct CountingSamplingShardBuilder<T: Send> {
-    tx: Option<Sender<(usize, Option<T>)>>,
+pub struct CountingSamplingShardBuilder<T: Send, S: Samples<T> = Option<T>> {
+    tx: Option<Sender<(usize, S)>>,
     count: usize,
-    sample: Option<T>,
+    capacity: usize,
+    samples: S,
+    _phantom_t: PhantomData<fn(T)>,
 }
 
-impl<T: Send> ShardBuilder for CountingSamplingShardBuilder<T> {
+impl<T: Send, S: Samples<T>> ShardBuilder for CountingSamplingShardBuilder<T, S> {
     type Item = T;
 
     fn insert(&mut self, item: Self::Item) {
         self.count += 1;
-        if self.sample.is_none() {
-            self.sample = Some(item);
-        }
+        self.samples.keep_smallest(item, self.capacity);
     }
 
     fn finish(self) {}
 }
 
-impl<T: Send> Clone for CountingSamplingShardBuilder<T> {
+impl<T: Send, S: Samples<T>> Clone for CountingSamplingShardBuilder<T, S> {
     fn clone(&self) -> Self {
         Self {
             tx: self.tx.clone(),
             count: 0,
-            sample: None,
+            capacity: self.capacity,
+            samples: S::default(),
+            _phantom_t: PhantomData,
         }
     }
 }
 
-impl<T: Send> Drop for CountingSamplingShardBuilder<T> {
+impl<T: Send, S: Samples<T>> Drop for CountingSamplingShardBuilder<T, S> {
     fn drop(&mut self) {
         if let Some(tx) = self.tx.take() {
-            tx.send((self.count, self.sample.take())).unwrap();
+            tx.send((self.count, std::mem::take(&mut self.samples)))
+                .unwrap();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::set::builder::{SetBuilder, ShardBuilder};
+
+    use super::CountingSamplingSetBuilder;
+
+    #[test]
+    fn with_capacity_keeps_up_to_capacity_samples() {
+        let mut builder = CountingSamplingSetBuilder::with_capacity(4);
+        let mut shard = builder.new_shard();
+        let mut other_shard = shard.clone();
+        for item in 0..3 {
+            shard.insert(item);
+            other_shard.insert(10 + item);
+        }
+        drop(shard);
+        drop(other_shard);
+
+        let (count, mut samples) = builder.finish();
+        assert_eq!(count, 6);
+        assert_eq!(samples.len(), 4);
+        samples.sort();
+        samples.dedup();
+        assert_eq!(samples.len(), 4);
+    }
+
+    #[test]
+    fn new_keeps_one_sample() {
+        let mut builder = CountingSamplingSetBuilder::new();
+        let mut shard = builder.new_shard();
+        shard.insert(1);
+        shard.insert(2);
+        drop(shard);
+
+        assert_eq!(builder.finish(), (2, Some(1)));
+    }
+
+    #[test]
+    fn samples_do_not_depend_on_shard_order() {
+        let run = |first: std::ops::Range<i32>, second: std::ops::Range<i32>| {
+            let mut builder = CountingSamplingSetBuilder::with_capacity(3);
+            let mut shard = builder.new_shard();
+            let mut other_shard = shard.clone();
+            for item in first {
+                shard.insert(item);
+            }
+            for item in second {
+                other_shard.insert(item);
+            }
+            drop(other_shard);
+            drop(shard);
+            builder.finish()
+        };
+
+        let expected = (10, vec![0, 1, 2]);
+        assert_eq!(run(5..10, 0..5), expected);
+        assert_eq!(run(0..5, 5..10), expected);
+        assert_eq!(run(1..10, 0..1), expected);
+    }
+
+    #[test]
+    fn shards_keep_the_smallest_not_the_first() {
+        let mut builder = CountingSamplingSetBuilder::with_capacity(2);
+        let mut shard = builder.new_shard();
+        let mut other_shard = shard.clone();
+        // The global minimum arrives after this shard has seen two larger items.
+        for item in [100, 101, 0] {
+            shard.insert(item);
+        }
+        for item in [50, 51] {
+            other_shard.insert(item);
+        }
+        drop(shard);
+        drop(other_shard);
+
+        assert_eq!(builder.finish(), (5, vec![0, 50]));
+    }
+}
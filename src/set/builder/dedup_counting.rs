@@ -0,0 +1,145 @@
+use std::collections::HashSet;
+use std::marker::PhantomData;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use crate::mosaic::{ArrayMosaic, RectangularMosaic};
+use crate::set::builder::{SetBuilder, ShardBuilder};
+
+/// Counts distinct mosaics, treating a mosaic and its half turn as the same.
+///
+/// [`CountingSetBuilder`](super::counting::CountingSetBuilder) counts every
+/// inserted mosaic, so a build that produces a mosaic more than once is
+/// overcounted. This builder keeps the
+/// [`rectangular_canonical`](RectangularMosaic::rectangular_canonical) form of
+/// each mosaic and counts those instead. It stores nothing else, so it needs
+/// less memory than building the set in memory, though still memory
+/// proportional to the distinct count.
+pub struct DedupCountingSetBuilder<const W: usize, const H: usize, M> {
+    tx: Sender<HashSet<ArrayMosaic<W, H>>>,
+    rx: Receiver<HashSet<ArrayMosaic<W, H>>>,
+    _phantom_m: PhantomData<fn(M)>,
+}
+
+impl<const W: usize, const H: usize, M: RectangularMosaic<W, H>> DedupCountingSetBuilder<W, H, M> {
+    pub fn new() -> Self {
+        let (tx, rx) = channel();
+        Self {
+            tx,
+            rx,
+            _phantom_m: PhantomData,
+        }
+    }
+}
+
+impl<const W: usize, const H: usize, M: RectangularMosaic<W, H>> Default
+    for DedupCountingSetBuilder<W, H, M>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize, M: RectangularMosaic<W, H>> SetBuilder
+    for DedupCountingSetBuilder<W, H, M>
+{
+    type Item = M;
+    type Shard = DedupCountingShardBuilder<W, H, M>;
+    type Result = usize;
+
+    fn new_shard(&mut self) -> Self::Shard {
+        DedupCountingShardBuilder {
+            tx: Some(self.tx.clone()),
+            canonical_mosaics: HashSet::new(),
+            _phantom_m: PhantomData,
+        }
+    }
+
+    fn finish(self) -> Self::Result {
+        drop(self.tx);
+        let mut canonical_mosaics = HashSet::new();
+        while let Ok(shard_canonical_mosaics) = self.rx.recv() {
+            canonical_mosaics.extend(shard_canonical_mosaics);
+        }
+        canonical_mosaics.len()
+    }
+}
+
+pub struct DedupCountingShardBuilder<const W: usize, const H: usize, M> {
+    tx: Option<Sender<HashSet<ArrayMosaic<W, H>>>>,
+    canonical_mosaics: HashSet<ArrayMosaic<W, H>>,
+    _phantom_m: PhantomData<fn(M)>,
+}
+
+impl<const W: usize, const H: usize, M: RectangularMosaic<W, H>> ShardBuilder
+    for DedupCountingShardBuilder<W, H, M>
+{
+    type Item = M;
+
+    fn insert(&mut self, item: Self::Item) {
+        self.canonical_mosaics.insert(item.rectangular_canonical());
+    }
+
+    fn finish(self) {}
+}
+
+impl<const W: usize, const H: usize, M> Clone for DedupCountingShardBuilder<W, H, M> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            canonical_mosaics: HashSet::new(),
+            _phantom_m: PhantomData,
+        }
+    }
+}
+
+impl<const W: usize, const H: usize, M> Drop for DedupCountingShardBuilder<W, H, M> {
+    fn drop(&mut self) {
+        if let Some(tx) = self.tx.take() {
+            tx.send(std::mem::take(&mut self.canonical_mosaics))
+                .unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mosaic::RectangularMosaic;
+    use crate::rectangular::RectangularRotation;
+    use crate::set::builder::counting::CountingSetBuilder;
+    use crate::set::builder::{SetBuilder, ShardBuilder};
+
+    use super::DedupCountingSetBuilder;
+
+    fn build<B: SetBuilder>(mut builder: B, items: &[B::Item]) -> B::Result
+    where
+        B::Item: Clone,
+    {
+        let mut shard = builder.new_shard();
+        let mut other_shard = shard.clone();
+        for (i, item) in items.iter().enumerate() {
+            if i % 2 == 0 {
+                shard.insert(item.clone());
+            } else {
+                other_shard.insert(item.clone());
+            }
+        }
+        drop(shard);
+        drop(other_shard);
+        builder.finish()
+    }
+
+    #[test]
+    fn counts_duplicates_once() {
+        let mosaic = mosaic![[0, 1, 2], [16, 17, 18]];
+        let half_turn = (&mosaic + RectangularRotation::HalfTurn).to_array_mosaic();
+        let other = mosaic![[3, 4, 5], [19, 20, 21]];
+        // One exact duplicate and one rotated duplicate.
+        let mosaics = [mosaic, other, half_turn, mosaic];
+
+        assert_eq!(build(CountingSetBuilder::new(), &mosaics), 4);
+        assert_eq!(
+            build(DedupCountingSetBuilder::<3, 2, _>::new(), &mosaics),
+            2
+        );
+    }
+}
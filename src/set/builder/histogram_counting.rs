@@ -0,0 +1,135 @@
+use std::marker::PhantomData;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use crate::mosaic::RectangularMosaic;
+use crate::rectangular::VerticalSide;
+use crate::set::builder::{SetBuilder, ShardBuilder};
+
+/// Counts mosaics by the first color of their right edge, indexed by the
+/// color's primitive value.
+pub type EdgeColorHistogram = [usize; 23];
+
+/// Counts the inserted mosaics and tallies an [`EdgeColorHistogram`] of their
+/// right edges.
+pub struct HistogramCountingSetBuilder<const W: usize, const H: usize, T> {
+    tx: Sender<(usize, EdgeColorHistogram)>,
+    rx: Receiver<(usize, EdgeColorHistogram)>,
+    _phantom_t: PhantomData<fn(T)>,
+}
+
+impl<const W: usize, const H: usize, T> HistogramCountingSetBuilder<W, H, T> {
+    pub fn new() -> Self {
+        let (tx, rx) = channel();
+        Self {
+            tx,
+            rx,
+            _phantom_t: PhantomData,
+        }
+    }
+}
+
+impl<const W: usize, const H: usize, T> Default for HistogramCountingSetBuilder<W, H, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize, T: RectangularMosaic<W, H>> SetBuilder
+    for HistogramCountingSetBuilder<W, H, T>
+{
+    type Item = T;
+    type Shard = HistogramCountingShardBuilder<W, H, T>;
+    type Result = (usize, EdgeColorHistogram);
+
+    fn new_shard(&mut self) -> HistogramCountingShardBuilder<W, H, T> {
+        HistogramCountingShardBuilder {
+            tx: Some(self.tx.clone()),
+            count: 0,
+            histogram: [0; 23],
+            _phantom_t: PhantomData,
+        }
+    }
+
+    fn finish(self) -> Self::Result {
+        drop(self.tx);
+        let mut count = 0;
+        let mut histogram = [0; 23];
+        while let Ok((shard_count, shard_histogram)) = self.rx.recv() {
+            count += shard_count;
+            for (total, shard_total) in histogram.iter_mut().zip(shard_histogram) {
+                *total += shard_total;
+            }
+        }
+        (count, histogram)
+    }
+}
+
+pub struct HistogramCountingShardBuilder<const W: usize, const H: usize, T> {
+    tx: Option<Sender<(usize, EdgeColorHistogram)>>,
+    count: usize,
+    histogram: EdgeColorHistogram,
+    _phantom_t: PhantomData<fn(T)>,
+}
+
+impl<const W: usize, const H: usize, T: RectangularMosaic<W, H>> ShardBuilder
+    for HistogramCountingShardBuilder<W, H, T>
+{
+    type Item = T;
+
+    fn insert(&mut self, item: Self::Item) {
+        self.count += 1;
+        let color = item.vertical_edge(VerticalSide::Right)[0];
+        self.histogram[color.to_primitive() as usize] += 1;
+    }
+
+    fn finish(self) {}
+}
+
+impl<const W: usize, const H: usize, T> Clone for HistogramCountingShardBuilder<W, H, T> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            count: 0,
+            histogram: [0; 23],
+            _phantom_t: PhantomData,
+        }
+    }
+}
+
+impl<const W: usize, const H: usize, T> Drop for HistogramCountingShardBuilder<W, H, T> {
+    fn drop(&mut self) {
+        if let Some(tx) = self.tx.take() {
+            tx.send((self.count, self.histogram)).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::set::builder::{SetBuilder, ShardBuilder};
+    use crate::Color;
+
+    use super::HistogramCountingSetBuilder;
+
+    #[test]
+    fn merges_shard_histograms() {
+        let mut builder = HistogramCountingSetBuilder::<1, 1, _>::new();
+        let mut shard = builder.new_shard();
+        let mut other_shard = shard.clone();
+        // Right edges of tiles 0, 1, 2, and 4 are j, f, b, and j.
+        shard.insert(mosaic![[0]]);
+        shard.insert(mosaic![[1]]);
+        other_shard.insert(mosaic![[2]]);
+        other_shard.insert(mosaic![[4]]);
+        drop(shard);
+        drop(other_shard);
+
+        let (count, histogram) = builder.finish();
+        assert_eq!(count, 4);
+        let mut expected = [0; 23];
+        expected[Color::J.to_primitive() as usize] = 2;
+        expected[Color::F.to_primitive() as usize] = 1;
+        expected[Color::B.to_primitive() as usize] = 1;
+        assert_eq!(histogram, expected);
+    }
+}
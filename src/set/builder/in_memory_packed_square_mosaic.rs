@@ -0,0 +1,60 @@
+use crate::mosaic::{ArrayMosaic, PackedArrayMosaic};
+use crate::set::builder::in_memory_square_mosaic::{
+    InMemorySquareMosaicSetBuilder, InMemorySquareMosaicShardBuilder,
+};
+use crate::set::builder::{SetBuilder, ShardBuilder};
+use crate::set::square::SquareMosaicSet;
+
+/// Builds a square mosaic set in memory, packing each inserted mosaic into 2x2
+/// quads as it arrives.
+pub struct InMemoryPackedSquareMosaicSetBuilder<const N: usize, const QN: usize> {
+    inner: InMemorySquareMosaicSetBuilder<N, PackedArrayMosaic<N, N, QN, QN>>,
+}
+
+impl<const N: usize, const QN: usize> InMemoryPackedSquareMosaicSetBuilder<N, QN> {
+    pub fn new() -> Self {
+        Self {
+            inner: InMemorySquareMosaicSetBuilder::new(),
+        }
+    }
+}
+
+impl<const N: usize, const QN: usize> Default for InMemoryPackedSquareMosaicSetBuilder<N, QN> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const QN: usize> SetBuilder for InMemoryPackedSquareMosaicSetBuilder<N, QN> {
+    type Item = ArrayMosaic<N, N>;
+    type Shard = InMemoryPackedSquareMosaicShardBuilder<N, QN>;
+    type Result = SquareMosaicSet<N, PackedArrayMosaic<N, N, QN, QN>>;
+
+    fn new_shard(&mut self) -> Self::Shard {
+        InMemoryPackedSquareMosaicShardBuilder {
+            inner: self.inner.new_shard(),
+        }
+    }
+
+    fn finish(self) -> Self::Result {
+        self.inner.finish()
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryPackedSquareMosaicShardBuilder<const N: usize, const QN: usize> {
+    inner: InMemorySquareMosaicShardBuilder<N, PackedArrayMosaic<N, N, QN, QN>>,
+}
+
+impl<const N: usize, const QN: usize> ShardBuilder
+    for InMemoryPackedSquareMosaicShardBuilder<N, QN>
+{
+    type Item = ArrayMosaic<N, N>;
+
+    fn insert(&mut self, item: ArrayMosaic<N, N>) {
+        self.inner
+            .insert(PackedArrayMosaic::from_array_mosaic(&item));
+    }
+
+    fn finish(self) {}
+}
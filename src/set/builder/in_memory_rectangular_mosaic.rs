@@ -9,8 +9,8 @@ pub struct InMemoryRectangularMosaicSetBuilder<
     const H: usize,
     M: RectangularMosaic<W, H>,
 > {
-    tx: Sender<RectangularMosaicSet<W, H, M>>,
-    rx: Receiver<RectangularMosaicSet<W, H, M>>,
+    tx: Sender<Vec<M>>,
+    rx: Receiver<Vec<M>>,
 }
 
 impl<const W: usize, const H: usize, M: RectangularMosaic<W, H>>
@@ -22,7 +22,15 @@ impl<const W: usize, const H: usize, M: RectangularMosaic<W, H>>
     }
 }
 
-impl<const W: usize, const H: usize, M: RectangularMosaic<W, H> + Send> SetBuilder
+impl<const W: usize, const H: usize, M: RectangularMosaic<W, H>> Default
+    for InMemoryRectangularMosaicSetBuilder<W, H, M>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize, M: RectangularMosaic<W, H> + Send + Sync> SetBuilder
     for InMemoryRectangularMosaicSetBuilder<W, H, M>
 {
     type Item = M;
@@ -32,17 +40,17 @@ impl<const W: usize, const H: usize, M: RectangularMosaic<W, H> + Send> SetBuild
     fn new_shard(&mut self) -> Self::Shard {
         InMemoryRectangularMosaicShardBuilder {
             tx: self.tx.clone(),
-            set: Some(RectangularMosaicSet::new()),
+            mosaics: Some(Vec::new()),
         }
     }
 
     fn finish(self) -> Self::Result {
         drop(self.tx);
-        let mut result = RectangularMosaicSet::new();
-        while let Ok(shard_result) = self.rx.recv() {
-            result.extend(shard_result);
+        let mut mosaics = Vec::new();
+        while let Ok(mut shard_mosaics) = self.rx.recv() {
+            mosaics.append(&mut shard_mosaics);
         }
-        result
+        RectangularMosaicSet::from_mosaics_par(mosaics)
     }
 }
 
@@ -51,8 +59,8 @@ pub struct InMemoryRectangularMosaicShardBuilder<
     const H: usize,
     M: RectangularMosaic<W, H> + Send,
 > {
-    tx: Sender<RectangularMosaicSet<W, H, M>>,
-    set: Option<RectangularMosaicSet<W, H, M>>,
+    tx: Sender<Vec<M>>,
+    mosaics: Option<Vec<M>>,
 }
 
 impl<const W: usize, const H: usize, M: RectangularMosaic<W, H> + Send> ShardBuilder
@@ -61,7 +69,7 @@ impl<const W: usize, const H: usize, M: RectangularMosaic<W, H> + Send> ShardBui
     type Item = M;
 
     fn insert(&mut self, item: M) {
-        self.set.as_mut().unwrap().insert(item);
+        self.mosaics.as_mut().unwrap().push(item);
     }
 
     fn finish(self) {}
@@ -73,7 +81,7 @@ impl<const W: usize, const H: usize, M: RectangularMosaic<W, H> + Send> Clone
     fn clone(&self) -> Self {
         Self {
             tx: self.tx.clone(),
-            set: Some(RectangularMosaicSet::new()),
+            mosaics: Some(Vec::new()),
         }
     }
 }
@@ -82,8 +90,8 @@ impl<const W: usize, const H: usize, M: RectangularMosaic<W, H> + Send> Drop
     for InMemoryRectangularMosaicShardBuilder<W, H, M>
 {
     fn drop(&mut self) {
-        if let Some(set) = self.set.take() {
-            self.tx.send(set).unwrap();
+        if let Some(mosaics) = self.mosaics.take() {
+            self.tx.send(mosaics).unwrap();
         }
     }
 }
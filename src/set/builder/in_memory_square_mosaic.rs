@@ -1,38 +1,61 @@
+use std::marker::PhantomData;
 use std::sync::mpsc::{channel, Receiver, Sender};
 
 use crate::mosaic::SquareMosaic;
 use crate::set::builder::{SetBuilder, ShardBuilder};
-use crate::set::square::SquareMosaicSet;
+use crate::set::square::{SquareMosaicSet, SquareMosaicStore};
 
-pub struct InMemorySquareMosaicSetBuilder<const N: usize, M: SquareMosaic<N> + Send> {
-    tx: Sender<SquareMosaicSet<N, M>>,
-    rx: Receiver<SquareMosaicSet<N, M>>,
+/// Builds a square mosaic set in memory.
+///
+/// `S` selects the set type produced. [`Self::new`] produces a
+/// [`SquareMosaicSet`]. Use [`Default`] for the others.
+pub struct InMemorySquareMosaicSetBuilder<
+    const N: usize,
+    M: SquareMosaic<N> + Send,
+    S: SquareMosaicStore<N, M> = SquareMosaicSet<N, M>,
+> {
+    tx: Sender<S>,
+    rx: Receiver<S>,
+    _phantom_m: PhantomData<fn(M)>,
 }
 
 impl<const N: usize, M: SquareMosaic<N> + Send> InMemorySquareMosaicSetBuilder<N, M> {
     pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<const N: usize, M: SquareMosaic<N> + Send, S: SquareMosaicStore<N, M>> Default
+    for InMemorySquareMosaicSetBuilder<N, M, S>
+{
+    fn default() -> Self {
         let (tx, rx) = channel();
-        Self { tx, rx }
+        Self {
+            tx,
+            rx,
+            _phantom_m: PhantomData,
+        }
     }
 }
 
-impl<const N: usize, M: SquareMosaic<N> + Send> SetBuilder
-    for InMemorySquareMosaicSetBuilder<N, M>
+impl<const N: usize, M: SquareMosaic<N> + Send, S: SquareMosaicStore<N, M>> SetBuilder
+    for InMemorySquareMosaicSetBuilder<N, M, S>
 {
     type Item = M;
-    type Shard = InMemorySquareMosaicShardBuilder<N, M>;
-    type Result = SquareMosaicSet<N, M>;
+    type Shard = InMemorySquareMosaicShardBuilder<N, M, S>;
+    type Result = S;
 
     fn new_shard(&mut self) -> Self::Shard {
         InMemorySquareMosaicShardBuilder {
             tx: self.tx.clone(),
-            set: Some(SquareMosaicSet::new()),
+            set: Some(S::default()),
+            _phantom_m: PhantomData,
         }
     }
 
     fn finish(self) -> Self::Result {
         drop(self.tx);
-        let mut result = SquareMosaicSet::new();
+        let mut result = S::default();
         while let Ok(shard_result) = self.rx.recv() {
             result.extend(shard_result);
         }
@@ -40,13 +63,18 @@ impl<const N: usize, M: SquareMosaic<N> + Send> SetBuilder
     }
 }
 
-pub struct InMemorySquareMosaicShardBuilder<const N: usize, M: SquareMosaic<N> + Send> {
-    tx: Sender<SquareMosaicSet<N, M>>,
-    set: Option<SquareMosaicSet<N, M>>,
+pub struct InMemorySquareMosaicShardBuilder<
+    const N: usize,
+    M: SquareMosaic<N> + Send,
+    S: SquareMosaicStore<N, M> = SquareMosaicSet<N, M>,
+> {
+    tx: Sender<S>,
+    set: Option<S>,
+    _phantom_m: PhantomData<fn(M)>,
 }
 
-impl<const N: usize, M: SquareMosaic<N> + Send> ShardBuilder
-    for InMemorySquareMosaicShardBuilder<N, M>
+impl<const N: usize, M: SquareMosaic<N> + Send, S: SquareMosaicStore<N, M>> ShardBuilder
+    for InMemorySquareMosaicShardBuilder<N, M, S>
 {
     type Item = M;
 
@@ -57,16 +85,21 @@ impl<const N: usize, M: SquareMosaic<N> + Send> ShardBuilder
     fn finish(self) {}
 }
 
-impl<const N: usize, M: SquareMosaic<N> + Send> Clone for InMemorySquareMosaicShardBuilder<N, M> {
+impl<const N: usize, M: SquareMosaic<N> + Send, S: SquareMosaicStore<N, M>> Clone
+    for InMemorySquareMosaicShardBuilder<N, M, S>
+{
     fn clone(&self) -> Self {
         Self {
             tx: self.tx.clone(),
-            set: Some(SquareMosaicSet::new()),
+            set: Some(S::default()),
+            _phantom_m: PhantomData,
         }
     }
 }
 
-impl<const N: usize, M: SquareMosaic<N> + Send> Drop for InMemorySquareMosaicShardBuilder<N, M> {
+impl<const N: usize, M: SquareMosaic<N> + Send, S: SquareMosaicStore<N, M>> Drop
+    for InMemorySquareMosaicShardBuilder<N, M, S>
+{
     fn drop(&mut self) {
         if let Some(set) = self.set.take() {
             self.tx.send(set).unwrap();
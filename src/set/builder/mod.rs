@@ -1,5 +1,11 @@
+pub mod assumption_emitting;
+pub mod bloom_counting;
+pub mod checkpointing_counting;
 pub mod counting;
 pub mod counting_sampling;
+pub mod dedup_counting;
+pub mod histogram_counting;
+pub mod in_memory_packed_square_mosaic;
 pub mod in_memory_rectangular_mosaic;
 pub mod in_memory_square_mosaic;
 
@@ -1,20 +1,76 @@
-use bitvec::bitarr;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
 use rayon::prelude::{ParallelBridge, ParallelIterator};
-use strum::IntoEnumIterator;
+use rayon::ThreadPoolBuilder;
 
 use crate::mosaic::{
-    ArrayMosaic, RectangularMosaic, RotatedRectangularMosaic, RotatedSquareMosaic, SquareMosaic,
+    concat_horizontal, concat_vertical, ArrayMosaic, RectangularMosaic, RotatedRectangularMosaic,
+    RotatedSquareMosaic, SquareMosaic,
 };
 use crate::rectangular::{HorizontalSide, RectangularRotation};
 use crate::set::builder::{SetBuilder, ShardBuilder};
 use crate::set::rectangle::RectangularMosaicSet;
 use crate::set::square::SquareMosaicSet;
-use crate::{ExteriorMask, RotatedTile, Rotation, Side, Tile};
+use crate::tile_set::{EternityIiTiles, TileColors};
+use crate::{clue_tiles, is_clue_tile, RotatedTile, Rotation, Side, Tile, TileClass};
 
 pub mod builder;
 pub mod rectangle;
 pub mod square;
 
+/// Counts the tiles in each [`Rotation`] across every cell of `mosaics`, indexed
+/// by the rotation's primitive value.
+fn rotation_histogram<'a, const W: usize, const H: usize, M: RectangularMosaic<W, H> + 'a>(
+    mosaics: impl Iterator<Item = &'a M>,
+) -> [usize; 4] {
+    let mut histogram = [0; 4];
+    for mosaic in mosaics {
+        for y in 0..H {
+            for x in 0..W {
+                histogram[mosaic.get(x, y).rotation.to_primitive() as usize] += 1;
+            }
+        }
+    }
+    histogram
+}
+
+/// Panics if any two of `rotated_mosaics` are equal, which is how each set's
+/// `assert_distinct` checks that no mosaic is stored twice in any rotation.
+///
+/// `rotated_mosaics` comes from a set's `iter_all_rotations`, which yields each
+/// stored mosaic in each of `rotations` in turn. The first colliding pair is
+/// reported by stored index and rotation, in insertion order.
+///
+/// This costs the same as checking set sizes: one ordered map insert per
+/// rotation of every mosaic.
+fn assert_distinct_rotations<const W: usize, const H: usize, R: RectangularMosaic<W, H>>(
+    rotations: &[impl Debug],
+    rotated_mosaics: impl Iterator<Item = R>,
+) {
+    let describe = |position: usize| {
+        format!(
+            "({}, {:?})",
+            position / rotations.len(),
+            rotations[position % rotations.len()],
+        )
+    };
+    let mut seen = BTreeMap::new();
+    for (position, rotated_mosaic) in rotated_mosaics.enumerate() {
+        if let Some(earlier) = seen.insert(rotated_mosaic.to_array_mosaic(), position) {
+            panic!(
+                "mosaics {} and {} coincide\n{}",
+                describe(earlier),
+                describe(position),
+                rotated_mosaic.display(4),
+            );
+        }
+    }
+}
+
 /// Returns whether the rotated tile is a corner in canonical orientation.
 ///
 /// ```text
@@ -28,8 +84,9 @@ pub mod square;
 ///
 /// Corner pieces in canonical orientation have exterior sides on the top and
 /// left.
-fn is_canonical_corner(rotated_tile: RotatedTile) -> bool {
-    rotated_tile.exterior_mask() == ExteriorMask::zero().with_top(true).with_left(true)
+fn is_canonical_corner(tiles: &impl TileColors, rotated_tile: RotatedTile) -> bool {
+    let mask = tiles.exterior_mask(rotated_tile);
+    tiles.class(rotated_tile) == TileClass::Corner && mask.top() && mask.left()
 }
 
 /// Returns whether the rotated tile is an edge in canonical orientation.
@@ -44,8 +101,8 @@ fn is_canonical_corner(rotated_tile: RotatedTile) -> bool {
 ///
 /// Edge pieces in canonical orientation have an exterior side on
 /// the left.
-fn is_canonical_edge(rotated_tile: RotatedTile) -> bool {
-    rotated_tile.exterior_mask() == ExteriorMask::zero().with_left(true)
+fn is_canonical_edge(tiles: &impl TileColors, rotated_tile: RotatedTile) -> bool {
+    tiles.class(rotated_tile) == TileClass::Edge && tiles.exterior_mask(rotated_tile).left()
 }
 
 /// Returns whether the rotated tile is a center in canonical orientation.
@@ -59,32 +116,41 @@ fn is_canonical_edge(rotated_tile: RotatedTile) -> bool {
 /// ```
 ///
 /// Center pieces have no exterior sides. The identity rotation is canonical.
-fn is_canonical_center(rotated_tile: RotatedTile) -> bool {
-    rotated_tile.exterior_mask() == ExteriorMask::zero()
-        && rotated_tile.rotation == Rotation::Identity
+fn is_canonical_center(tiles: &impl TileColors, rotated_tile: RotatedTile) -> bool {
+    tiles.class(rotated_tile) == TileClass::Center && rotated_tile.rotation == Rotation::Identity
 }
 
 pub fn build_1x1_sets() -> (
     SquareMosaicSet<1, impl SquareMosaic<1>>,
     SquareMosaicSet<1, impl SquareMosaic<1>>,
     SquareMosaicSet<1, impl SquareMosaic<1>>,
+) {
+    build_1x1_sets_for(&EternityIiTiles)
+}
+
+/// Like [`build_1x1_sets`], but classifying the tiles of `tiles`.
+///
+/// The sets hold [`RotatedTile`]s, whose colors always come from the built-in
+/// table, so only the classification follows `tiles`.
+pub fn build_1x1_sets_for(
+    tiles: &impl TileColors,
+) -> (
+    SquareMosaicSet<1, impl SquareMosaic<1>>,
+    SquareMosaicSet<1, impl SquareMosaic<1>>,
+    SquareMosaicSet<1, impl SquareMosaic<1>>,
 ) {
     // Consider all possible rotated tiles, classifying them and collecting only
     // the canonical ones.
     let mut square_1x1_corners = SquareMosaicSet::new();
     let mut square_1x1_edges = SquareMosaicSet::new();
     let mut square_1x1_centers = SquareMosaicSet::new();
-    for tile_id in 0..=255 {
-        let tile = Tile::from_primitive(tile_id);
-        for rotation in Rotation::iter() {
-            let rotated_tile = RotatedTile { tile, rotation };
-            if is_canonical_corner(rotated_tile) {
-                square_1x1_corners.insert(mosaic![[@rotated_tile]]);
-            } else if is_canonical_edge(rotated_tile) {
-                square_1x1_edges.insert(mosaic![[@rotated_tile]]);
-            } else if is_canonical_center(rotated_tile) {
-                square_1x1_centers.insert(mosaic![[@rotated_tile]]);
-            }
+    for rotated_tile in tiles.tiles().into_iter().flat_map(Tile::rotations) {
+        if is_canonical_corner(tiles, rotated_tile) {
+            square_1x1_corners.insert(mosaic![[@rotated_tile]]);
+        } else if is_canonical_edge(tiles, rotated_tile) {
+            square_1x1_edges.insert(mosaic![[@rotated_tile]]);
+        } else if is_canonical_center(tiles, rotated_tile) {
+            square_1x1_centers.insert(mosaic![[@rotated_tile]]);
         }
     }
     (square_1x1_corners, square_1x1_edges, square_1x1_centers)
@@ -100,39 +166,39 @@ pub fn build_1x1_sets_with_clues() -> (
     SquareMosaicSet<1, impl SquareMosaic<1>>,
     SquareMosaicSet<1, impl SquareMosaic<1>>,
 ) {
+    let [i8, c3, c14, n3, n14] = clue_tiles().map(|(_, _, rotated_tile)| rotated_tile);
     let mut square_1x1_centers_c3_clue = SquareMosaicSet::new();
-    square_1x1_centers_c3_clue.insert(mosaic!([76 Identity]));
+    square_1x1_centers_c3_clue.insert(mosaic![[@c3]]);
     let mut square_1x1_centers_c14_clue = SquareMosaicSet::new();
-    square_1x1_centers_c14_clue.insert(mosaic!([179 QuarterTurnLeft]));
+    square_1x1_centers_c14_clue.insert(mosaic![[@c14]]);
     let mut square_1x1_centers_i8_clue = SquareMosaicSet::new();
-    square_1x1_centers_i8_clue.insert(mosaic!([135 Identity]));
+    square_1x1_centers_i8_clue.insert(mosaic![[@i8]]);
     let mut square_1x1_centers_n3_clue = SquareMosaicSet::new();
-    square_1x1_centers_n3_clue.insert(mosaic!([211 HalfTurn]));
+    square_1x1_centers_n3_clue.insert(mosaic![[@n3]]);
     let mut square_1x1_centers_n14_clue = SquareMosaicSet::new();
-    square_1x1_centers_n14_clue.insert(mosaic!([125 QuarterTurnRight]));
+    square_1x1_centers_n14_clue.insert(mosaic![[@n14]]);
 
     // Consider all possible rotated tiles, classifying them and collecting only
     // the canonical ones that are not clue tiles.
     let mut square_1x1_corners_no_clues = SquareMosaicSet::new();
     let mut square_1x1_edges_no_clues = SquareMosaicSet::new();
     let mut square_1x1_centers_no_clues = SquareMosaicSet::new();
-    for tile_id in 0..=255 {
+    for rotated_tile in RotatedTile::values() {
         // Skip the clue tiles.
-        if [76, 125, 135, 179, 211].contains(&tile_id) {
+        if is_clue_tile(rotated_tile.tile) {
             continue;
         }
-        let tile = Tile::from_primitive(tile_id);
-        for rotation in Rotation::iter() {
-            let rotated_tile = RotatedTile { tile, rotation };
-            if is_canonical_corner(rotated_tile) {
-                square_1x1_corners_no_clues.insert(mosaic![[@rotated_tile]]);
-            } else if is_canonical_edge(rotated_tile) {
-                square_1x1_edges_no_clues.insert(mosaic![[@rotated_tile]]);
-            } else if is_canonical_center(rotated_tile) {
-                square_1x1_centers_no_clues.insert(mosaic![[@rotated_tile]]);
-            }
+        if is_canonical_corner(&EternityIiTiles, rotated_tile) {
+            square_1x1_corners_no_clues.insert(mosaic![[@rotated_tile]]);
+        } else if is_canonical_edge(&EternityIiTiles, rotated_tile) {
+            square_1x1_edges_no_clues.insert(mosaic![[@rotated_tile]]);
+        } else if is_canonical_center(&EternityIiTiles, rotated_tile) {
+            square_1x1_centers_no_clues.insert(mosaic![[@rotated_tile]]);
         }
     }
+    debug_assert_eq!(square_1x1_corners_no_clues.contains_clue_violation(), None);
+    debug_assert_eq!(square_1x1_edges_no_clues.contains_clue_violation(), None);
+    debug_assert_eq!(square_1x1_centers_no_clues.contains_clue_violation(), None);
     (
         square_1x1_corners_no_clues,
         square_1x1_edges_no_clues,
@@ -149,56 +215,14 @@ fn combine_squares_horizontally_to_rectangle<const SHORT: usize, const LONG: usi
     a: impl SquareMosaic<SHORT>,
     b: impl SquareMosaic<SHORT>,
 ) -> Option<ArrayMosaic<LONG, SHORT>> {
-    assert_eq!(SHORT * 2, LONG);
-
-    let mut used_tiles = bitarr![0; 256];
-    let mut mark = |rotated_tile: RotatedTile| -> Option<RotatedTile> {
-        let index = rotated_tile.tile.to_primitive() as usize;
-        if used_tiles[index] {
-            return None;
-        }
-        used_tiles.set(index, true);
-        Some(rotated_tile)
-    };
-
-    let mut mosaic = ArrayMosaic {
-        tiles: [[RotatedTile::ZERO; LONG]; SHORT],
-    };
-    for y in 0..SHORT {
-        for x in 0..SHORT {
-            mosaic.tiles[y][x] = mark(a.get(x, y))?;
-            mosaic.tiles[y][x + SHORT] = mark(b.get(x, y))?;
-        }
-    }
-    Some(mosaic)
+    concat_horizontal(a, b).ok()
 }
 
 fn combine_rectangles_vertically_to_square<const SHORT: usize, const LONG: usize>(
     a: impl RectangularMosaic<LONG, SHORT>,
     b: impl RectangularMosaic<LONG, SHORT>,
 ) -> Option<ArrayMosaic<LONG, LONG>> {
-    assert_eq!(SHORT * 2, LONG);
-
-    let mut used_tiles = bitarr![0; 256];
-    let mut mark = |rotated_tile: RotatedTile| -> Option<RotatedTile> {
-        let index = rotated_tile.tile.to_primitive() as usize;
-        if used_tiles[index] {
-            return None;
-        }
-        used_tiles.set(index, true);
-        Some(rotated_tile)
-    };
-
-    let mut mosaic = ArrayMosaic {
-        tiles: [[RotatedTile::ZERO; LONG]; LONG],
-    };
-    for y in 0..SHORT {
-        for x in 0..LONG {
-            mosaic.tiles[y][x] = mark(a.get(x, y))?;
-            mosaic.tiles[y + SHORT][x] = mark(b.get(x, y))?;
-        }
-    }
-    Some(mosaic)
+    concat_vertical(a, b).ok()
 }
 
 pub fn min_rotated_tile<const W: usize, const H: usize>(
@@ -213,6 +237,87 @@ pub fn min_rotated_tile<const W: usize, const H: usize>(
     result
 }
 
+/// How many inserts a worker accumulates before reporting progress. Keeps
+/// workers from contending on the shared counter.
+pub const PROGRESS_INTERVAL: usize = 4096;
+
+/// Tracks the running insert count across workers for a progress callback.
+struct Progress<'a> {
+    inserted: AtomicUsize,
+    callback: &'a (dyn Fn(usize) + Sync),
+}
+
+impl<'a> Progress<'a> {
+    fn new(callback: &'a (dyn Fn(usize) + Sync)) -> Self {
+        Self {
+            inserted: AtomicUsize::new(0),
+            callback,
+        }
+    }
+
+    /// Records one insert by a worker with `pending` unreported inserts,
+    /// reporting them once there are [`PROGRESS_INTERVAL`] of them.
+    fn record_insert(&self, pending: &mut usize) {
+        *pending += 1;
+        if *pending == PROGRESS_INTERVAL {
+            (self.callback)(self.inserted.fetch_add(*pending, Ordering::Relaxed) + *pending);
+            *pending = 0;
+        }
+    }
+}
+
+/// Summarizes the fan-out of a join: for each shared edge of the `a` set, how
+/// many `b` candidates share it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FanOutStats {
+    /// The number of shared edges recorded.
+    pub edges: usize,
+    /// The smallest fan-out, or zero if no edges were recorded.
+    pub min: usize,
+    pub max: usize,
+    /// The sum of every edge's fan-out.
+    pub total: usize,
+    /// Counts edges by fan-out in the ranges given by [`Self::bucket_range`].
+    pub histogram: Vec<usize>,
+}
+
+impl FanOutStats {
+    pub fn record(&mut self, fan_out: usize) {
+        self.min = if self.edges == 0 {
+            fan_out
+        } else {
+            self.min.min(fan_out)
+        };
+        self.max = self.max.max(fan_out);
+        self.total += fan_out;
+        self.edges += 1;
+
+        let bucket = (usize::BITS - fan_out.leading_zeros()) as usize;
+        if self.histogram.len() <= bucket {
+            self.histogram.resize(bucket + 1, 0);
+        }
+        self.histogram[bucket] += 1;
+    }
+
+    /// Returns the mean fan-out, or zero if no edges were recorded.
+    pub fn mean(&self) -> f64 {
+        if self.edges == 0 {
+            0.0
+        } else {
+            self.total as f64 / self.edges as f64
+        }
+    }
+
+    /// Returns the fan-outs counted by `histogram[bucket]`: zero for bucket
+    /// zero, then successive powers of two.
+    pub fn bucket_range(bucket: usize) -> Range<usize> {
+        match bucket {
+            0 => 0..1,
+            _ => 1 << (bucket - 1)..1 << bucket,
+        }
+    }
+}
+
 pub fn build_rectangles_memo<
     const SHORT: usize,
     const LONG: usize,
@@ -220,25 +325,188 @@ pub fn build_rectangles_memo<
     MosaicA: SquareMosaic<SHORT> + Send + Sync,
     MosaicB: SquareMosaic<SHORT> + Send + Sync,
     Memo,
+>(
+    set_builder: B,
+    a_set: &SquareMosaicSet<SHORT, MosaicA>,
+    a_memo: impl Fn(RotatedSquareMosaic<SHORT, MosaicA>) -> Option<Memo> + Send + Sync,
+    b_set: &SquareMosaicSet<SHORT, MosaicB>,
+    b_filter: impl Fn(&Memo, RotatedSquareMosaic<SHORT, MosaicB>) -> bool + Send + Sync,
+) -> B::Result {
+    build_rectangles_memo_with_progress(set_builder, a_set, a_memo, b_set, b_filter, &|_| ())
+}
+
+/// Like [`build_rectangles_memo`], but periodically calls `progress` with the
+/// running number of inserted mosaics.
+///
+/// Each worker reports every [`PROGRESS_INTERVAL`] inserts, so the callback may
+/// be called concurrently and the final count isn't necessarily reported.
+pub fn build_rectangles_memo_with_progress<
+    const SHORT: usize,
+    const LONG: usize,
+    B: SetBuilder<Item = ArrayMosaic<LONG, SHORT>>,
+    MosaicA: SquareMosaic<SHORT> + Send + Sync,
+    MosaicB: SquareMosaic<SHORT> + Send + Sync,
+    Memo,
+>(
+    set_builder: B,
+    a_set: &SquareMosaicSet<SHORT, MosaicA>,
+    a_memo: impl Fn(RotatedSquareMosaic<SHORT, MosaicA>) -> Option<Memo> + Send + Sync,
+    b_set: &SquareMosaicSet<SHORT, MosaicB>,
+    b_filter: impl Fn(&Memo, RotatedSquareMosaic<SHORT, MosaicB>) -> bool + Send + Sync,
+    progress: &(dyn Fn(usize) + Sync),
+) -> B::Result {
+    build_rectangles_memo_impl(
+        set_builder,
+        a_set,
+        a_memo,
+        b_set,
+        b_filter,
+        progress,
+        &AtomicBool::new(false),
+        None,
+    )
+}
+
+/// Like [`build_rectangles_memo`], but stops early once `cancel` is set, for
+/// example from another thread sharing it through an [`Arc`](std::sync::Arc).
+///
+/// Workers check the flag between mosaics. A cancelled build returns the
+/// builder's result for whatever was inserted before stopping.
+pub fn build_rectangles_memo_cancellable<
+    const SHORT: usize,
+    const LONG: usize,
+    B: SetBuilder<Item = ArrayMosaic<LONG, SHORT>>,
+    MosaicA: SquareMosaic<SHORT> + Send + Sync,
+    MosaicB: SquareMosaic<SHORT> + Send + Sync,
+    Memo,
+>(
+    set_builder: B,
+    a_set: &SquareMosaicSet<SHORT, MosaicA>,
+    a_memo: impl Fn(RotatedSquareMosaic<SHORT, MosaicA>) -> Option<Memo> + Send + Sync,
+    b_set: &SquareMosaicSet<SHORT, MosaicB>,
+    b_filter: impl Fn(&Memo, RotatedSquareMosaic<SHORT, MosaicB>) -> bool + Send + Sync,
+    cancel: &AtomicBool,
+) -> B::Result {
+    build_rectangles_memo_impl(
+        set_builder,
+        a_set,
+        a_memo,
+        b_set,
+        b_filter,
+        &|_| (),
+        cancel,
+        None,
+    )
+}
+
+/// Like [`build_rectangles_memo`], but runs on a dedicated pool of `threads`
+/// threads instead of rayon's global pool, for reproducible benchmarks.
+///
+/// Only counts are reproducible: the rectangles found don't depend on the
+/// thread count, but which shard inserts each one, and in what order, does.
+/// Anything that observes insert order can differ between runs, such as the
+/// order of [`RectangularMosaicSet::iter_mosaics`] or of the blocks an
+/// [`AssumptionEmittingSetBuilder`] writes.
+///
+/// [`AssumptionEmittingSetBuilder`]: builder::assumption_emitting::AssumptionEmittingSetBuilder
+///
+/// # Panics
+///
+/// Panics if the thread pool can't be built.
+pub fn build_rectangles_memo_with_threads<
+    const SHORT: usize,
+    const LONG: usize,
+    B: SetBuilder<Item = ArrayMosaic<LONG, SHORT>> + Send,
+    MosaicA: SquareMosaic<SHORT> + Send + Sync,
+    MosaicB: SquareMosaic<SHORT> + Send + Sync,
+    Memo,
+>(
+    set_builder: B,
+    a_set: &SquareMosaicSet<SHORT, MosaicA>,
+    a_memo: impl Fn(RotatedSquareMosaic<SHORT, MosaicA>) -> Option<Memo> + Send + Sync,
+    b_set: &SquareMosaicSet<SHORT, MosaicB>,
+    b_filter: impl Fn(&Memo, RotatedSquareMosaic<SHORT, MosaicB>) -> bool + Send + Sync,
+    threads: usize,
+) -> B::Result
+where
+    B::Result: Send,
+{
+    ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap()
+        .install(|| build_rectangles_memo(set_builder, a_set, a_memo, b_set, b_filter))
+}
+
+/// Like [`build_rectangles_memo`], but also returns [`FanOutStats`] for the
+/// join, counting the `b` candidates for each shared edge before `a_memo` and
+/// `b_filter` apply.
+pub fn build_rectangles_memo_instrumented<
+    const SHORT: usize,
+    const LONG: usize,
+    B: SetBuilder<Item = ArrayMosaic<LONG, SHORT>>,
+    MosaicA: SquareMosaic<SHORT> + Send + Sync,
+    MosaicB: SquareMosaic<SHORT> + Send + Sync,
+    Memo,
+>(
+    set_builder: B,
+    a_set: &SquareMosaicSet<SHORT, MosaicA>,
+    a_memo: impl Fn(RotatedSquareMosaic<SHORT, MosaicA>) -> Option<Memo> + Send + Sync,
+    b_set: &SquareMosaicSet<SHORT, MosaicB>,
+    b_filter: impl Fn(&Memo, RotatedSquareMosaic<SHORT, MosaicB>) -> bool + Send + Sync,
+) -> (B::Result, FanOutStats) {
+    let fan_out = Mutex::new(FanOutStats::default());
+    let result = build_rectangles_memo_impl(
+        set_builder,
+        a_set,
+        a_memo,
+        b_set,
+        b_filter,
+        &|_| (),
+        &AtomicBool::new(false),
+        Some(&fan_out),
+    );
+    (result, fan_out.into_inner().unwrap())
+}
+
+fn build_rectangles_memo_impl<
+    const SHORT: usize,
+    const LONG: usize,
+    B: SetBuilder<Item = ArrayMosaic<LONG, SHORT>>,
+    MosaicA: SquareMosaic<SHORT> + Send + Sync,
+    MosaicB: SquareMosaic<SHORT> + Send + Sync,
+    Memo,
 >(
     mut set_builder: B,
     a_set: &SquareMosaicSet<SHORT, MosaicA>,
     a_memo: impl Fn(RotatedSquareMosaic<SHORT, MosaicA>) -> Option<Memo> + Send + Sync,
     b_set: &SquareMosaicSet<SHORT, MosaicB>,
     b_filter: impl Fn(&Memo, RotatedSquareMosaic<SHORT, MosaicB>) -> bool + Send + Sync,
+    progress: &(dyn Fn(usize) + Sync),
+    cancel: &AtomicBool,
+    fan_out: Option<&Mutex<FanOutStats>>,
 ) -> B::Result {
     assert_eq!(LONG, SHORT * 2);
 
+    let progress = Progress::new(progress);
     a_set.iter_by_edge(Side::Right).par_bridge().for_each_with(
-        set_builder.new_shard(),
-        |shard_builder, (a_shared_edge, a_set)| {
+        (set_builder.new_shard(), 0),
+        |(shard_builder, pending), (a_shared_edge, a_set)| {
             let b_shared_edge = a_shared_edge.reversed();
+            if let Some(fan_out) = fan_out {
+                let candidates = b_set.query(Side::Left, &b_shared_edge).count();
+                fan_out.lock().unwrap().record(candidates);
+            }
             for (_, a) in a_set {
+                if cancel.load(Ordering::Relaxed) {
+                    return;
+                }
                 if let Some(memo) = a_memo(a) {
                     for (_, b) in b_set.query(Side::Left, &b_shared_edge) {
                         if b_filter(&memo, b) {
                             if let Some(mosaic) = combine_squares_horizontally_to_rectangle(a, b) {
                                 shard_builder.insert(mosaic);
+                                progress.record_insert(pending);
                             }
                         }
                     }
@@ -271,6 +539,37 @@ pub fn build_rectangles<
     )
 }
 
+/// Counts the rectangles [`build_rectangles`] would produce without building
+/// them, by multiplying the sizes of matching groups in the two edge indexes.
+///
+/// This skips the check that no tile is used twice, so in general it's an upper
+/// bound. It's exact when the two sets can't share a tile.
+pub fn count_rectangles_upper_bound<
+    const SHORT: usize,
+    MosaicA: SquareMosaic<SHORT>,
+    MosaicB: SquareMosaic<SHORT>,
+>(
+    a_set: &SquareMosaicSet<SHORT, MosaicA>,
+    a_filter: impl Fn(RotatedSquareMosaic<SHORT, MosaicA>) -> bool,
+    b_set: &SquareMosaicSet<SHORT, MosaicB>,
+    b_filter: impl Fn(RotatedSquareMosaic<SHORT, MosaicB>) -> bool,
+) -> usize {
+    a_set
+        .iter_by_edge(Side::Right)
+        .map(|(a_shared_edge, a_set)| {
+            let a_count = a_set.filter(|&(_, a)| a_filter(a)).count();
+            if a_count == 0 {
+                return 0;
+            }
+            let b_count = b_set
+                .query(Side::Left, &a_shared_edge.reversed())
+                .filter(|&(_, b)| b_filter(b))
+                .count();
+            a_count * b_count
+        })
+        .sum()
+}
+
 pub fn build_squares_memo<
     const SHORT: usize,
     const LONG: usize,
@@ -278,29 +577,139 @@ pub fn build_squares_memo<
     MosaicA: RectangularMosaic<LONG, SHORT> + Send + Sync,
     MosaicB: RectangularMosaic<LONG, SHORT> + Send + Sync,
     Memo,
+>(
+    set_builder: B,
+    a_set: &RectangularMosaicSet<LONG, SHORT, MosaicA>,
+    a_memo: impl Fn(RotatedRectangularMosaic<LONG, SHORT, MosaicA>) -> Option<Memo> + Send + Sync,
+    b_set: &RectangularMosaicSet<LONG, SHORT, MosaicB>,
+    b_filter: impl Fn(&Memo, RotatedRectangularMosaic<LONG, SHORT, MosaicB>) -> bool + Send + Sync,
+) -> B::Result {
+    build_squares_memo_with_progress(set_builder, a_set, a_memo, b_set, b_filter, &|_| ())
+}
+
+/// Like [`build_squares_memo`], but periodically calls `progress` with the
+/// running number of inserted mosaics.
+///
+/// Each worker reports every [`PROGRESS_INTERVAL`] inserts, so the callback may
+/// be called concurrently and the final count isn't necessarily reported.
+pub fn build_squares_memo_with_progress<
+    const SHORT: usize,
+    const LONG: usize,
+    B: SetBuilder<Item = ArrayMosaic<LONG, LONG>>,
+    MosaicA: RectangularMosaic<LONG, SHORT> + Send + Sync,
+    MosaicB: RectangularMosaic<LONG, SHORT> + Send + Sync,
+    Memo,
+>(
+    set_builder: B,
+    a_set: &RectangularMosaicSet<LONG, SHORT, MosaicA>,
+    a_memo: impl Fn(RotatedRectangularMosaic<LONG, SHORT, MosaicA>) -> Option<Memo> + Send + Sync,
+    b_set: &RectangularMosaicSet<LONG, SHORT, MosaicB>,
+    b_filter: impl Fn(&Memo, RotatedRectangularMosaic<LONG, SHORT, MosaicB>) -> bool + Send + Sync,
+    progress: &(dyn Fn(usize) + Sync),
+) -> B::Result {
+    build_squares_memo_impl(
+        set_builder,
+        a_set,
+        a_memo,
+        b_set,
+        b_filter,
+        progress,
+        &AtomicBool::new(false),
+    )
+}
+
+/// Like [`build_squares_memo`], but stops early once `cancel` is set, for
+/// example from another thread sharing it through an [`Arc`](std::sync::Arc).
+///
+/// Workers check the flag between mosaics. A cancelled build returns the
+/// builder's result for whatever was inserted before stopping.
+pub fn build_squares_memo_cancellable<
+    const SHORT: usize,
+    const LONG: usize,
+    B: SetBuilder<Item = ArrayMosaic<LONG, LONG>>,
+    MosaicA: RectangularMosaic<LONG, SHORT> + Send + Sync,
+    MosaicB: RectangularMosaic<LONG, SHORT> + Send + Sync,
+    Memo,
+>(
+    set_builder: B,
+    a_set: &RectangularMosaicSet<LONG, SHORT, MosaicA>,
+    a_memo: impl Fn(RotatedRectangularMosaic<LONG, SHORT, MosaicA>) -> Option<Memo> + Send + Sync,
+    b_set: &RectangularMosaicSet<LONG, SHORT, MosaicB>,
+    b_filter: impl Fn(&Memo, RotatedRectangularMosaic<LONG, SHORT, MosaicB>) -> bool + Send + Sync,
+    cancel: &AtomicBool,
+) -> B::Result {
+    build_squares_memo_impl(set_builder, a_set, a_memo, b_set, b_filter, &|_| (), cancel)
+}
+
+/// Like [`build_squares_memo`], but runs on a dedicated pool of `threads`
+/// threads, like [`build_rectangles_memo_with_threads`].
+///
+/// # Panics
+///
+/// Panics if the thread pool can't be built.
+pub fn build_squares_memo_with_threads<
+    const SHORT: usize,
+    const LONG: usize,
+    B: SetBuilder<Item = ArrayMosaic<LONG, LONG>> + Send,
+    MosaicA: RectangularMosaic<LONG, SHORT> + Send + Sync,
+    MosaicB: RectangularMosaic<LONG, SHORT> + Send + Sync,
+    Memo,
+>(
+    set_builder: B,
+    a_set: &RectangularMosaicSet<LONG, SHORT, MosaicA>,
+    a_memo: impl Fn(RotatedRectangularMosaic<LONG, SHORT, MosaicA>) -> Option<Memo> + Send + Sync,
+    b_set: &RectangularMosaicSet<LONG, SHORT, MosaicB>,
+    b_filter: impl Fn(&Memo, RotatedRectangularMosaic<LONG, SHORT, MosaicB>) -> bool + Send + Sync,
+    threads: usize,
+) -> B::Result
+where
+    B::Result: Send,
+{
+    ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap()
+        .install(|| build_squares_memo(set_builder, a_set, a_memo, b_set, b_filter))
+}
+
+fn build_squares_memo_impl<
+    const SHORT: usize,
+    const LONG: usize,
+    B: SetBuilder<Item = ArrayMosaic<LONG, LONG>>,
+    MosaicA: RectangularMosaic<LONG, SHORT> + Send + Sync,
+    MosaicB: RectangularMosaic<LONG, SHORT> + Send + Sync,
+    Memo,
 >(
     mut set_builder: B,
     a_set: &RectangularMosaicSet<LONG, SHORT, MosaicA>,
     a_memo: impl Fn(RotatedRectangularMosaic<LONG, SHORT, MosaicA>) -> Option<Memo> + Send + Sync,
     b_set: &RectangularMosaicSet<LONG, SHORT, MosaicB>,
     b_filter: impl Fn(&Memo, RotatedRectangularMosaic<LONG, SHORT, MosaicB>) -> bool + Send + Sync,
+    progress: &(dyn Fn(usize) + Sync),
+    cancel: &AtomicBool,
 ) -> B::Result {
     assert_eq!(LONG, SHORT * 2);
 
+    let progress = Progress::new(progress);
     a_set
         .iter_by_horizontal_edge(HorizontalSide::Bottom)
         .par_bridge()
         .for_each_with(
-            set_builder.new_shard(),
-            |shard_builder, (a_shared_edge, a_set)| {
-                let b_shared_edge = a_shared_edge.reversed();
+            (set_builder.new_shard(), 0),
+            |(shard_builder, pending), (a_shared_edge, a_set)| {
                 for (_, a) in a_set {
+                    if cancel.load(Ordering::Relaxed) {
+                        return;
+                    }
                     if let Some(memo) = a_memo(a) {
-                        for (_, b) in b_set.query_horizontal(HorizontalSide::Top, &b_shared_edge) {
+                        for (_, b) in
+                            b_set.query_horizontal_abutting(HorizontalSide::Top, a_shared_edge)
+                        {
                             if b_filter(&memo, b) {
                                 if let Some(mosaic) = combine_rectangles_vertically_to_square(a, b)
                                 {
                                     shard_builder.insert(mosaic);
+                                    progress.record_insert(pending);
                                 }
                             }
                         }
@@ -432,14 +841,97 @@ pub fn build_rectangular_centers<
         centers,
         |a| Some(min_rotated_tile(a)),
         centers,
-        |&a_min_rotated_tile, b| {
-            let min_rotated_tile = a_min_rotated_tile.min(min_rotated_tile(b));
-            min_rotated_tile.rotation == Rotation::Identity
-                || min_rotated_tile.rotation == Rotation::QuarterTurnLeft
-        },
+        is_canonical_rectangular_center,
+    )
+}
+
+/// Like [`build_rectangular_centers`], but also returns [`FanOutStats`] for the
+/// join.
+pub fn build_rectangular_centers_instrumented<
+    const SHORT: usize,
+    const LONG: usize,
+    B: SetBuilder<Item = ArrayMosaic<LONG, SHORT>>,
+    CenterMosaic: SquareMosaic<SHORT> + Send + Sync,
+>(
+    set_builder: B,
+    centers: &SquareMosaicSet<SHORT, CenterMosaic>,
+) -> (B::Result, FanOutStats) {
+    build_rectangles_memo_instrumented(
+        set_builder,
+        centers,
+        |a| Some(min_rotated_tile(a)),
+        centers,
+        is_canonical_rectangular_center,
     )
 }
 
+/// Keeps a rectangular center whose lowest-numbered tile, across the `a` half
+/// (already reduced to `a_min_rotated_tile`) and `b`, is in canonical
+/// orientation or a quarter turn left of it.
+fn is_canonical_rectangular_center<const SHORT: usize, CenterMosaic: SquareMosaic<SHORT>>(
+    &a_min_rotated_tile: &RotatedTile,
+    b: RotatedSquareMosaic<SHORT, CenterMosaic>,
+) -> bool {
+    let min_rotated_tile = a_min_rotated_tile.min(min_rotated_tile(b));
+    min_rotated_tile.rotation == Rotation::Identity
+        || min_rotated_tile.rotation == Rotation::QuarterTurnLeft
+}
+
+/// Returns the number of mosaics [`build_rectangular_corners`] will produce
+/// from 1x1 corners and edges.
+///
+/// A 1x1 corner and a 1x1 edge never share a tile, so this is exact. Larger
+/// corner squares hold edge tiles too, so there's no such count for them.
+pub fn expected_rectangular_corner_count<
+    CornerMosaic: SquareMosaic<1>,
+    EdgeMosaic: SquareMosaic<1>,
+>(
+    corners: &SquareMosaicSet<1, CornerMosaic>,
+    edges: &SquareMosaicSet<1, EdgeMosaic>,
+) -> usize {
+    count_rectangles_upper_bound(
+        corners,
+        |a| a.rotation == Rotation::Identity,
+        edges,
+        |b| b.rotation == Rotation::QuarterTurnRight,
+    )
+}
+
+/// Returns the number of mosaics [`build_rectangular_edges`] will produce
+/// from 1x1 edges and centers.
+///
+/// A 1x1 edge and a 1x1 center never share a tile, so this is exact. Larger
+/// edge squares hold center tiles too, so there's no such count for them.
+pub fn expected_rectangular_edge_count<
+    EdgeMosaic: SquareMosaic<1>,
+    CenterMosaic: SquareMosaic<1>,
+>(
+    edges: &SquareMosaicSet<1, EdgeMosaic>,
+    centers: &SquareMosaicSet<1, CenterMosaic>,
+) -> usize {
+    count_rectangles_upper_bound(
+        edges,
+        |a| a.rotation == Rotation::Identity,
+        centers,
+        |_b| true,
+    )
+}
+
+/// Returns an upper bound on the number of mosaics
+/// [`build_rectangular_centers`] will produce.
+///
+/// There's no closed form here because the two halves can share a tile. Turning
+/// a matching pair a half turn gives another matching pair, and the orientation
+/// filter keeps exactly one of the two, so half the matching pairs is a bound.
+pub fn rectangular_center_count_upper_bound<
+    const SHORT: usize,
+    CenterMosaic: SquareMosaic<SHORT>,
+>(
+    centers: &SquareMosaicSet<SHORT, CenterMosaic>,
+) -> usize {
+    count_rectangles_upper_bound(centers, |_a| true, centers, |_b| true) / 2
+}
+
 /// Builds a square corner mosaic set from smaller rectangular corner and edge
 /// mosaic sets.
 ///
@@ -551,3 +1043,227 @@ pub fn build_square_centers<
         },
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::{Arc, Mutex};
+
+    use std::collections::BTreeSet;
+
+    use crate::set::builder::counting::CountingSetBuilder;
+    use crate::set::builder::in_memory_rectangular_mosaic::InMemoryRectangularMosaicSetBuilder;
+    use crate::set::builder::in_memory_square_mosaic::InMemorySquareMosaicSetBuilder;
+    use crate::set::square::{HashSquareMosaicSet, SquareMosaicSet};
+
+    use super::{
+        build_1x1_sets, build_rectangles_memo_cancellable, build_rectangles_memo_instrumented,
+        build_rectangles_memo_with_progress, build_rectangles_memo_with_threads,
+        build_rectangular_centers, build_rectangular_corners, build_rectangular_edges,
+        build_square_corners, expected_rectangular_corner_count, expected_rectangular_edge_count,
+        rectangular_center_count_upper_bound, FanOutStats, Progress, PROGRESS_INTERVAL,
+    };
+
+    #[test]
+    fn square_builder_produces_either_set_type() {
+        let (corners, edges, centers) = build_1x1_sets();
+        let rectangular_corners = build_rectangular_corners(
+            InMemoryRectangularMosaicSetBuilder::<2, 1, _>::new(),
+            &corners,
+            &edges,
+        );
+        let rectangular_edges = build_rectangular_edges(
+            InMemoryRectangularMosaicSetBuilder::<2, 1, _>::new(),
+            &edges,
+            &centers,
+        );
+
+        let square_corners = build_square_corners(
+            InMemorySquareMosaicSetBuilder::new(),
+            &rectangular_corners,
+            &rectangular_edges,
+        );
+        let hash_square_corners = build_square_corners(
+            InMemorySquareMosaicSetBuilder::<2, _, HashSquareMosaicSet<2, _>>::default(),
+            &rectangular_corners,
+            &rectangular_edges,
+        );
+        assert_ne!(square_corners.len(), 0);
+        assert_eq!(
+            BTreeSet::from_iter(hash_square_corners.iter_mosaics()),
+            BTreeSet::from_iter(square_corners.iter_mosaics()),
+        );
+    }
+
+    #[test]
+    fn rotation_histogram_covers_every_rotation() {
+        let (corners, edges, centers) = build_1x1_sets();
+        let rectangular_corners = build_rectangular_corners(
+            InMemoryRectangularMosaicSetBuilder::<2, 1, _>::new(),
+            &corners,
+            &edges,
+        );
+        let rectangular_edges = build_rectangular_edges(
+            InMemoryRectangularMosaicSetBuilder::<2, 1, _>::new(),
+            &edges,
+            &centers,
+        );
+        let histogram = rectangular_corners.rotation_histogram();
+        assert_eq!(
+            histogram.iter().sum::<usize>(),
+            2 * rectangular_corners.len()
+        );
+
+        // Corner and edge tiles are stored with their exterior sides facing
+        // every direction, so canonical 2x2 corners use every rotation, and
+        // identity doesn't dominate. These counts come from enumerating the
+        // corner, two edges, and center of each 2x2 corner directly.
+        let square_corners = build_square_corners(
+            InMemorySquareMosaicSetBuilder::new(),
+            &rectangular_corners,
+            &rectangular_edges,
+        );
+        let histogram = square_corners.rotation_histogram();
+        assert_eq!(square_corners.len(), 1312);
+        assert_eq!(histogram.iter().sum::<usize>(), 4 * square_corners.len());
+        assert_eq!(histogram, [1286, 1298, 1467, 1197]);
+    }
+
+    #[test]
+    fn progress_reports_running_insert_count() {
+        let (_, _, centers) = build_1x1_sets();
+
+        let reports = Mutex::new(Vec::new());
+        let count = build_rectangles_memo_with_progress(
+            CountingSetBuilder::<_>::new(),
+            &centers,
+            |_a| Some(()),
+            &centers,
+            |(), _b| true,
+            &|inserted: usize| reports.lock().unwrap().push(inserted),
+        );
+
+        // How many reports there are depends on how the work was split, but
+        // each one is a distinct multiple of the interval.
+        let mut reports = reports.into_inner().unwrap();
+        reports.sort();
+        for (i, inserted) in reports.iter().copied().enumerate() {
+            assert_eq!(inserted, (i + 1) * PROGRESS_INTERVAL);
+            assert!(inserted <= count);
+        }
+    }
+
+    #[test]
+    fn cancelled_build_stops_early() {
+        let (_, _, centers) = build_1x1_sets();
+
+        let cancel = Arc::new(AtomicBool::new(true));
+        let count = build_rectangles_memo_cancellable(
+            CountingSetBuilder::<_>::new(),
+            &centers,
+            |_a| Some(()),
+            &centers,
+            |(), _b| true,
+            &cancel,
+        );
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn thread_count_does_not_change_result() {
+        let (_, _, centers) = build_1x1_sets();
+
+        let count = |threads| {
+            build_rectangles_memo_with_threads(
+                CountingSetBuilder::<_>::new(),
+                &centers,
+                |_a| Some(()),
+                &centers,
+                |(), _b| true,
+                threads,
+            )
+        };
+        let count_1 = count(1);
+        assert!(count_1 > 0);
+        assert_eq!(count(4), count_1);
+    }
+
+    #[test]
+    fn instrumented_build_reports_fan_out() {
+        // Tile 0 is a corner with exterior top and left sides and distinct
+        // right and bottom colors. Its four rotations have three distinct right
+        // edges, and each meets as many left edges as it has rotations.
+        let mut set = SquareMosaicSet::new();
+        set.insert(mosaic![[0]]);
+
+        let (count, fan_out) = build_rectangles_memo_instrumented(
+            CountingSetBuilder::<_>::new(),
+            &set,
+            |_a| Some(()),
+            &set,
+            |(), _b| true,
+        );
+        // A tile can't be joined with itself.
+        assert_eq!(count, 0);
+        assert_eq!(
+            fan_out,
+            FanOutStats {
+                edges: 3,
+                min: 1,
+                max: 2,
+                total: 4,
+                histogram: vec![0, 2, 1],
+            },
+        );
+        assert_eq!(fan_out.mean(), 4.0 / 3.0);
+        assert_eq!(FanOutStats::bucket_range(2), 2..4);
+    }
+
+    #[test]
+    fn progress_reports_every_interval() {
+        let reports = Mutex::new(Vec::new());
+        let callback = |inserted: usize| reports.lock().unwrap().push(inserted);
+        let progress = Progress::new(&callback);
+
+        let mut pending = 0;
+        for _ in 0..2 * PROGRESS_INTERVAL + 1 {
+            progress.record_insert(&mut pending);
+        }
+        assert_eq!(pending, 1);
+        assert_eq!(
+            reports.into_inner().unwrap(),
+            [PROGRESS_INTERVAL, 2 * PROGRESS_INTERVAL],
+        );
+    }
+
+    #[test]
+    fn analytic_2x1_counts() {
+        let (corners, edges, centers) = build_1x1_sets();
+
+        let rectangular_corners = build_rectangular_corners(
+            InMemoryRectangularMosaicSetBuilder::<2, 1, _>::new(),
+            &corners,
+            &edges,
+        );
+        assert_eq!(
+            rectangular_corners.len(),
+            expected_rectangular_corner_count(&corners, &edges),
+        );
+
+        let rectangular_edges = build_rectangular_edges(
+            InMemoryRectangularMosaicSetBuilder::<2, 1, _>::new(),
+            &edges,
+            &centers,
+        );
+        assert_eq!(
+            rectangular_edges.len(),
+            expected_rectangular_edge_count(&edges, &centers),
+        );
+
+        let rectangular_centers = build_rectangular_centers(
+            InMemoryRectangularMosaicSetBuilder::<2, 1, _>::new(),
+            &centers,
+        );
+        assert!(rectangular_centers.len() <= rectangular_center_count_upper_bound(&centers));
+    }
+}
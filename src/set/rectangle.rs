@@ -1,11 +1,13 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{self, Debug, Formatter};
+use std::io::{self, Write};
 
-use strum::IntoEnumIterator;
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
 use crate::edge::ArrayEdge;
-use crate::mosaic::{RectangularMosaic, RotatedRectangularMosaic};
+use crate::mosaic::{ArrayMosaic, RectangularMosaic, RotatedRectangularMosaic};
 use crate::rectangular::{HorizontalSide, RectangularRotation, VerticalSide};
+use crate::set::{assert_distinct_rotations, rotation_histogram};
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct RotatedRectangularMosaicIndex {
@@ -26,6 +28,14 @@ pub struct RectangularMosaicSet<const W: usize, const H: usize, M: RectangularMo
     index_by_rotated_top_edge: BTreeMap<ArrayEdge<W>, BTreeSet<RotatedRectangularMosaicIndex>>,
 }
 
+impl<const W: usize, const H: usize, M: RectangularMosaic<W, H>> Default
+    for RectangularMosaicSet<W, H, M>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<const W: usize, const H: usize, M: RectangularMosaic<W, H>> RectangularMosaicSet<W, H, M> {
     pub fn new() -> Self {
         Self {
@@ -39,49 +49,101 @@ impl<const W: usize, const H: usize, M: RectangularMosaic<W, H>> RectangularMosa
         self.mosaics.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.mosaics.is_empty()
+    }
+
     pub fn iter_mosaics(&self) -> impl Iterator<Item = &M> + '_ {
         self.mosaics.iter()
     }
 
-    fn get(&self, i: RotatedRectangularMosaicIndex) -> RotatedRectangularMosaic<W, H, M> {
+    /// Like [`Self::iter_mosaics`], but ordered by each mosaic's
+    /// [canonical form](RectangularMosaic::rectangular_canonical) rather than
+    /// by insertion, so the order doesn't depend on how a parallel build
+    /// scheduled its inserts.
+    ///
+    /// This sorts every mosaic up front.
+    pub fn iter_mosaics_sorted(&self) -> impl Iterator<Item = &M> + '_ {
+        let mut mosaics = Vec::from_iter(
+            self.mosaics
+                .iter()
+                .map(|mosaic| (mosaic.rectangular_canonical(), mosaic)),
+        );
+        mosaics.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        mosaics.into_iter().map(|(_, mosaic)| mosaic)
+    }
+
+    /// Yields every stored mosaic in both of its rotations.
+    pub fn iter_all_rotations(
+        &self,
+    ) -> impl Iterator<Item = RotatedRectangularMosaic<'_, W, H, M>> + '_ {
+        self.mosaics.iter().flat_map(|mosaic| {
+            RectangularRotation::all().map(|rotation| RotatedRectangularMosaic { mosaic, rotation })
+        })
+    }
+
+    /// Writes each mosaic as a JSON object on its own line.
+    ///
+    /// See [`RectangularMosaic::write_json`] for the object layout.
+    pub fn write_ndjson<O: Write>(&self, mut w: O) -> io::Result<()> {
+        for mosaic in &self.mosaics {
+            mosaic.write_json(w.by_ref())?;
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+
+    fn get(&self, i: RotatedRectangularMosaicIndex) -> RotatedRectangularMosaic<'_, W, H, M> {
         RotatedRectangularMosaic {
             mosaic: &self.mosaics[i.index],
             rotation: i.rotation,
         }
     }
 
+    /// Counts the tiles in each [`Rotation`](crate::Rotation) across every cell of every
+    /// mosaic, indexed by the rotation's primitive value.
+    pub fn rotation_histogram(&self) -> [usize; 4] {
+        rotation_histogram::<W, H, M>(self.mosaics.iter())
+    }
+
+    /// Counts the rotated mosaics presenting each right edge, which is the
+    /// number of candidates a join on that edge considers.
+    pub fn right_edge_key_histogram(&self) -> BTreeMap<ArrayEdge<H>, usize> {
+        self.index_by_rotated_right_edge
+            .iter()
+            .map(|(edge, indices)| (*edge, indices.len()))
+            .collect()
+    }
+
+    /// Panics if either rotation of one mosaic equals either rotation of
+    /// another.
     pub fn assert_distinct(&self) {
-        let all_rotations = BTreeSet::from_iter(
-            RectangularRotation::iter()
-                .map(|rotation| {
-                    self.mosaics
-                        .iter()
-                        .map(move |mosaic| mosaic.with_rectangular_rotation(rotation))
-                })
-                .flatten(),
-        );
-        // If both rotations of a mosaic appear, the set will deduplicate them
-        // and the sizes won't match.
-        assert_eq!(all_rotations.len(), 2 * self.len());
+        assert_distinct_rotations(&RectangularRotation::all(), self.iter_all_rotations());
+    }
+
+    /// Returns the top and right edges of `mosaic` in each of its rotations.
+    ///
+    /// A half turn brings the bottom and left edges to the top and right, so
+    /// one [`RectangularMosaic::all_edges`] call covers both rotations.
+    fn rotated_edges(mosaic: &M) -> [(RectangularRotation, ArrayEdge<W>, ArrayEdge<H>); 2] {
+        let (right, top, left, bottom) = mosaic.all_edges();
+        [
+            (RectangularRotation::Identity, top, right),
+            (RectangularRotation::HalfTurn, bottom, left),
+        ]
     }
 
     pub fn insert(&mut self, mosaic: M) {
         let index = self.mosaics.len();
 
-        for rotation in RectangularRotation::iter() {
+        for (rotation, top_edge, right_edge) in Self::rotated_edges(&mosaic) {
             self.index_by_rotated_right_edge
-                .entry(
-                    (RotatedRectangularMosaic::from(&mosaic) + rotation)
-                        .vertical_edge(VerticalSide::Right),
-                )
+                .entry(right_edge)
                 .or_default()
                 .insert(RotatedRectangularMosaicIndex { index, rotation });
 
             self.index_by_rotated_top_edge
-                .entry(
-                    (RotatedRectangularMosaic::from(&mosaic) + rotation)
-                        .horizontal_edge(HorizontalSide::Top),
-                )
+                .entry(top_edge)
                 .or_default()
                 .insert(RotatedRectangularMosaicIndex { index, rotation });
         }
@@ -89,9 +151,45 @@ impl<const W: usize, const H: usize, M: RectangularMosaic<W, H>> RectangularMosa
         self.mosaics.push(mosaic);
     }
 
+    /// Builds a set holding `mosaics` in order, identical to inserting them one
+    /// at a time.
+    ///
+    /// Edges are computed in parallel, then each index is built on its own
+    /// thread.
+    pub fn from_mosaics_par(mosaics: Vec<M>) -> Self
+    where
+        M: Sync,
+    {
+        let entries: Vec<_> = mosaics
+            .par_iter()
+            .enumerate()
+            .flat_map_iter(|(index, mosaic)| {
+                Self::rotated_edges(mosaic).map(|(rotation, top_edge, right_edge)| {
+                    (
+                        RotatedRectangularMosaicIndex { index, rotation },
+                        top_edge,
+                        right_edge,
+                    )
+                })
+            })
+            .collect();
+
+        let (index_by_rotated_right_edge, index_by_rotated_top_edge) = rayon::join(
+            || build_index(entries.iter().map(|&(i, _, right_edge)| (right_edge, i))),
+            || build_index(entries.iter().map(|&(i, top_edge, _)| (top_edge, i))),
+        );
+
+        Self {
+            mosaics,
+            index_by_rotated_right_edge,
+            index_by_rotated_top_edge,
+        }
+    }
+
+    /// Moves every mosaic from `other` into this set.
     pub fn extend(&mut self, mut other: Self) {
         let base_index = self.mosaics.len();
-        self.mosaics.extend(other.mosaics.drain(..));
+        self.mosaics.append(&mut other.mosaics);
         for (edge, mosaics) in other.index_by_rotated_right_edge {
             let entry = self.index_by_rotated_right_edge.entry(edge).or_default();
             for i in mosaics {
@@ -112,13 +210,61 @@ impl<const W: usize, const H: usize, M: RectangularMosaic<W, H>> RectangularMosa
         }
     }
 
+    fn canonical_forms(&self) -> BTreeSet<ArrayMosaic<W, H>> {
+        BTreeSet::from_iter(
+            self.mosaics
+                .iter()
+                .map(|mosaic| mosaic.rectangular_canonical()),
+        )
+    }
+
+    /// Returns a new set holding the mosaics in this set whose canonical forms
+    /// satisfy `keep`.
+    fn filtered(&self, keep: impl Fn(&ArrayMosaic<W, H>) -> bool) -> Self {
+        let mut result = Self::new();
+        for mosaic in &self.mosaics {
+            if keep(&mosaic.rectangular_canonical()) {
+                result.insert(mosaic.clone());
+            }
+        }
+        result
+    }
+
+    /// Inserts each mosaic from `other` that isn't already in this set.
+    ///
+    /// Mosaics are compared by their
+    /// [canonical forms](RectangularMosaic::rectangular_canonical), so a
+    /// mosaic is skipped if any rotation of it is present.
+    pub fn union(&mut self, other: &Self) {
+        let mut canonical_forms = self.canonical_forms();
+        for mosaic in &other.mosaics {
+            if canonical_forms.insert(mosaic.rectangular_canonical()) {
+                self.insert(mosaic.clone());
+            }
+        }
+    }
+
+    /// Returns the mosaics in this set that are also in `other`, compared as
+    /// in [`Self::union`].
+    pub fn intersection(&self, other: &Self) -> Self {
+        let other_canonical_forms = other.canonical_forms();
+        self.filtered(|canonical| other_canonical_forms.contains(canonical))
+    }
+
+    /// Returns the mosaics in this set that are not in `other`, compared as in
+    /// [`Self::union`].
+    pub fn difference(&self, other: &Self) -> Self {
+        let other_canonical_forms = other.canonical_forms();
+        self.filtered(|canonical| !other_canonical_forms.contains(canonical))
+    }
+
     pub fn iter_by_vertical_edge(
         &self,
         side: VerticalSide,
     ) -> impl Iterator<
         Item = (
             &ArrayEdge<H>,
-            impl Iterator<Item = (usize, RotatedRectangularMosaic<W, H, M>)> + '_,
+            impl Iterator<Item = (usize, RotatedRectangularMosaic<'_, W, H, M>)> + '_,
         ),
     > + '_ {
         self.index_by_rotated_right_edge
@@ -139,7 +285,7 @@ impl<const W: usize, const H: usize, M: RectangularMosaic<W, H>> RectangularMosa
     ) -> impl Iterator<
         Item = (
             &ArrayEdge<W>,
-            impl Iterator<Item = (usize, RotatedRectangularMosaic<W, H, M>)> + '_,
+            impl Iterator<Item = (usize, RotatedRectangularMosaic<'_, W, H, M>)> + '_,
         ),
     > + '_ {
         self.index_by_rotated_top_edge
@@ -154,11 +300,18 @@ impl<const W: usize, const H: usize, M: RectangularMosaic<W, H>> RectangularMosa
             })
     }
 
+    /// Returns the mosaics whose `side` edge is `edge`, rotated so that edge is
+    /// on `side`.
+    ///
+    /// Edges are read clockwise around the mosaic, as in
+    /// [`RectangularMosaic::vertical_edge`]. Only right edges are indexed, so a
+    /// query for [`VerticalSide::Left`] looks up the right edge index and turns
+    /// each result by [`VerticalSide::rotation_from_right`].
     pub fn query_vertical(
         &self,
         side: VerticalSide,
         edge: &ArrayEdge<H>,
-    ) -> impl Iterator<Item = (usize, RotatedRectangularMosaic<W, H, M>)> + '_ {
+    ) -> impl Iterator<Item = (usize, RotatedRectangularMosaic<'_, W, H, M>)> + '_ {
         self.index_by_rotated_right_edge
             .get(edge)
             .into_iter()
@@ -166,28 +319,140 @@ impl<const W: usize, const H: usize, M: RectangularMosaic<W, H>> RectangularMosa
             .map(move |&i| (i.index, self.get(i) + side.rotation_from_right()))
     }
 
+    /// Returns the mosaics whose `side` edge is `edge`, rotated so that edge is
+    /// on `side`.
+    ///
+    /// Edges are read clockwise around the mosaic, as in
+    /// [`RectangularMosaic::horizontal_edge`]. Only top edges are indexed, so a
+    /// query for [`HorizontalSide::Bottom`] looks up the top edge index and
+    /// turns each result by [`HorizontalSide::rotation_from_top`].
     pub fn query_horizontal(
         &self,
         side: HorizontalSide,
         edge: &ArrayEdge<W>,
-    ) -> impl Iterator<Item = (usize, RotatedRectangularMosaic<W, H, M>)> + '_ {
+    ) -> impl Iterator<Item = (usize, RotatedRectangularMosaic<'_, W, H, M>)> + '_ {
         self.index_by_rotated_top_edge
             .get(edge)
             .into_iter()
             .flatten()
             .map(move |&i| (i.index, self.get(i) + side.rotation_from_top()))
     }
+
+    /// Returns the mosaics that fit against a neighbor's opposite vertical
+    /// edge, rotated so their `side` edge abuts it.
+    ///
+    /// `neighbor_edge` is read clockwise around the neighbor, so it's reversed
+    /// before querying. For example, passing a mosaic's right edge with
+    /// [`VerticalSide::Left`] finds the mosaics that can sit to its right.
+    pub fn query_vertical_abutting(
+        &self,
+        side: VerticalSide,
+        neighbor_edge: &ArrayEdge<H>,
+    ) -> impl Iterator<Item = (usize, RotatedRectangularMosaic<'_, W, H, M>)> + '_ {
+        self.query_vertical(side, &neighbor_edge.reversed())
+    }
+
+    /// Returns the mosaics that fit against a neighbor's opposite horizontal
+    /// edge, rotated so their `side` edge abuts it.
+    ///
+    /// `neighbor_edge` is read clockwise around the neighbor, so it's reversed
+    /// before querying. For example, passing a mosaic's bottom edge with
+    /// [`HorizontalSide::Top`] finds the mosaics that can sit below it.
+    pub fn query_horizontal_abutting(
+        &self,
+        side: HorizontalSide,
+        neighbor_edge: &ArrayEdge<W>,
+    ) -> impl Iterator<Item = (usize, RotatedRectangularMosaic<'_, W, H, M>)> + '_ {
+        self.query_horizontal(side, &neighbor_edge.reversed())
+    }
+}
+
+fn build_index<K: Ord>(
+    entries: impl Iterator<Item = (K, RotatedRectangularMosaicIndex)>,
+) -> BTreeMap<K, BTreeSet<RotatedRectangularMosaicIndex>> {
+    let mut index: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+    for (key, i) in entries {
+        index.entry(key).or_default().insert(i);
+    }
+    index
 }
 
 #[cfg(test)]
 mod tests {
     use std::collections::{BTreeMap, BTreeSet};
+    use std::time::Instant;
 
     use crate::edge::ArrayEdge;
+    use crate::mosaic::{ArrayMosaic, RectangularMosaic};
     use crate::rectangular::{HorizontalSide, RectangularRotation, VerticalSide};
+    use crate::RotatedTile;
 
     use super::RectangularMosaicSet;
 
+    /// Returns `count` mosaics of pseudorandom rotated tiles, with repeats.
+    fn synthetic_mosaics(count: usize) -> Vec<ArrayMosaic<3, 2>> {
+        let rotated_tiles = Vec::from_iter(RotatedTile::values());
+        let mut state = 1u64;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            rotated_tiles[(state >> 54) as usize]
+        };
+        Vec::from_iter((0..count).map(|_| ArrayMosaic {
+            tiles: std::array::from_fn(|_| std::array::from_fn(|_| next())),
+        }))
+    }
+
+    fn sequential(mosaics: &[ArrayMosaic<3, 2>]) -> RectangularMosaicSet<3, 2, ArrayMosaic<3, 2>> {
+        let mut set = RectangularMosaicSet::new();
+        for &mosaic in mosaics {
+            set.insert(mosaic);
+        }
+        set
+    }
+
+    #[test]
+    fn from_mosaics_par_matches_sequential() {
+        let mosaics = synthetic_mosaics(1000);
+        let expected = sequential(&mosaics);
+        let actual = RectangularMosaicSet::from_mosaics_par(mosaics);
+        assert_eq!(actual.mosaics, expected.mosaics);
+        assert_eq!(
+            actual.index_by_rotated_right_edge,
+            expected.index_by_rotated_right_edge,
+        );
+        assert_eq!(
+            actual.index_by_rotated_top_edge,
+            expected.index_by_rotated_top_edge,
+        );
+    }
+
+    /// Compares [`RectangularMosaicSet::from_mosaics_par`] to sequential
+    /// inserts. Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_from_mosaics_par() {
+        let mosaics = synthetic_mosaics(100_000);
+
+        let start = Instant::now();
+        let expected = sequential(&mosaics);
+        let sequential_time = start.elapsed();
+
+        let start = Instant::now();
+        let actual = RectangularMosaicSet::from_mosaics_par(mosaics);
+        let parallel_time = start.elapsed();
+
+        assert_eq!(
+            actual.index_by_rotated_right_edge,
+            expected.index_by_rotated_right_edge,
+        );
+        println!(
+            "sequential {sequential_time:?}, parallel {parallel_time:?}, speedup {:.2}x",
+            sequential_time.as_secs_f64() / parallel_time.as_secs_f64(),
+        );
+    }
+
     #[test]
     fn iter_by_edge() {
         let mosaic = mosaic![[0, 1, 2], [16, 17, 18]];
@@ -213,6 +478,10 @@ mod tests {
                     .map(|(edge, mosaics)| (*edge, BTreeSet::from_iter(mosaics))),
             )
         };
+        assert_eq!(
+            set.right_edge_key_histogram(),
+            btree_map![edge_aa => 1, edge_bv => 1],
+        );
         assert_eq!(
             iter_by_vertical_edge_as_btree(VerticalSide::Right),
             btree_map![
@@ -291,4 +560,164 @@ mod tests {
             btree_set![(0, identity)],
         );
     }
+
+    #[test]
+    fn write_ndjson() {
+        let mut set = RectangularMosaicSet::new();
+        set.insert(mosaic![[0, 1 HalfTurn], [16, 17]]);
+        set.insert(mosaic![[2, 3], [18 QuarterTurnLeft, 19]]);
+
+        let mut output = Vec::new();
+        set.write_ndjson(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!(output.lines().count(), set.len());
+        for line in output.lines() {
+            serde_json::from_str::<serde_json::Value>(line).unwrap();
+        }
+        assert_eq!(
+            output.lines().next().unwrap(),
+            r#"{"tiles":[[0,1],[16,17]],"rotations":[[0,2],[0,0]]}"#,
+        );
+    }
+
+    #[test]
+    fn union_intersection_difference() {
+        let shared = mosaic![[2, 3], [18, 19]];
+        let mut a = RectangularMosaicSet::new();
+        a.insert(mosaic![[0, 1], [16, 17]]);
+        a.insert(shared);
+        let mut b = RectangularMosaicSet::new();
+        b.insert(
+            shared
+                .with_rectangular_rotation(RectangularRotation::HalfTurn)
+                .to_array_mosaic(),
+        );
+        b.insert(mosaic![[4, 5], [20, 21]]);
+
+        let intersection = a.intersection(&b);
+        assert_eq!(
+            Vec::from_iter(intersection.iter_mosaics().copied()),
+            [shared],
+        );
+        let difference = a.difference(&b);
+        assert_eq!(
+            Vec::from_iter(difference.iter_mosaics().copied()),
+            [mosaic![[0, 1], [16, 17]]],
+        );
+
+        let canonical_forms = BTreeSet::from_iter(
+            a.iter_mosaics()
+                .chain(b.iter_mosaics())
+                .map(|mosaic| mosaic.rectangular_canonical()),
+        );
+        let mut union = a.clone();
+        union.union(&b);
+        assert_eq!(union.len(), canonical_forms.len());
+        assert_eq!(union.len(), 3);
+        union.assert_distinct();
+
+        // The edge indices cover the mosaic added from `b`.
+        let added = mosaic![[4, 5], [20, 21]];
+        let top_edge = added.horizontal_edge(HorizontalSide::Top);
+        assert!(union
+            .query_horizontal(HorizontalSide::Top, &top_edge)
+            .any(|(_, mosaic)| mosaic.to_array_mosaic() == added));
+        let left_edge = added.vertical_edge(VerticalSide::Left);
+        assert!(union
+            .query_vertical(VerticalSide::Left, &left_edge)
+            .any(|(_, mosaic)| mosaic.to_array_mosaic() == added));
+    }
+
+    #[test]
+    fn query_all_sides() {
+        let mosaic = mosaic![[0, 1, 2], [16, 17, 18]];
+        let mut set = RectangularMosaicSet::new();
+        set.insert(mosaic);
+
+        for side in [VerticalSide::Right, VerticalSide::Left] {
+            for edge in [
+                mosaic.vertical_edge(side),
+                mosaic.vertical_edge(side).reversed(),
+            ] {
+                // Querying the left side is the same as querying the right side
+                // and turning the results around.
+                let manual = BTreeSet::from_iter(
+                    set.query_vertical(VerticalSide::Right, &edge)
+                        .map(|(i, m)| (i, m + side.rotation_from_right())),
+                );
+                assert_eq!(BTreeSet::from_iter(set.query_vertical(side, &edge)), manual);
+                assert_eq!(
+                    BTreeSet::from_iter(set.query_vertical_abutting(side, &edge.reversed())),
+                    manual,
+                );
+            }
+            // The queried edge ends up on the requested side.
+            for (_, m) in set.query_vertical(side, &mosaic.vertical_edge(side)) {
+                assert_eq!(m.vertical_edge(side), mosaic.vertical_edge(side));
+            }
+        }
+        for side in [HorizontalSide::Top, HorizontalSide::Bottom] {
+            for edge in [
+                mosaic.horizontal_edge(side),
+                mosaic.horizontal_edge(side).reversed(),
+            ] {
+                let manual = BTreeSet::from_iter(
+                    set.query_horizontal(HorizontalSide::Top, &edge)
+                        .map(|(i, m)| (i, m + side.rotation_from_top())),
+                );
+                assert_eq!(
+                    BTreeSet::from_iter(set.query_horizontal(side, &edge)),
+                    manual,
+                );
+                assert_eq!(
+                    BTreeSet::from_iter(set.query_horizontal_abutting(side, &edge.reversed())),
+                    manual,
+                );
+            }
+            for (_, m) in set.query_horizontal(side, &mosaic.horizontal_edge(side)) {
+                assert_eq!(m.horizontal_edge(side), mosaic.horizontal_edge(side));
+            }
+        }
+    }
+
+    #[test]
+    fn is_empty() {
+        let mut set = RectangularMosaicSet::default();
+        assert!(set.is_empty());
+        set.insert(mosaic![[0, 1, 2], [16, 17, 18]]);
+        assert!(!set.is_empty());
+    }
+
+    #[test]
+    fn iter_mosaics_sorted_ignores_insertion_order() {
+        let mosaics = [
+            mosaic![[3, 4, 5], [19, 20, 21]],
+            mosaic![[18, 17, 16], [2, 1, 0]],
+            mosaic![[6, 7, 8], [22, 23, 24]],
+        ];
+        let mut forward = RectangularMosaicSet::new();
+        for mosaic in mosaics {
+            forward.insert(mosaic);
+        }
+        let mut backward = RectangularMosaicSet::new();
+        for mosaic in mosaics.into_iter().rev() {
+            backward.insert(mosaic);
+        }
+
+        let sorted = Vec::from_iter(forward.iter_mosaics_sorted());
+        assert_eq!(sorted, Vec::from_iter(backward.iter_mosaics_sorted()));
+        assert_eq!(sorted, [&mosaics[1], &mosaics[0], &mosaics[2]]);
+    }
+
+    #[test]
+    fn iter_all_rotations() {
+        let mut set = RectangularMosaicSet::new();
+        set.insert(mosaic![[0, 1, 2], [16, 17, 18]]);
+        set.insert(mosaic![[3, 4, 5], [19, 20, 21]]);
+
+        let rotations = Vec::from_iter(set.iter_all_rotations());
+        assert_eq!(rotations.len(), 2 * set.len());
+        assert_eq!(BTreeSet::from_iter(&rotations).len(), rotations.len());
+    }
 }
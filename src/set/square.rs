@@ -1,14 +1,22 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{btree_map, hash_map, BTreeMap, BTreeSet, HashMap};
 use std::fmt::{self, Debug, Formatter};
+use std::iter::Map;
 
 use strum::IntoEnumIterator;
 
 use crate::edge::ArrayEdge;
-use crate::mosaic::{RotatedSquareMosaic, SquareMosaic};
-use crate::{Rotation, Side};
+use crate::mosaic::{
+    bucas_url, to_bucas_board_edges, ArrayMosaic, RectangularMosaic, RotatedSquareMosaic,
+    SquareMosaic,
+};
+use crate::rectangular::Reflection;
+use crate::set::{assert_distinct_rotations, rotation_histogram};
+use crate::{is_clue_tile, Rotation, Side, Tile};
 
+/// One rotation of one mosaic in a [`SquareMosaicSet`], as listed in its
+/// [`SquareEdgeIndex`].
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct RotatedSquareMosaicIndex {
+pub struct RotatedSquareMosaicIndex {
     index: usize,
     rotation: Rotation,
 }
@@ -19,74 +27,261 @@ impl Debug for RotatedSquareMosaicIndex {
     }
 }
 
+/// Yields each of `mosaics` in each of its four rotations, in order.
+fn all_rotations<const N: usize, M: SquareMosaic<N>>(
+    mosaics: &[M],
+) -> impl Iterator<Item = RotatedSquareMosaic<'_, N, M>> + '_ {
+    mosaics.iter().flat_map(|mosaic| {
+        Rotation::iter().map(move |rotation| RotatedSquareMosaic { mosaic, rotation })
+    })
+}
+
+/// The index a [`SquareMosaicSet`] keeps from each rotated right edge to the
+/// mosaics with that edge, listed in insertion order.
+pub trait SquareEdgeIndex<const N: usize>: Default + Send {
+    type Iter<'a>: Iterator<Item = (ArrayEdge<N>, &'a [RotatedSquareMosaicIndex])>
+    where
+        Self: 'a;
+
+    /// Returns the list for `edge`, adding an empty one if there isn't one.
+    fn entry(&mut self, edge: &ArrayEdge<N>) -> &mut Vec<RotatedSquareMosaicIndex>;
+
+    /// Returns the list for `edge`, which is empty if there isn't one.
+    fn get(&self, edge: &ArrayEdge<N>) -> &[RotatedSquareMosaicIndex];
+
+    /// Yields every edge with its list.
+    fn iter(&self) -> Self::Iter<'_>;
+}
+
+type EdgeEntry<'a, K> = (&'a K, &'a Vec<RotatedSquareMosaicIndex>);
+type UnpackedEdgeEntry<'a, const N: usize> = (ArrayEdge<N>, &'a [RotatedSquareMosaicIndex]);
+
+fn unpacked_entry<const N: usize>(
+    (&edge, mosaics): EdgeEntry<'_, ArrayEdge<N>>,
+) -> UnpackedEdgeEntry<'_, N> {
+    (edge, mosaics.as_slice())
+}
+
+fn packed_entry<const N: usize>((&edge, mosaics): EdgeEntry<'_, u64>) -> UnpackedEdgeEntry<'_, N> {
+    (ArrayEdge::from_packed(edge), mosaics.as_slice())
+}
+
+/// A [`SquareEdgeIndex`] keyed on edges, iterated in edge order. This is the
+/// default for [`SquareMosaicSet`].
+#[derive(Clone, Debug, Default)]
+pub struct BTreeEdgeIndex<const N: usize>(BTreeMap<ArrayEdge<N>, Vec<RotatedSquareMosaicIndex>>);
+
+impl<const N: usize> SquareEdgeIndex<N> for BTreeEdgeIndex<N> {
+    type Iter<'a> = Map<
+        btree_map::Iter<'a, ArrayEdge<N>, Vec<RotatedSquareMosaicIndex>>,
+        fn(EdgeEntry<'a, ArrayEdge<N>>) -> UnpackedEdgeEntry<'a, N>,
+    >;
+
+    fn entry(&mut self, edge: &ArrayEdge<N>) -> &mut Vec<RotatedSquareMosaicIndex> {
+        self.0.entry(*edge).or_default()
+    }
+
+    fn get(&self, edge: &ArrayEdge<N>) -> &[RotatedSquareMosaicIndex] {
+        self.0.get(edge).map_or(&[], Vec::as_slice)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.0.iter().map(unpacked_entry::<N> as fn(_) -> _)
+    }
+}
+
+/// A [`SquareEdgeIndex`] keyed on [packed](ArrayEdge::to_packed) edges, which
+/// are cheaper to compare, iterated in edge order.
+///
+/// Inserting panics for `N > 12`.
+#[derive(Clone, Debug, Default)]
+pub struct PackedEdgeIndex<const N: usize>(BTreeMap<u64, Vec<RotatedSquareMosaicIndex>>);
+
+impl<const N: usize> SquareEdgeIndex<N> for PackedEdgeIndex<N> {
+    type Iter<'a> = Map<
+        btree_map::Iter<'a, u64, Vec<RotatedSquareMosaicIndex>>,
+        fn(EdgeEntry<'a, u64>) -> UnpackedEdgeEntry<'a, N>,
+    >;
+
+    fn entry(&mut self, edge: &ArrayEdge<N>) -> &mut Vec<RotatedSquareMosaicIndex> {
+        self.0.entry(edge.to_packed()).or_default()
+    }
+
+    fn get(&self, edge: &ArrayEdge<N>) -> &[RotatedSquareMosaicIndex] {
+        self.0.get(&edge.to_packed()).map_or(&[], Vec::as_slice)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.0.iter().map(packed_entry::<N> as fn(_) -> _)
+    }
+}
+
+/// A [`SquareEdgeIndex`] that hashes [packed](ArrayEdge::to_packed) edges, for
+/// faster inserts into large sets.
+///
+/// Iteration order over edges is unspecified. Inserting panics for `N > 12`.
+#[derive(Clone, Debug, Default)]
+pub struct HashEdgeIndex<const N: usize>(HashMap<u64, Vec<RotatedSquareMosaicIndex>>);
+
+impl<const N: usize> SquareEdgeIndex<N> for HashEdgeIndex<N> {
+    type Iter<'a> = Map<
+        hash_map::Iter<'a, u64, Vec<RotatedSquareMosaicIndex>>,
+        fn(EdgeEntry<'a, u64>) -> UnpackedEdgeEntry<'a, N>,
+    >;
+
+    fn entry(&mut self, edge: &ArrayEdge<N>) -> &mut Vec<RotatedSquareMosaicIndex> {
+        self.0.entry(edge.to_packed()).or_default()
+    }
+
+    fn get(&self, edge: &ArrayEdge<N>) -> &[RotatedSquareMosaicIndex] {
+        self.0.get(&edge.to_packed()).map_or(&[], Vec::as_slice)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.0.iter().map(packed_entry::<N> as fn(_) -> _)
+    }
+}
+
+/// A set of square mosaics, indexed by the edge on each side of each rotation.
+///
+/// `I` selects how that index is stored. [`Self::new`] uses a
+/// [`BTreeEdgeIndex`]. Use [`Default`] for the others.
 #[derive(Clone, Debug)]
-pub struct SquareMosaicSet<const N: usize, M: SquareMosaic<N>> {
+pub struct SquareMosaicSet<
+    const N: usize,
+    M: SquareMosaic<N>,
+    I: SquareEdgeIndex<N> = BTreeEdgeIndex<N>,
+> {
     mosaics: Vec<M>,
-    index_by_rotated_right_edge: BTreeMap<ArrayEdge<N>, BTreeSet<RotatedSquareMosaicIndex>>,
+    index_by_rotated_right_edge: I,
+    /// The canonical forms of the stored mosaics, kept only by
+    /// [`Self::new_with_reflection_dedup`].
+    canonical_mosaics: Option<BTreeSet<ArrayMosaic<N, N>>>,
+}
+
+/// A [`SquareMosaicSet`] whose edge index is keyed on packed edges.
+pub type PackedSquareMosaicSet<const N: usize, M> = SquareMosaicSet<N, M, PackedEdgeIndex<N>>;
+
+/// A [`SquareMosaicSet`] whose edge index hashes packed edges.
+pub type HashSquareMosaicSet<const N: usize, M> = SquareMosaicSet<N, M, HashEdgeIndex<N>>;
+
+impl<const N: usize, M: SquareMosaic<N>, I: SquareEdgeIndex<N>> Default
+    for SquareMosaicSet<N, M, I>
+{
+    fn default() -> Self {
+        Self {
+            mosaics: Vec::new(),
+            index_by_rotated_right_edge: I::default(),
+            canonical_mosaics: None,
+        }
+    }
 }
 
 impl<const N: usize, M: SquareMosaic<N>> SquareMosaicSet<N, M> {
     pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a set that skips inserting a mosaic if its
+    /// [reflection](Reflection) is already present in any rotation.
+    ///
+    /// Reflection isn't a physical operation, so such a set holds one mosaic
+    /// per symmetry class. It's only meaningful for counting those classes, not
+    /// for building boards.
+    pub fn new_with_reflection_dedup() -> Self {
         Self {
-            mosaics: Vec::new(),
-            index_by_rotated_right_edge: BTreeMap::new(),
+            canonical_mosaics: Some(BTreeSet::new()),
+            ..Self::default()
         }
     }
+}
 
+impl<const N: usize, M: SquareMosaic<N>, I: SquareEdgeIndex<N>> SquareMosaicSet<N, M, I> {
     pub fn len(&self) -> usize {
         self.mosaics.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.mosaics.is_empty()
+    }
+
     pub fn iter_mosaics(&self) -> impl Iterator<Item = &M> + '_ {
         self.mosaics.iter()
     }
 
-    fn get(&self, i: RotatedSquareMosaicIndex) -> RotatedSquareMosaic<N, M> {
+    /// Yields every stored mosaic in each of its four rotations.
+    pub fn iter_all_rotations(&self) -> impl Iterator<Item = RotatedSquareMosaic<'_, N, M>> + '_ {
+        all_rotations(&self.mosaics)
+    }
+
+    fn get(&self, i: RotatedSquareMosaicIndex) -> RotatedSquareMosaic<'_, N, M> {
         RotatedSquareMosaic {
             mosaic: &self.mosaics[i.index],
             rotation: i.rotation,
         }
     }
 
+    /// Counts the tiles in each [`Rotation`] across every cell of every
+    /// mosaic, indexed by the rotation's primitive value.
+    pub fn rotation_histogram(&self) -> [usize; 4] {
+        rotation_histogram::<N, N, M>(self.mosaics.iter())
+    }
+
+    /// Returns the index of the first stored mosaic that places a clue tile,
+    /// along with that tile, for sets that are meant to exclude the clues.
+    pub fn contains_clue_violation(&self) -> Option<(usize, Tile)> {
+        self.mosaics.iter().enumerate().find_map(|(index, mosaic)| {
+            (0..N)
+                .flat_map(|y| (0..N).map(move |x| mosaic.get(x, y).tile))
+                .find(|&tile| is_clue_tile(tile))
+                .map(|tile| (index, tile))
+        })
+    }
+
+    /// Panics if any rotation of one mosaic equals any rotation of another.
     pub fn assert_distinct(&self) {
-        let all_rotations = BTreeSet::from_iter(
-            Rotation::iter()
-                .map(|rotation| {
-                    self.mosaics
-                        .iter()
-                        .map(move |mosaic| mosaic.with_square_rotation(rotation))
-                })
-                .flatten(),
-        );
-        // If any two rotations of a mosaic appear, the set will deduplicate
-        // them and the sizes won't match.
-        assert_eq!(all_rotations.len(), 4 * self.len());
+        assert_distinct_rotations(&Rotation::QUARTER_TURNS, self.iter_all_rotations());
     }
 
     pub fn insert(&mut self, mosaic: M) {
+        if let Some(canonical_mosaics) = &mut self.canonical_mosaics {
+            let reflected = mosaic.with_reflection(Reflection::Horizontal).canonical();
+            if canonical_mosaics.contains(&reflected) {
+                return;
+            }
+            canonical_mosaics.insert(mosaic.canonical());
+        }
+
         let index = self.mosaics.len();
 
         for rotation in Rotation::iter() {
             self.index_by_rotated_right_edge
-                .entry(mosaic.with_square_rotation(rotation).edge(Side::Right))
-                .or_default()
-                .insert(RotatedSquareMosaicIndex { index, rotation });
+                .entry(&mosaic.with_square_rotation(rotation).edge(Side::Right))
+                .push(RotatedSquareMosaicIndex { index, rotation });
         }
 
         self.mosaics.push(mosaic);
     }
 
+    /// Moves every mosaic from `other` into this set. With reflection dedup,
+    /// they're inserted one at a time so that each is checked.
     pub fn extend(&mut self, mut other: Self) {
+        if self.canonical_mosaics.is_some() {
+            for mosaic in other.mosaics {
+                self.insert(mosaic);
+            }
+            return;
+        }
+
         let base_index = self.mosaics.len();
-        self.mosaics.extend(other.mosaics.drain(..));
-        for (edge, mosaics) in other.index_by_rotated_right_edge {
-            let entry = self.index_by_rotated_right_edge.entry(edge).or_default();
-            for i in mosaics {
-                entry.insert(RotatedSquareMosaicIndex {
+        self.mosaics.append(&mut other.mosaics);
+        for (edge, mosaics) in other.index_by_rotated_right_edge.iter() {
+            self.index_by_rotated_right_edge
+                .entry(&edge)
+                .extend(mosaics.iter().map(|i| RotatedSquareMosaicIndex {
                     index: i.index + base_index,
                     rotation: i.rotation,
-                });
-            }
+                }));
         }
     }
 
@@ -95,8 +290,8 @@ impl<const N: usize, M: SquareMosaic<N>> SquareMosaicSet<N, M> {
         side: Side,
     ) -> impl Iterator<
         Item = (
-            &ArrayEdge<N>,
-            impl Iterator<Item = (usize, RotatedSquareMosaic<N, M>)> + '_,
+            ArrayEdge<N>,
+            impl Iterator<Item = (usize, RotatedSquareMosaic<'_, N, M>)> + '_,
         ),
     > + '_ {
         self.index_by_rotated_right_edge
@@ -115,23 +310,175 @@ impl<const N: usize, M: SquareMosaic<N>> SquareMosaicSet<N, M> {
         &self,
         side: Side,
         edge: &ArrayEdge<N>,
-    ) -> impl Iterator<Item = (usize, RotatedSquareMosaic<N, M>)> + '_ {
+    ) -> impl Iterator<Item = (usize, RotatedSquareMosaic<'_, N, M>)> + '_ {
         self.index_by_rotated_right_edge
             .get(edge)
-            .into_iter()
-            .flatten()
+            .iter()
             .map(move |&i| (i.index, self.get(i) + side.rotation_from_right()))
     }
 }
 
+impl<M: SquareMosaic<16>, I: SquareEdgeIndex<16>> SquareMosaicSet<16, M, I> {
+    /// Returns a bucas.name URL for each stored board, in insertion order, in
+    /// the same format as `translate_to_url`.
+    ///
+    /// This doesn't check the boards; boards built by joining matching edges
+    /// are solutions by construction, and
+    /// [`is_valid`](RectangularMosaic::is_valid) checks any others.
+    pub fn iter_solutions_as_urls(&self) -> impl Iterator<Item = String> + '_ {
+        self.mosaics
+            .iter()
+            .map(|mosaic| bucas_url(&to_bucas_board_edges(mosaic)))
+    }
+}
+
+/// A square mosaic set that
+/// [`InMemorySquareMosaicSetBuilder`](crate::set::builder::in_memory_square_mosaic::InMemorySquareMosaicSetBuilder)
+/// can produce.
+pub trait SquareMosaicStore<const N: usize, M: SquareMosaic<N>>: Default + Send {
+    fn insert(&mut self, mosaic: M);
+    fn extend(&mut self, other: Self);
+}
+
+impl<const N: usize, M: SquareMosaic<N> + Send, I: SquareEdgeIndex<N>> SquareMosaicStore<N, M>
+    for SquareMosaicSet<N, M, I>
+{
+    fn insert(&mut self, mosaic: M) {
+        SquareMosaicSet::insert(self, mosaic)
+    }
+
+    fn extend(&mut self, other: Self) {
+        SquareMosaicSet::extend(self, other)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::{BTreeMap, BTreeSet};
 
+    use strum::IntoEnumIterator;
+
     use crate::edge::ArrayEdge;
-    use crate::{Rotation, Side};
+    use crate::mosaic::{
+        from_bucas_board_edges, to_bucas_board_edges, ArrayMosaic, RotatedSquareMosaic,
+    };
+    use crate::set::build_1x1_sets_with_clues;
+    use crate::{RotatedTile, Rotation, Side, Tile};
+
+    use super::{HashSquareMosaicSet, PackedSquareMosaicSet, SquareMosaicSet};
+
+    #[test]
+    fn reflection_dedup_skips_mirror_images() {
+        // The second mosaic is the first with its grid mirrored, and the third
+        // is a rotation of the second.
+        let mosaics = [
+            mosaic![[0, 1], [16, 17]],
+            mosaic![[1, 0], [17, 16]],
+            mosaic![[16 HalfTurn, 17 HalfTurn], [0 HalfTurn, 1 HalfTurn]],
+            mosaic![[0, 2], [16, 17]],
+        ];
+
+        let mut set = SquareMosaicSet::new();
+        let mut deduped = SquareMosaicSet::new_with_reflection_dedup();
+        for mosaic in mosaics {
+            set.insert(mosaic);
+            deduped.insert(mosaic);
+        }
+        assert_eq!(set.len(), 4);
+        assert_eq!(deduped.len(), 2);
+        assert_eq!(
+            Vec::from_iter(deduped.iter_mosaics()),
+            [&mosaics[0], &mosaics[3]],
+        );
+    }
+
+    #[test]
+    fn iter_solutions_as_urls_matches_translate_to_url() {
+        let mut mosaic = ArrayMosaic {
+            tiles: [[RotatedTile::ZERO; 16]; 16],
+        };
+        for y in 0..16 {
+            for x in 0..16 {
+                mosaic.tiles[y][x] = RotatedTile {
+                    tile: Tile::from_primitive((16 * y + x) as u8),
+                    rotation: Rotation::new_masked((x + y) as u8),
+                };
+            }
+        }
+        let mut set = SquareMosaicSet::new();
+        set.insert(mosaic);
+
+        let board_edges = to_bucas_board_edges(&mosaic);
+
+        let urls: Vec<_> = set.iter_solutions_as_urls().collect();
+        assert_eq!(
+            urls,
+            [format!(
+                "https://e2.bucas.name/#board_w=16&board_h=16&board_edges={board_edges}&motifs_order=jblackwood",
+            )],
+        );
+        let parsed = urls[0]
+            .split_once("board_edges=")
+            .and_then(|(_, rest)| rest.split_once('&'))
+            .map(|(board_edges, _)| from_bucas_board_edges(board_edges));
+        assert_eq!(parsed, Some(Ok(mosaic)));
+    }
+
+    #[test]
+    #[should_panic(expected = "mosaics (0, HalfTurn) and (1, Identity) coincide")]
+    fn assert_distinct_reports_collision() {
+        let mut set = SquareMosaicSet::new();
+        set.insert(mosaic![[0, 1], [16, 17]]);
+        set.insert(mosaic![[17 HalfTurn, 16 HalfTurn], [1 HalfTurn, 0 HalfTurn]]);
+        set.assert_distinct();
+    }
+
+    #[test]
+    fn contains_clue_violation() {
+        let (corners, edges, centers, c3, ..) = build_1x1_sets_with_clues();
+        assert_eq!(corners.contains_clue_violation(), None);
+        assert_eq!(edges.contains_clue_violation(), None);
+        assert_eq!(centers.contains_clue_violation(), None);
+        assert!(c3.contains_clue_violation().is_some());
+
+        let mut set = SquareMosaicSet::new();
+        set.insert(mosaic![[0, 1], [16, 17]]);
+        set.insert(mosaic![[2, 3], [76, 18]]);
+        assert_eq!(
+            set.contains_clue_violation(),
+            Some((1, Tile::from_primitive(76))),
+        );
+    }
+
+    #[test]
+    fn is_empty() {
+        let mut set = SquareMosaicSet::<2, _>::default();
+        assert!(set.is_empty());
+        set.insert(mosaic![[0, 1], [16, 17]]);
+        assert!(!set.is_empty());
+
+        let mut hash_set = HashSquareMosaicSet::default();
+        assert!(hash_set.is_empty());
+        hash_set.insert(mosaic![[0, 1], [16, 17]]);
+        assert!(!hash_set.is_empty());
+    }
 
-    use super::SquareMosaicSet;
+    #[test]
+    fn iter_all_rotations() {
+        let mut set = SquareMosaicSet::new();
+        set.insert(mosaic![[0, 1], [16, 17]]);
+        set.insert(mosaic![[2, 3], [18, 19]]);
+
+        let rotations = Vec::from_iter(set.iter_all_rotations());
+        assert_eq!(rotations.len(), 4 * set.len());
+        assert_eq!(BTreeSet::from_iter(&rotations).len(), rotations.len());
+        for rotation in Rotation::iter() {
+            assert!(rotations.contains(&RotatedSquareMosaic {
+                mosaic: &mosaic![[2, 3], [18, 19]],
+                rotation,
+            }));
+        }
+    }
 
     #[test]
     fn iter_by_edge() {
@@ -150,7 +497,7 @@ mod tests {
         let iter_by_edge_as_btree = |side| {
             BTreeMap::from_iter(
                 set.iter_by_edge(side)
-                    .map(|(edge, mosaics)| (*edge, BTreeSet::from_iter(mosaics))),
+                    .map(|(edge, mosaics)| (edge, BTreeSet::from_iter(mosaics))),
             )
         };
         assert_eq!(
@@ -263,4 +610,75 @@ mod tests {
             btree_set![(0, identity)],
         );
     }
+
+    #[test]
+    fn packed_matches_unpacked() {
+        let mut set = SquareMosaicSet::new();
+        let mut packed_set = PackedSquareMosaicSet::default();
+        for mosaic in [
+            mosaic![[0, 1], [16, 17]],
+            mosaic![[2, 3], [18, 19]],
+            mosaic![[1, 2], [17, 18]],
+        ] {
+            set.insert(mosaic);
+            packed_set.insert(mosaic);
+        }
+        assert_eq!(packed_set.len(), set.len());
+
+        for side in Side::iter() {
+            let by_edge = BTreeMap::from_iter(
+                set.iter_by_edge(side)
+                    .map(|(edge, mosaics)| (edge, BTreeSet::from_iter(mosaics))),
+            );
+            let packed_by_edge = BTreeMap::from_iter(
+                packed_set
+                    .iter_by_edge(side)
+                    .map(|(edge, mosaics)| (edge, BTreeSet::from_iter(mosaics))),
+            );
+            assert_eq!(packed_by_edge, by_edge);
+
+            for edge in by_edge.keys() {
+                assert_eq!(
+                    BTreeSet::from_iter(packed_set.query(side, edge)),
+                    BTreeSet::from_iter(set.query(side, edge)),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn hash_matches_btree() {
+        let mut set = SquareMosaicSet::new();
+        let mut hash_set = HashSquareMosaicSet::default();
+        for mosaic in [
+            mosaic![[0, 1], [16, 17]],
+            mosaic![[2, 3], [18, 19]],
+            mosaic![[1, 2], [17, 18]],
+        ] {
+            set.insert(mosaic);
+            hash_set.insert(mosaic);
+        }
+        assert_eq!(hash_set.len(), set.len());
+        hash_set.assert_distinct();
+
+        for side in Side::iter() {
+            let by_edge = BTreeMap::from_iter(
+                set.iter_by_edge(side)
+                    .map(|(edge, mosaics)| (edge, BTreeSet::from_iter(mosaics))),
+            );
+            let hash_by_edge = BTreeMap::from_iter(
+                hash_set
+                    .iter_by_edge(side)
+                    .map(|(edge, mosaics)| (edge, BTreeSet::from_iter(mosaics))),
+            );
+            assert_eq!(hash_by_edge, by_edge);
+
+            for edge in by_edge.keys() {
+                assert_eq!(
+                    BTreeSet::from_iter(hash_set.query(side, edge)),
+                    BTreeSet::from_iter(set.query(side, edge)),
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,296 @@
+//! Tile definitions loaded at runtime, for edge-matching puzzles other than
+//! Eternity II.
+//!
+//! Code that works with any puzzle takes a [`TileColors`]: [`EternityIiTiles`]
+//! reads the built-in table at no cost, and a [`TileSet`] reads a loaded one.
+//! The `emit_problem` binary and
+//! [`build_1x1_sets_for`](crate::set::build_1x1_sets_for) do. Mosaics and the
+//! set builders still read colors through [`Tile::color`], so the larger
+//! mosaic builds only run on the built-in tiles.
+
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, BufRead};
+
+use crate::{Color, ExteriorMask, RotatedTile, Side, Tile, TileClass};
+
+/// A source of tile colors.
+pub trait TileColors {
+    /// Returns the number of tiles. Valid tiles are numbered from zero.
+    fn tile_count(&self) -> usize;
+
+    /// Returns the colors on all four sides of `tile`, in [`Side`] order.
+    ///
+    /// # Panics
+    ///
+    /// May panic if `tile` is not less than [`Self::tile_count`].
+    fn colors(&self, tile: Tile) -> [Color; 4];
+
+    fn color(&self, tile: Tile, side: Side) -> Color {
+        self.colors(tile)[side.to_primitive() as usize]
+    }
+
+    /// Returns every tile, in order.
+    fn tiles(&self) -> Vec<Tile> {
+        Vec::from_iter((0..self.tile_count()).map(|tile| Tile::from_primitive(tile as u8)))
+    }
+
+    /// Like [`RotatedTile::colors`], but reading this source's colors.
+    fn rotated_colors(&self, rotated_tile: RotatedTile) -> [Color; 4] {
+        let colors = self.colors(rotated_tile.tile);
+        [Side::Right, Side::Top, Side::Left, Side::Bottom].map(|side| {
+            colors[side.reverse_transform(rotated_tile.rotation).to_primitive() as usize]
+        })
+    }
+
+    /// Like [`RotatedTile::color`], but reading this source's colors.
+    fn rotated_color(&self, rotated_tile: RotatedTile, side: Side) -> Color {
+        self.color(
+            rotated_tile.tile,
+            side.reverse_transform(rotated_tile.rotation),
+        )
+    }
+
+    /// Like [`RotatedTile::exterior_mask`], but reading this source's colors.
+    fn exterior_mask(&self, rotated_tile: RotatedTile) -> ExteriorMask {
+        let [right, top, left, bottom] = self.rotated_colors(rotated_tile);
+        ExteriorMask::zero()
+            .with_right(right == Color::EXTERIOR)
+            .with_top(top == Color::EXTERIOR)
+            .with_left(left == Color::EXTERIOR)
+            .with_bottom(bottom == Color::EXTERIOR)
+    }
+
+    /// Like [`RotatedTile::class`], but reading this source's colors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tile's exterior sides can't appear on a rectangular
+    /// board, such as two opposite exterior sides.
+    fn class(&self, rotated_tile: RotatedTile) -> TileClass {
+        let mask = self.exterior_mask(rotated_tile);
+        assert!(
+            mask.is_valid_tile_mask(),
+            "{rotated_tile:?} has an impossible exterior mask {mask:?}",
+        );
+        match mask.to_primitive().count_ones() {
+            0 => TileClass::Center,
+            1 => TileClass::Edge,
+            _ => TileClass::Corner,
+        }
+    }
+}
+
+/// The built-in Eternity II tiles, as read by [`Tile::color`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EternityIiTiles;
+
+impl TileColors for EternityIiTiles {
+    fn tile_count(&self) -> usize {
+        256
+    }
+
+    fn colors(&self, tile: Tile) -> [Color; 4] {
+        tile.colors()
+    }
+}
+
+/// A table of tile colors.
+///
+/// [`Tile`] is eight bits wide, so a set holds at most 256 tiles.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TileSet {
+    colors: Vec<[Color; 4]>,
+}
+
+impl TileSet {
+    pub const MAX_TILES: usize = 256;
+
+    /// Returns the built-in Eternity II tiles as a loaded set.
+    pub fn eternity_ii() -> Self {
+        Self {
+            colors: Vec::from_iter(Tile::values().map(Tile::colors)),
+        }
+    }
+
+    /// Parses one tile per line, each as four color characters in [`Side`]
+    /// order: right, top, left, bottom. Blank lines and surrounding whitespace
+    /// are ignored.
+    ///
+    /// Tiles whose exterior sides can't appear on a rectangular board, such as
+    /// two opposite exterior sides, are rejected.
+    pub fn from_reader<R: BufRead>(r: R) -> Result<Self, TileSetParseError> {
+        let mut colors = Vec::new();
+        for (line_index, line) in r.lines().enumerate() {
+            let line = line.map_err(TileSetParseError::Io)?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let line_number = line_index + 1;
+            let Ok(bytes) = <&[u8; 4]>::try_from(line.as_bytes()) else {
+                return Err(TileSetParseError::WrongLength {
+                    line: line_number,
+                    len: line.len(),
+                });
+            };
+            if colors.len() == Self::MAX_TILES {
+                return Err(TileSetParseError::TooManyTiles);
+            }
+            let mut tile = [Color::EXTERIOR; 4];
+            for (index, &byte) in bytes.iter().enumerate() {
+                tile[index] =
+                    Color::from_byte_char(byte).ok_or(TileSetParseError::InvalidColor {
+                        line: line_number,
+                        index,
+                        byte,
+                    })?;
+            }
+            let [right, top, left, bottom] = tile;
+            let mask = ExteriorMask::zero()
+                .with_right(right == Color::EXTERIOR)
+                .with_top(top == Color::EXTERIOR)
+                .with_left(left == Color::EXTERIOR)
+                .with_bottom(bottom == Color::EXTERIOR);
+            if !mask.is_valid_tile_mask() {
+                return Err(TileSetParseError::ImpossibleExterior { line: line_number });
+            }
+            colors.push(tile);
+        }
+        Ok(Self { colors })
+    }
+}
+
+impl TileColors for TileSet {
+    fn tile_count(&self) -> usize {
+        self.colors.len()
+    }
+
+    fn colors(&self, tile: Tile) -> [Color; 4] {
+        self.colors[tile.to_primitive() as usize]
+    }
+}
+
+#[derive(Debug)]
+pub enum TileSetParseError {
+    Io(io::Error),
+    WrongLength {
+        line: usize,
+        len: usize,
+    },
+    InvalidColor {
+        line: usize,
+        index: usize,
+        byte: u8,
+    },
+    /// The tile's exterior sides can't all face off a rectangular board.
+    ImpossibleExterior {
+        line: usize,
+    },
+    /// There are more than [`TileSet::MAX_TILES`] tiles.
+    TooManyTiles,
+}
+
+impl Display for TileSetParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "reading tile set: {e}"),
+            Self::WrongLength { line, len } => {
+                write!(f, "line {line} has {len} characters, expected 4")
+            }
+            Self::InvalidColor { line, index, byte } => write!(
+                f,
+                "line {line} position {index} has invalid color {:?}",
+                byte.escape_ascii().to_string(),
+            ),
+            Self::ImpossibleExterior { line } => write!(
+                f,
+                "line {line} has exterior sides that can't all face off the board",
+            ),
+            Self::TooManyTiles => write!(f, "more than {} tiles", TileSet::MAX_TILES),
+        }
+    }
+}
+
+impl std::error::Error for TileSetParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Color, RotatedTile, Side, Tile};
+
+    use super::{EternityIiTiles, TileColors, TileSet, TileSetParseError};
+
+    #[test]
+    fn eternity_ii_matches_builtin_tiles() {
+        let tile_set = TileSet::eternity_ii();
+        assert_eq!(tile_set.tile_count(), EternityIiTiles.tile_count());
+        for tile in Tile::values() {
+            assert_eq!(tile_set.colors(tile), tile.colors());
+            assert_eq!(
+                tile_set.color(tile, Side::Top),
+                EternityIiTiles.color(tile, Side::Top),
+            );
+        }
+        assert_eq!(tile_set.tiles(), Vec::from_iter(Tile::values()));
+        for rotated_tile in RotatedTile::values() {
+            assert_eq!(tile_set.rotated_colors(rotated_tile), rotated_tile.colors());
+            for side in [Side::Right, Side::Top, Side::Left, Side::Bottom] {
+                assert_eq!(
+                    tile_set.rotated_color(rotated_tile, side),
+                    rotated_tile.color(side),
+                );
+            }
+            assert_eq!(
+                tile_set.exterior_mask(rotated_tile),
+                rotated_tile.exterior_mask(),
+            );
+            assert_eq!(tile_set.class(rotated_tile), rotated_tile.class());
+        }
+    }
+
+    #[test]
+    fn from_reader() {
+        let tile_set = TileSet::from_reader(&b"jaar\n\n  fajt \nbafv\n"[..]).unwrap();
+        assert_eq!(tile_set.tile_count(), 3);
+        assert_eq!(
+            Vec::from_iter((0..3).map(|tile| tile_set.colors(Tile::from_primitive(tile)))),
+            Vec::from_iter(Tile::values().take(3).map(Tile::colors)),
+        );
+        assert_eq!(
+            tile_set.color(Tile::from_primitive(1), Side::Right),
+            Color::F,
+        );
+    }
+
+    #[test]
+    fn from_reader_errors() {
+        assert!(matches!(
+            TileSet::from_reader(&b"jaar\njaa\n"[..]),
+            Err(TileSetParseError::WrongLength { line: 2, len: 3 }),
+        ));
+        assert!(matches!(
+            TileSet::from_reader(&b"jaaz\n"[..]),
+            Err(TileSetParseError::InvalidColor {
+                line: 1,
+                index: 3,
+                byte: b'z',
+            }),
+        ));
+        for line in ["abab", "aaaa", "jaaa"] {
+            assert!(matches!(
+                TileSet::from_reader(format!("jaar\n{line}\n").as_bytes()),
+                Err(TileSetParseError::ImpossibleExterior { line: 2 }),
+            ));
+        }
+        assert!(matches!(
+            TileSet::from_reader("jaar\n".repeat(257).as_bytes()),
+            Err(TileSetParseError::TooManyTiles),
+        ));
+    }
+}
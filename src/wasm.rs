@@ -0,0 +1,29 @@
+//! Entry points for rendering boards in a browser.
+//!
+//! A board crosses into JavaScript as its bucas.name `board_edges` string,
+//! which is compact and already understood elsewhere.
+
+use wasm_bindgen::prelude::*;
+
+use crate::mosaic::{self, from_bucas_board_edges, to_bucas_board_edges};
+
+/// The width of each cell in units of [`render_svg`]'s output.
+const CELL_PX: u32 = 32;
+
+/// Parses a bucas.name `board_edges` string, returning the board serialized
+/// as a normalized `board_edges` string.
+#[wasm_bindgen]
+pub fn board_from_bucas(s: &str) -> Result<JsValue, JsError> {
+    let board = from_bucas_board_edges(s)?;
+    Ok(JsValue::from_str(&to_bucas_board_edges(&board)))
+}
+
+/// Renders a board returned by [`board_from_bucas`] as an inline SVG document.
+#[wasm_bindgen]
+pub fn render_svg(board: &JsValue) -> Result<String, JsError> {
+    let board = board
+        .as_string()
+        .ok_or_else(|| JsError::new("expected a board from board_from_bucas"))?;
+    let board = from_bucas_board_edges(&board)?;
+    Ok(mosaic::render_svg(&board, CELL_PX))
+}